@@ -0,0 +1,5 @@
+DROP INDEX shinies_uuid;
+ALTER TABLE shinies DROP COLUMN uuid;
+
+DROP INDEX hunts_uuid;
+ALTER TABLE hunts DROP COLUMN uuid;
@@ -0,0 +1,7 @@
+ALTER TABLE hunts ADD COLUMN uuid TEXT NOT NULL DEFAULT '';
+UPDATE hunts SET uuid = lower(hex(randomblob(16)));
+CREATE UNIQUE INDEX hunts_uuid ON hunts(uuid);
+
+ALTER TABLE shinies ADD COLUMN uuid TEXT NOT NULL DEFAULT '';
+UPDATE shinies SET uuid = lower(hex(randomblob(16)));
+CREATE UNIQUE INDEX shinies_uuid ON shinies(uuid);
@@ -0,0 +1,9 @@
+CREATE TABLE counters (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    position INTEGER NOT NULL,
+    hunt_id INTEGER REFERENCES hunts(id) ON DELETE SET NULL,
+    increment INTEGER NOT NULL DEFAULT 1,
+    count INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT INTO counters (position) VALUES (0), (1), (2), (3);
@@ -0,0 +1,10 @@
+CREATE TABLE sessions (
+    id INTEGER NOT NULL PRIMARY KEY AUTOINCREMENT,
+    hunt_id INTEGER NOT NULL REFERENCES hunts(id) ON DELETE CASCADE,
+    phase INTEGER NOT NULL,
+    start_time TIMESTAMP NOT NULL,
+    end_time TIMESTAMP NOT NULL,
+    encounters INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE INDEX sessions_hunt_id ON sessions(hunt_id);
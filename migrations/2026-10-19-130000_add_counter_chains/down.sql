@@ -0,0 +1,4 @@
+ALTER TABLE counters DROP COLUMN broken_chains;
+ALTER TABLE counters DROP COLUMN best_chain;
+ALTER TABLE counters DROP COLUMN chain;
+ALTER TABLE counters DROP COLUMN mode;
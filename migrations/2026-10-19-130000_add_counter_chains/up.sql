@@ -0,0 +1,4 @@
+ALTER TABLE counters ADD COLUMN mode TEXT NOT NULL DEFAULT 'normal';
+ALTER TABLE counters ADD COLUMN chain INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE counters ADD COLUMN best_chain INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE counters ADD COLUMN broken_chains INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1 @@
+DROP TABLE hunt_targets;
@@ -0,0 +1,7 @@
+-- Targets of a hunt besides the main one stored in hunts.target
+CREATE TABLE hunt_targets (
+    hunt_id INTEGER NOT NULL REFERENCES hunts(id) ON DELETE CASCADE,
+    species INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    PRIMARY KEY (hunt_id, species)
+);
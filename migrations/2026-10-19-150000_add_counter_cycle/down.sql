@@ -0,0 +1 @@
+ALTER TABLE counters DROP COLUMN cycle_ms;
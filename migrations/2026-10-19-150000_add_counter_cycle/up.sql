@@ -0,0 +1 @@
+ALTER TABLE counters ADD COLUMN cycle_ms INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,4 @@
+ALTER TABLE hunts DROP COLUMN alert_sound;
+ALTER TABLE hunts DROP COLUMN alert_probabilities;
+ALTER TABLE hunts DROP COLUMN alert_base_odds;
+ALTER TABLE hunts DROP COLUMN alert_every;
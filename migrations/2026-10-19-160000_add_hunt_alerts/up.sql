@@ -0,0 +1,5 @@
+-- Alerts shown while counting the encounters of a hunt
+ALTER TABLE hunts ADD COLUMN alert_every INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE hunts ADD COLUMN alert_base_odds BOOLEAN NOT NULL DEFAULT 1;
+ALTER TABLE hunts ADD COLUMN alert_probabilities TEXT NOT NULL DEFAULT '50,90,99';
+ALTER TABLE hunts ADD COLUMN alert_sound BOOLEAN NOT NULL DEFAULT 0;
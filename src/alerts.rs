@@ -0,0 +1,211 @@
+use crate::odds;
+
+const ALERT_SOUND: &[u8] = include_bytes!("../assets/alert.wav");
+
+/// Alerts configured on a hunt, triggered by the encounters of its phase
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HuntAlerts {
+    /// Alert every given number of encounters, never when 0
+    pub every: i32,
+    /// Alert when passing the base odds of the game
+    pub base_odds: bool,
+    /// Cumulative probabilities of having found the shiny, in percent
+    pub probabilities: Vec<u32>,
+    pub sound: bool,
+}
+
+impl Default for HuntAlerts {
+    fn default() -> Self {
+        Self {
+            every: 0,
+            base_odds: true,
+            probabilities: vec![50, 90, 99],
+            sound: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alert {
+    Milestone(i32),
+    BaseOdds(i32),
+    Probability(u32),
+}
+
+/// An alert shown over the counters until dismissed
+#[derive(Debug, Clone)]
+pub struct Banner {
+    pub hunt: String,
+    pub alert: Alert,
+}
+
+/// Number of banners shown at once, the oldest ones being dropped
+pub const MAX_BANNERS: usize = 5;
+
+impl HuntAlerts {
+    /// Reads a list of percentages separated by commas or spaces, which must
+    /// be between 1 and 99.
+    pub fn parse_probabilities(text: &str) -> Option<Vec<u32>> {
+        let mut probabilities = text
+            .split(|c: char| c == ',' || c == ';' || c.is_whitespace())
+            .filter(|value| !value.is_empty())
+            .map(|value| {
+                value
+                    .trim_end_matches('%')
+                    .parse()
+                    .ok()
+                    .filter(|p| (1..=99).contains(p))
+            })
+            .collect::<Option<Vec<u32>>>()?;
+        probabilities.sort_unstable();
+        probabilities.dedup();
+        Some(probabilities)
+    }
+
+    pub fn probabilities_text(&self) -> String {
+        self.probabilities
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<String>>()
+            .join(", ")
+    }
+
+    /// Alerts whose threshold was passed when the encounters went from
+    /// `before` to `after`, at the given odds
+    pub fn crossed(&self, odds: f64, before: i32, after: i32) -> Vec<Alert> {
+        let passed = |threshold: i32| before < threshold && threshold <= after;
+        let mut alerts = vec![];
+        if self.every > 0 && after / self.every > before.max(0) / self.every {
+            alerts.push(Alert::Milestone(after / self.every * self.every));
+        }
+        let base_odds = odds.round() as i32;
+        if self.base_odds && passed(base_odds) {
+            alerts.push(Alert::BaseOdds(base_odds));
+        }
+        alerts.extend(
+            self.probabilities
+                .iter()
+                .filter(|p| {
+                    passed(odds::encounters_for_probability(odds, **p as f64 / 100.0).ceil() as i32)
+                })
+                .map(|p| Alert::Probability(*p)),
+        );
+        alerts
+    }
+}
+
+impl std::fmt::Display for Alert {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Alert::Milestone(encounters) => write!(f, "{encounters} rencontres atteintes"),
+            Alert::BaseOdds(odds) => write!(f, "Cote de base de 1/{odds} dépassée"),
+            Alert::Probability(p) => write!(f, "{p} % de chances d'avoir trouvé le shiny"),
+        }
+    }
+}
+
+/// Plays the alert sound with the audio player of the system, without
+/// waiting for it to finish. Nothing is played when no player is available.
+pub fn play_sound() {
+    use std::process::{Command, Stdio};
+
+    // The file is written every time so that a file left there by anything
+    // else isn't played. Writing fails while the sound is still playing on
+    // some systems, which is fine as long as the file holds our sound.
+    let path = std::env::temp_dir().join("shutils-alert.wav");
+    if std::fs::write(&path, ALERT_SOUND).is_err()
+        && std::fs::read(&path).ok().as_deref() != Some(ALERT_SOUND)
+    {
+        return;
+    }
+    let players: Vec<Command> = if cfg!(target_os = "macos") {
+        let mut afplay = Command::new("afplay");
+        afplay.arg(&path);
+        vec![afplay]
+    } else if cfg!(target_os = "windows") {
+        // The path goes through the environment so that it's never read
+        // as part of the command
+        let mut powershell = Command::new("powershell");
+        powershell
+            .args([
+                "-NoProfile",
+                "-Command",
+                "(New-Object Media.SoundPlayer $env:SHUTILS_ALERT_SOUND).PlaySync()",
+            ])
+            .env("SHUTILS_ALERT_SOUND", &path);
+        vec![powershell]
+    } else {
+        let mut paplay = Command::new("paplay");
+        paplay.arg(&path);
+        let mut aplay = Command::new("aplay");
+        aplay.arg("-q").arg(&path);
+        vec![paplay, aplay]
+    };
+    for mut player in players {
+        if let Ok(mut child) = player.stdout(Stdio::null()).stderr(Stdio::null()).spawn() {
+            std::thread::spawn(move || child.wait());
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_probabilities_sorts_and_dedups() {
+        assert_eq!(
+            HuntAlerts::parse_probabilities("90, 50%;99 50"),
+            Some(vec![50, 90, 99])
+        );
+        assert_eq!(HuntAlerts::parse_probabilities(""), Some(vec![]));
+    }
+
+    #[test]
+    fn parse_probabilities_rejects_invalid_percentages() {
+        assert_eq!(HuntAlerts::parse_probabilities("0"), None);
+        assert_eq!(HuntAlerts::parse_probabilities("100"), None);
+        assert_eq!(HuntAlerts::parse_probabilities("50, beaucoup"), None);
+    }
+
+    #[test]
+    fn crossed_reports_the_thresholds_passed() {
+        let alerts = HuntAlerts {
+            every: 1000,
+            base_odds: true,
+            probabilities: vec![50, 90],
+            sound: false,
+        };
+        assert_eq!(alerts.crossed(4096.0, 998, 999), vec![]);
+        assert_eq!(
+            alerts.crossed(4096.0, 999, 1000),
+            vec![Alert::Milestone(1000)]
+        );
+        assert_eq!(
+            alerts.crossed(4096.0, 2838, 2839),
+            vec![Alert::Probability(50)]
+        );
+        assert_eq!(
+            alerts.crossed(4096.0, 3999, 4096),
+            vec![Alert::Milestone(4000), Alert::BaseOdds(4096)]
+        );
+    }
+
+    #[test]
+    fn crossed_reports_every_threshold_of_a_big_step() {
+        let alerts = HuntAlerts {
+            every: 0,
+            ..HuntAlerts::default()
+        };
+        assert_eq!(
+            alerts.crossed(4096.0, 0, 20000),
+            vec![
+                Alert::BaseOdds(4096),
+                Alert::Probability(50),
+                Alert::Probability(90),
+                Alert::Probability(99),
+            ]
+        );
+    }
+}
@@ -0,0 +1,448 @@
+use crate::hunt::Hunt;
+use crate::shiny::Shiny;
+
+use chrono::{DateTime, Datelike, Local, Months, NaiveDate, TimeDelta};
+use iced::alignment::{Horizontal, Vertical};
+use iced::mouse::{self, Cursor};
+use iced::widget::canvas::{self, event, Frame, Geometry, Path, Stroke, Text};
+use iced::{Color, Point, Rectangle, Renderer, Size, Theme};
+
+use std::collections::HashMap;
+
+const LINE_COLOR: Color = Color::from_rgb(0.537, 0.627, 0.820);
+const MARKER_COLOR: Color = Color::from_rgb(0.941, 0.784, 0.314);
+const AXIS_COLOR: Color = Color::from_rgba(0.941, 0.941, 0.941, 0.4);
+const LABEL_COLOR: Color = Color::from_rgb(0.941, 0.941, 0.941);
+
+/// Space kept around the plot for the labels of the axes
+const MARGIN_LEFT: f32 = 48.0;
+const MARGIN_BOTTOM: f32 = 24.0;
+const MARGIN: f32 = 8.0;
+
+fn label(content: String, position: Point, horizontal: Horizontal, vertical: Vertical) -> Text {
+    Text {
+        content,
+        position,
+        color: LABEL_COLOR,
+        size: 12.into(),
+        horizontal_alignment: horizontal,
+        vertical_alignment: vertical,
+        ..Text::default()
+    }
+}
+
+/// Area of the frame where values are drawn, and its axes
+fn plot_area(frame: &mut Frame, max: f64) -> Rectangle {
+    let area = Rectangle {
+        x: MARGIN_LEFT,
+        y: MARGIN,
+        width: (frame.width() - MARGIN_LEFT - MARGIN).max(0.0),
+        height: (frame.height() - MARGIN - MARGIN_BOTTOM).max(0.0),
+    };
+    frame.stroke(
+        &Path::new(|path| {
+            path.move_to(Point::new(area.x, area.y));
+            path.line_to(Point::new(area.x, area.y + area.height));
+            path.line_to(Point::new(area.x + area.width, area.y + area.height));
+        }),
+        Stroke::default().with_color(AXIS_COLOR).with_width(1.0),
+    );
+    frame.fill_text(label(
+        format!("{max:.0}"),
+        Point::new(area.x - 6.0, area.y),
+        Horizontal::Right,
+        Vertical::Top,
+    ));
+    frame.fill_text(label(
+        "0".into(),
+        Point::new(area.x - 6.0, area.y + area.height),
+        Horizontal::Right,
+        Vertical::Bottom,
+    ));
+    area
+}
+
+/// Cumulative encounters of a hunt over time, with a marker for each shiny
+#[derive(Debug, Clone)]
+pub struct EncountersChart {
+    points: Vec<(DateTime<Local>, i32)>,
+    shinies: Vec<(DateTime<Local>, i32)>,
+}
+
+impl EncountersChart {
+    /// Encounters are known over time through the hunting sessions. Those
+    /// counted outside of a session are placed at the start of the hunt.
+    pub fn of_hunt(hunt: &Hunt) -> Self {
+        let total = hunt.previous_encounters + hunt.phase_encounters;
+        let mut sessions: Vec<_> = hunt.sessions.iter().collect();
+        sessions.sort_by_key(|session| session.end_time);
+
+        let start = hunt
+            .start_time
+            .or(sessions.first().map(|session| session.start_time));
+        let end = hunt.end_time.unwrap_or(Local::now());
+        let mut count = (total - sessions.iter().map(|s| s.encounters).sum::<i32>()).max(0);
+        let mut points: Vec<(DateTime<Local>, i32)> =
+            start.map(|start| (start, count)).into_iter().collect();
+        for session in sessions {
+            count += session.encounters;
+            points.push((session.end_time, count));
+        }
+        points.push((end, total.max(count)));
+
+        Self {
+            points,
+            shinies: hunt
+                .shinies
+                .iter()
+                .filter_map(|shiny| Some((shiny.found_time?, shiny.total_encounters?)))
+                .collect(),
+        }
+    }
+}
+
+impl<Message> canvas::Program<Message> for EncountersChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let values = self.points.iter().chain(&self.shinies);
+        let max = values
+            .clone()
+            .map(|(_, count)| *count)
+            .max()
+            .unwrap_or(0)
+            .max(1) as f64;
+        let (Some(first), Some(last)) = (
+            values.clone().map(|(time, _)| *time).min(),
+            values.map(|(time, _)| *time).max(),
+        ) else {
+            return vec![frame.into_geometry()];
+        };
+        let area = plot_area(&mut frame, max);
+        let span = (last - first).num_seconds().max(1) as f32;
+        let position = |(time, count): &(DateTime<Local>, i32)| {
+            Point::new(
+                area.x + area.width * (*time - first).num_seconds() as f32 / span,
+                area.y + area.height * (1.0 - *count as f32 / max as f32),
+            )
+        };
+
+        frame.stroke(
+            &Path::new(|path| {
+                for (i, point) in self.points.iter().enumerate() {
+                    if i == 0 {
+                        path.move_to(position(point));
+                    } else {
+                        path.line_to(position(point));
+                    }
+                }
+            }),
+            Stroke::default().with_color(LINE_COLOR).with_width(2.0),
+        );
+        for shiny in &self.shinies {
+            frame.fill(&Path::circle(position(shiny), 5.0), MARKER_COLOR);
+        }
+
+        let bottom = area.y + area.height + 4.0;
+        frame.fill_text(label(
+            first.format("%d/%m/%Y").to_string(),
+            Point::new(area.x, bottom),
+            Horizontal::Left,
+            Vertical::Top,
+        ));
+        frame.fill_text(label(
+            last.format("%d/%m/%Y").to_string(),
+            Point::new(area.x + area.width, bottom),
+            Horizontal::Right,
+            Vertical::Top,
+        ));
+        vec![frame.into_geometry()]
+    }
+}
+
+/// Values over consecutive periods, such as days or months
+#[derive(Debug, Clone)]
+pub struct BarChart {
+    bars: Vec<(String, f64)>,
+}
+
+impl BarChart {
+    /// Encounters of every hunt's sessions, for each of the last `days` days
+    pub fn encounters_per_day(hunts: &[Hunt], days: u32) -> Self {
+        let today = Local::now().date_naive();
+        let bars = (0..days as i64)
+            .rev()
+            .map(|days_ago| {
+                let day = today - TimeDelta::days(days_ago);
+                let encounters: i32 = hunts
+                    .iter()
+                    .flat_map(|hunt| &hunt.sessions)
+                    .filter(|session| session.start_time.date_naive() == day)
+                    .map(|session| session.encounters)
+                    .sum();
+                (day.format("%d/%m").to_string(), encounters as f64)
+            })
+            .collect();
+        Self { bars }
+    }
+
+    /// Shinies found during each of the last `months` months
+    pub fn shinies_per_month(shinies: &[Shiny], months: u32) -> Self {
+        let today = Local::now().date_naive();
+        let this_month = NaiveDate::from_ymd_opt(today.year(), today.month(), 1).unwrap();
+        let bars = (0..months)
+            .rev()
+            .map(|months_ago| {
+                let month = this_month - Months::new(months_ago);
+                let count = shinies
+                    .iter()
+                    .filter_map(|shiny| shiny.found_time)
+                    .filter(|time| time.year() == month.year() && time.month() == month.month())
+                    .count();
+                (month.format("%m/%y").to_string(), count as f64)
+            })
+            .collect();
+        Self { bars }
+    }
+}
+
+impl<Message> canvas::Program<Message> for BarChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        if self.bars.is_empty() {
+            return vec![frame.into_geometry()];
+        }
+        let max = self
+            .bars
+            .iter()
+            .map(|(_, value)| *value)
+            .fold(1.0, f64::max);
+        let area = plot_area(&mut frame, max);
+        let slot = area.width / self.bars.len() as f32;
+        // Labels are skipped when there are too many bars for them to fit
+        let label_every = (self.bars.len() / 10).max(1);
+
+        for (i, (name, value)) in self.bars.iter().enumerate() {
+            let height = area.height * (*value / max) as f32;
+            let x = area.x + slot * i as f32;
+            frame.fill(
+                &Path::rectangle(
+                    Point::new(x + slot * 0.15, area.y + area.height - height),
+                    Size::new(slot * 0.7, height),
+                ),
+                LINE_COLOR,
+            );
+            if i % label_every == 0 {
+                frame.fill_text(label(
+                    name.clone(),
+                    Point::new(x + slot / 2.0, area.y + area.height + 4.0),
+                    Horizontal::Center,
+                    Vertical::Top,
+                ));
+            }
+        }
+        vec![frame.into_geometry()]
+    }
+}
+
+/// Activity of each day of the last year, one column per week
+pub struct CalendarHeatmap<Message> {
+    /// Encounters and shinies found on each day
+    days: HashMap<NaiveDate, (i32, usize)>,
+    /// Monday of the first week shown
+    start: NaiveDate,
+    today: NaiveDate,
+    selected: Option<NaiveDate>,
+    on_select: fn(NaiveDate) -> Message,
+}
+
+const HEATMAP_TOP: f32 = 16.0;
+const HEATMAP_LEFT: f32 = 24.0;
+
+impl<Message> CalendarHeatmap<Message> {
+    pub fn new(
+        hunts: &[Hunt],
+        shinies: &[Shiny],
+        selected: Option<NaiveDate>,
+        on_select: fn(NaiveDate) -> Message,
+    ) -> Self {
+        let today = Local::now().date_naive();
+        let year_ago = today - TimeDelta::days(364);
+        let start = year_ago - TimeDelta::days(year_ago.weekday().num_days_from_monday() as i64);
+
+        let mut days: HashMap<NaiveDate, (i32, usize)> = HashMap::new();
+        for session in hunts.iter().flat_map(|hunt| &hunt.sessions) {
+            days.entry(session.start_time.date_naive()).or_default().0 += session.encounters;
+        }
+        for found_time in shinies.iter().filter_map(|shiny| shiny.found_time) {
+            days.entry(found_time.date_naive()).or_default().1 += 1;
+        }
+
+        Self {
+            days,
+            start,
+            today,
+            selected,
+            on_select,
+        }
+    }
+
+    fn weeks(&self) -> i64 {
+        (self.today - self.start).num_days() / 7 + 1
+    }
+
+    fn cell_size(&self, bounds: Size) -> f32 {
+        ((bounds.width - HEATMAP_LEFT) / self.weeks() as f32)
+            .min((bounds.height - HEATMAP_TOP) / 7.0)
+    }
+
+    fn day_at(&self, position: Point, bounds: Size) -> Option<NaiveDate> {
+        let cell = self.cell_size(bounds);
+        let week = ((position.x - HEATMAP_LEFT) / cell).floor();
+        let weekday = ((position.y - HEATMAP_TOP) / cell).floor();
+        if !(0.0..7.0).contains(&weekday) || !(0.0..self.weeks() as f32).contains(&week) {
+            return None;
+        }
+        let day = self.start + TimeDelta::days(week as i64 * 7 + weekday as i64);
+        (day <= self.today).then_some(day)
+    }
+}
+
+impl<Message> canvas::Program<Message> for CalendarHeatmap<Message> {
+    type State = ();
+
+    fn update(
+        &self,
+        _state: &mut (),
+        event: canvas::Event,
+        bounds: Rectangle,
+        cursor: Cursor,
+    ) -> (event::Status, Option<Message>) {
+        match (event, cursor.position_in(bounds)) {
+            (
+                canvas::Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)),
+                Some(position),
+            ) => match self.day_at(position, bounds.size()) {
+                Some(day) => (event::Status::Captured, Some((self.on_select)(day))),
+                None => (event::Status::Ignored, None),
+            },
+            _ => (event::Status::Ignored, None),
+        }
+    }
+
+    fn draw(
+        &self,
+        _state: &(),
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let cell = self.cell_size(bounds.size());
+        let max = self
+            .days
+            .iter()
+            .filter(|(day, _)| **day >= self.start)
+            .map(|(_, (encounters, _))| *encounters)
+            .max()
+            .unwrap_or(0)
+            .max(1) as f32;
+
+        for (weekday, name) in [(0, "L"), (2, "M"), (4, "V")] {
+            frame.fill_text(label(
+                name.into(),
+                Point::new(
+                    HEATMAP_LEFT - 6.0,
+                    HEATMAP_TOP + cell * (weekday as f32 + 0.5),
+                ),
+                Horizontal::Right,
+                Vertical::Center,
+            ));
+        }
+
+        let mut day = self.start;
+        while day <= self.today {
+            let offset = (day - self.start).num_days();
+            let position = Point::new(
+                HEATMAP_LEFT + cell * (offset / 7) as f32,
+                HEATMAP_TOP + cell * (offset % 7) as f32,
+            );
+            if day.day() == 1 {
+                frame.fill_text(label(
+                    day.format_localized("%b", chrono::Locale::fr_FR)
+                        .to_string(),
+                    Point::new(position.x, 0.0),
+                    Horizontal::Left,
+                    Vertical::Top,
+                ));
+            }
+
+            let (encounters, shinies) = self.days.get(&day).copied().unwrap_or_default();
+            // Square root so that quieter days remain visible next to the
+            // busiest one
+            let intensity = (encounters as f32 / max).sqrt();
+            let color = if encounters > 0 {
+                Color {
+                    a: 0.25 + 0.75 * intensity,
+                    ..LINE_COLOR
+                }
+            } else {
+                AXIS_COLOR.scale_alpha(0.3)
+            };
+            let square = Path::rectangle(
+                Point::new(position.x + 1.0, position.y + 1.0),
+                Size::new(cell - 2.0, cell - 2.0),
+            );
+            frame.fill(&square, color);
+            if shinies > 0 {
+                frame.fill(
+                    &Path::circle(
+                        Point::new(position.x + cell / 2.0, position.y + cell / 2.0),
+                        cell / 5.0,
+                    ),
+                    MARKER_COLOR,
+                );
+            }
+            if self.selected == Some(day) {
+                frame.stroke(
+                    &square,
+                    Stroke::default().with_color(LABEL_COLOR).with_width(2.0),
+                );
+            }
+            day += TimeDelta::days(1);
+        }
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &(),
+        bounds: Rectangle,
+        cursor: Cursor,
+    ) -> mouse::Interaction {
+        match cursor.position_in(bounds) {
+            Some(position) if self.day_at(position, bounds.size()).is_some() => {
+                mouse::Interaction::Pointer
+            }
+            _ => mouse::Interaction::default(),
+        }
+    }
+}
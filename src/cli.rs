@@ -0,0 +1,448 @@
+use crate::data::Species;
+use crate::hunt::Hunt;
+use crate::report::ReportFormat;
+use crate::session::Session;
+use crate::settings::Settings;
+use crate::shiny::Shiny;
+use crate::stats::Statistics;
+
+use chrono::{DateTime, Local};
+use diesel::{Connection, SqliteConnection};
+use serde::Serialize;
+
+use std::collections::HashMap;
+use std::error::Error;
+
+type CliResult = Result<(), Box<dyn Error + Send + Sync>>;
+
+const USAGE: &str = "\
+Utilisation : SHUtils [commande]
+Sans commande, lance l'interface graphique.
+
+Commandes :
+  hunt list [--all] [--json]
+      Liste les recherches en cours, ou toutes avec --all
+  hunt inc <id> [n] [--json]
+      Ajoute n rencontres (1 par défaut, négatif pour retirer) à la recherche
+  shiny add <espèce> [--hunt <id> [--continue]] [--name <surnom>] [--gender <f|m>]
+            [--encounters <n>] [--phase-encounters <n>] [--phase <n>]
+            [--version <v>] [--method <m>] [--place <zone>] [--notes <notes>] [--json]
+      Ajoute un shiny ; avec --hunt, termine la recherche et reprend ses compteurs,
+      ou avec --continue, la poursuit pour ses autres cibles
+  export <hunts|shinies> [--format <csv|json|markdown|bbcode>]
+      Exporte les recherches ou les shinies (CSV par défaut)
+  stats [--json]
+      Affiche les statistiques globales
+
+L'interface graphique relit une recherche avant d'y compter des rencontres,
+mais ses autres modifications (éditeur, outil de fusion) peuvent écraser
+celles faites ici pendant qu'elle tourne.";
+
+/// Options that are flags, every other `--option` expects a value
+const FLAGS: [&str; 4] = ["all", "continue", "help", "json"];
+
+struct Args {
+    positional: Vec<String>,
+    options: HashMap<String, String>,
+}
+
+impl Args {
+    fn parse(args: &[String]) -> Result<Self, Box<dyn Error + Send + Sync>> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        let mut iter = args.iter();
+        while let Some(arg) = iter.next() {
+            match arg.strip_prefix("--") {
+                Some(name) if FLAGS.contains(&name) => {
+                    options.insert(name.to_string(), String::new());
+                }
+                Some(name) => match iter.next() {
+                    Some(value) => {
+                        options.insert(name.to_string(), value.clone());
+                    }
+                    None => return Err(format!("L'option --{name} attend une valeur").into()),
+                },
+                None => positional.push(arg.clone()),
+            }
+        }
+        Ok(Args {
+            positional,
+            options,
+        })
+    }
+
+    fn flag(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    fn option(&self, name: &str) -> Option<String> {
+        self.options.get(name).cloned()
+    }
+
+    fn number(&self, name: &str) -> Result<Option<i32>, Box<dyn Error + Send + Sync>> {
+        match self.options.get(name) {
+            Some(value) => {
+                Ok(Some(value.parse().map_err(|_| {
+                    format!("--{name} attend un nombre, pas « {value} »")
+                })?))
+            }
+            None => Ok(None),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct HuntRecord {
+    id: Option<i32>,
+    uuid: String,
+    species: i32,
+    species_name: String,
+    total_encounters: i32,
+    phase_encounters: i32,
+    phase: i32,
+    completed: bool,
+    start_time: Option<DateTime<Local>>,
+    end_time: Option<DateTime<Local>>,
+    version: Option<String>,
+    method: Option<String>,
+    place: Option<String>,
+    notes: Option<String>,
+}
+
+impl From<&Hunt> for HuntRecord {
+    fn from(hunt: &Hunt) -> Self {
+        HuntRecord {
+            id: hunt.id,
+            uuid: hunt.uuid.clone(),
+            species: hunt.target.into(),
+            species_name: hunt.targets_name(),
+            total_encounters: hunt.previous_encounters + hunt.phase_encounters,
+            phase_encounters: hunt.phase_encounters,
+            phase: hunt.phase_count,
+            completed: hunt.completed,
+            start_time: hunt.start_time,
+            end_time: hunt.end_time,
+            version: hunt.version.clone(),
+            method: hunt.method.clone(),
+            place: hunt.place.clone(),
+            notes: hunt.notes.clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct ShinyRecord {
+    id: Option<i32>,
+    uuid: String,
+    species: i32,
+    species_name: String,
+    name: Option<String>,
+    gender: Option<i32>,
+    total_encounters: Option<i32>,
+    phase_encounters: Option<i32>,
+    phase: Option<i32>,
+    found_time: Option<DateTime<Local>>,
+    version: Option<String>,
+    method: Option<String>,
+    place: Option<String>,
+    notes: Option<String>,
+    hunt_id: Option<i32>,
+}
+
+impl From<&Shiny> for ShinyRecord {
+    fn from(shiny: &Shiny) -> Self {
+        ShinyRecord {
+            id: shiny.id,
+            uuid: shiny.uuid.clone(),
+            species: shiny.species.into(),
+            species_name: shiny.species.to_string(),
+            name: shiny.name.clone(),
+            gender: shiny.gender,
+            total_encounters: shiny.total_encounters,
+            phase_encounters: shiny.phase_encounters,
+            phase: shiny.phase_number,
+            found_time: shiny.found_time,
+            version: shiny.version.clone(),
+            method: shiny.method.clone(),
+            place: shiny.place.clone(),
+            notes: shiny.notes.clone(),
+            hunt_id: shiny.hunt_id,
+        }
+    }
+}
+
+fn print_json(value: &impl Serialize) -> CliResult {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+fn print_hunt(hunt: &Hunt) {
+    println!(
+        "{:>4}  {:<20} {:>7} {:>7} {:>5}  {}{}",
+        hunt.id.unwrap_or_default(),
+        hunt.targets_name(),
+        hunt.phase_encounters,
+        hunt.previous_encounters + hunt.phase_encounters,
+        hunt.phase_count,
+        [&hunt.version, &hunt.method, &hunt.place]
+            .iter()
+            .map(|field| field.as_deref().unwrap_or("?"))
+            .collect::<Vec<_>>()
+            .join(" / "),
+        if hunt.completed { " (terminée)" } else { "" }
+    );
+}
+
+fn hunt_list(args: &Args, db: &mut SqliteConnection) -> CliResult {
+    let hunts: Vec<Hunt> = Hunt::get_all(db)?
+        .into_iter()
+        .filter(|hunt| args.flag("all") || !hunt.completed)
+        .collect();
+
+    if args.flag("json") {
+        return print_json(&hunts.iter().map(HuntRecord::from).collect::<Vec<_>>());
+    }
+    println!(
+        "{:>4}  {:<20} {:>7} {:>7} {:>5}  Version / Méthode / Zone",
+        "id", "Espèce", "Phase", "Total", "N°"
+    );
+    for hunt in &hunts {
+        print_hunt(hunt);
+    }
+    Ok(())
+}
+
+fn hunt_inc(args: &Args, db: &mut SqliteConnection) -> CliResult {
+    let id: i32 = args
+        .positional
+        .get(2)
+        .ok_or("Il faut préciser l'id de la recherche")?
+        .parse()
+        .map_err(|_| "L'id de la recherche doit être un nombre")?;
+    let amount: i32 = match args.positional.get(3) {
+        Some(amount) => amount
+            .parse()
+            .map_err(|_| "Le nombre de rencontres doit être un nombre")?,
+        None => 1,
+    };
+
+    if !Hunt::add_encounters(id, amount, db)? {
+        return Err(format!("Aucune recherche n°{id}").into());
+    }
+    let hunt = Hunt::get_by_id(id, db)?;
+    if amount > 0 {
+        // Dates the encounters for the charts and goals, like the counters do
+        let now = Local::now();
+        let session = Session {
+            id: None,
+            hunt_id: id,
+            phase: hunt.phase_count,
+            start_time: now,
+            end_time: now,
+            encounters: amount,
+        };
+        session.upsert(db)?;
+    }
+
+    if args.flag("json") {
+        print_json(&HuntRecord::from(&hunt))
+    } else {
+        print_hunt(&hunt);
+        Ok(())
+    }
+}
+
+fn shiny_add(args: &Args, db: &mut SqliteConnection) -> CliResult {
+    let species_name = args
+        .positional
+        .get(2)
+        .ok_or("Il faut préciser l'espèce du shiny")?;
+    let species =
+        Species::from_name(species_name).ok_or(format!("Espèce inconnue : {species_name}"))?;
+
+    let (mut shiny, hunt) = match args.number("hunt")? {
+        Some(hunt_id) => {
+            let mut hunt = Hunt::get_by_id(hunt_id, db)
+                .map_err(|_| format!("Aucune recherche n°{hunt_id}"))?;
+            let shiny = hunt.shiny_found(species, Local::now(), args.flag("continue"));
+            (shiny, Some(hunt))
+        }
+        None => (
+            Shiny {
+                found_time: Some(Local::now()),
+                ..Shiny::default()
+            },
+            None,
+        ),
+    };
+    shiny.species = species;
+    shiny.name = args.option("name").or(shiny.name);
+    shiny.gender = match args.option("gender").as_deref() {
+        Some("f") => Some(0),
+        Some("m") => Some(1),
+        Some(other) => return Err(format!("Sexe inconnu : {other} (f ou m)").into()),
+        None => shiny.gender,
+    };
+    shiny.total_encounters = args.number("encounters")?.or(shiny.total_encounters);
+    shiny.phase_encounters = args.number("phase-encounters")?.or(shiny.phase_encounters);
+    shiny.phase_number = args.number("phase")?.or(shiny.phase_number);
+    shiny.version = args.option("version").or(shiny.version);
+    shiny.method = args.option("method").or(shiny.method);
+    shiny.place = args.option("place").or(shiny.place);
+    shiny.notes = args.option("notes").or(shiny.notes);
+
+    // The hunt and its shiny are saved together or not at all
+    let db_shiny = db.transaction(|db| {
+        if let Some(hunt) = &hunt {
+            hunt.upsert(db)?;
+        }
+        shiny.upsert(db)
+    })?;
+    let shiny = Shiny::from_db_shiny(db_shiny);
+    if args.flag("json") {
+        print_json(&ShinyRecord::from(&shiny))
+    } else {
+        println!(
+            "Shiny n°{} ajouté : {}",
+            shiny.id.unwrap_or_default(),
+            shiny
+        );
+        Ok(())
+    }
+}
+
+fn export(args: &Args, db: &mut SqliteConnection) -> CliResult {
+    let format = args.option("format").unwrap_or("csv".into());
+    let report_format = match format.as_str() {
+        "markdown" => Some(ReportFormat::Markdown),
+        "bbcode" => Some(ReportFormat::BBCode),
+        "csv" | "json" => None,
+        other => return Err(format!("Format inconnu : {other}").into()),
+    };
+    let templates = Settings::load(db)?.report_templates;
+
+    match args.positional.get(1).map(|s| s.as_str()) {
+        Some("hunts") => {
+            let hunts = Hunt::get_all(db)?;
+            if let Some(report_format) = report_format {
+                println!("{}", templates.hunts_report(hunts.iter(), report_format));
+                return Ok(());
+            }
+            let records: Vec<HuntRecord> = hunts.iter().map(HuntRecord::from).collect();
+            if format == "json" {
+                print_json(&records)
+            } else {
+                write_csv(&records)
+            }
+        }
+        Some("shinies") => {
+            let shinies = Shiny::get_all(db)?;
+            if let Some(report_format) = report_format {
+                println!(
+                    "{}",
+                    templates.shinies_report(shinies.iter(), report_format)
+                );
+                return Ok(());
+            }
+            let records: Vec<ShinyRecord> = shinies.iter().map(ShinyRecord::from).collect();
+            if format == "json" {
+                print_json(&records)
+            } else {
+                write_csv(&records)
+            }
+        }
+        _ => Err("Il faut préciser quoi exporter : hunts ou shinies".into()),
+    }
+}
+
+fn write_csv(records: &[impl Serialize]) -> CliResult {
+    let mut writer = csv::Writer::from_writer(std::io::stdout());
+    for record in records {
+        writer.serialize(record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn stats(args: &Args, db: &mut SqliteConnection) -> CliResult {
+    let statistics = Statistics::compute(&Hunt::get_all(db)?, &Shiny::get_all(db)?);
+
+    if args.flag("json") {
+        return print_json(&statistics);
+    }
+    let format_mean = |value: Option<f64>| value.map(|v| format!("{v:.0}")).unwrap_or("-".into());
+    println!("Shinies : {}", statistics.shiny_count);
+    println!(
+        "Recherches : {} dont {} en cours",
+        statistics.hunt_count, statistics.active_hunt_count
+    );
+    println!("Rencontres : {}", statistics.total_encounters);
+    println!(
+        "Rencontres par shiny : {} en moyenne, {} en médiane",
+        format_mean(statistics.mean_encounters_per_shiny),
+        format_mean(statistics.median_encounters_per_shiny)
+    );
+    for (title, breakdowns) in [
+        ("Par version", &statistics.by_version),
+        ("Par méthode", &statistics.by_method),
+        ("Par zone", &statistics.by_place),
+        ("Par année", &statistics.by_year),
+    ] {
+        if breakdowns.is_empty() {
+            continue;
+        }
+        println!("\n{title} :");
+        for breakdown in breakdowns {
+            println!(
+                "  {:<24} {:>4} shinies  {:>8} rencontres en moyenne",
+                breakdown.label,
+                breakdown.shiny_count,
+                format_mean(breakdown.mean_encounters)
+            );
+        }
+    }
+    if let Some(luck_score) = statistics.luck_score {
+        println!("Score de chance : {luck_score:.0}/100");
+    }
+    if let (Some(longest), Some(shortest)) = (&statistics.longest_hunt, &statistics.shortest_hunt) {
+        println!(
+            "\nPlus longue recherche : {} ({} rencontres)",
+            longest.name, longest.encounters
+        );
+        println!(
+            "Plus courte recherche : {} ({} rencontres)",
+            shortest.name, shortest.encounters
+        );
+    }
+    Ok(())
+}
+
+/// Runs the command given on the command line and returns the exit code.
+pub fn run(args: &[String]) -> i32 {
+    let result = Args::parse(args).and_then(|args| {
+        let command: Vec<&str> = args.positional.iter().take(2).map(|s| s.as_str()).collect();
+        if args.flag("help") || command == ["help"] {
+            println!("{USAGE}");
+            return Ok(());
+        }
+
+        let mut db = crate::establish_db_connection();
+        crate::run_migrations(&mut db)?;
+        match command.as_slice() {
+            ["hunt", "list"] => hunt_list(&args, &mut db),
+            ["hunt", "inc"] => hunt_inc(&args, &mut db),
+            ["shiny", "add"] => shiny_add(&args, &mut db),
+            ["export", ..] => export(&args, &mut db),
+            ["stats"] => stats(&args, &mut db),
+            _ => Err(USAGE.into()),
+        }
+    });
+
+    match result {
+        Ok(()) => 0,
+        Err(err) => {
+            eprintln!("{err}");
+            1
+        }
+    }
+}
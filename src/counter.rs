@@ -1,34 +1,209 @@
 use crate::hunt::Hunt;
+use crate::models::Counter as DbCounter;
+use crate::odds::ChainMethod;
+use crate::schema::counters;
+use crate::session::SessionTimer;
+
+use chrono::{DateTime, Local, TimeDelta};
+use diesel::prelude::*;
+
+use std::error::Error;
+
+/// Shortest soft reset cycle, so the automatic increments stay sensible
+pub const MIN_CYCLE_MS: i32 = 1000;
+/// Most automatic increments counted at once when the app falls behind,
+/// for instance after the computer went to sleep
+const MAX_AUTO_RESET_CATCH_UP: i32 = 10;
 
 pub struct Counter {
-    pub hunt: Option<usize>,
+    pub id: Option<i32>,
+    pub hunt: Option<i32>,
     pub inc: i32,
     pub count: i32,
+    pub mode: CounterMode,
+    pub chain: i32,
+    pub best_chain: i32,
+    pub broken_chains: i32,
+    /// Duration of a soft reset, in milliseconds
+    pub cycle_ms: i32,
+    pub timer: SessionTimer,
+    pub soft_reset: SoftResetState,
+}
+
+/// Measure of the cycle and automatic counting of a soft reset counter
+#[derive(Debug, Default, Clone)]
+pub struct SoftResetState {
+    /// Start of the cycle being measured
+    pub measuring_since: Option<DateTime<Local>>,
+    /// When the next automatic increment is due, while it is running
+    pub next_increment: Option<DateTime<Local>>,
+    /// Since when automatic increments are waiting to be saved
+    pub unsaved_since: Option<DateTime<Local>>,
+}
+
+/// How encounters are counted
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CounterMode {
+    #[default]
+    Normal,
+    Chain(ChainMethod),
+    SoftReset,
+}
+
+impl CounterMode {
+    pub fn all() -> Vec<CounterMode> {
+        std::iter::once(CounterMode::Normal)
+            .chain(ChainMethod::ALL.into_iter().map(CounterMode::Chain))
+            .chain(std::iter::once(CounterMode::SoftReset))
+            .collect()
+    }
+
+    fn key(&self) -> &'static str {
+        match self {
+            CounterMode::Normal => "normal",
+            CounterMode::Chain(method) => method.key(),
+            CounterMode::SoftReset => "soft_reset",
+        }
+    }
+
+    fn from_key(key: &str) -> CounterMode {
+        match key {
+            "soft_reset" => CounterMode::SoftReset,
+            _ => ChainMethod::from_key(key)
+                .map(CounterMode::Chain)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl std::fmt::Display for CounterMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CounterMode::Normal => f.write_str("Normal"),
+            CounterMode::Chain(method) => write!(f, "Chaîne : {method}"),
+            CounterMode::SoftReset => f.write_str("Soft reset"),
+        }
+    }
 }
 
 impl Default for Counter {
     fn default() -> Self {
         Self {
+            id: None,
             hunt: None,
             inc: 1,
             count: 0,
+            mode: CounterMode::Normal,
+            chain: 0,
+            best_chain: 0,
+            broken_chains: 0,
+            cycle_ms: 0,
+            timer: SessionTimer::default(),
+            soft_reset: SoftResetState::default(),
         }
     }
 }
 
+#[derive(Debug, AsChangeset, Identifiable, Insertable)]
+#[diesel(table_name = crate::schema::counters)]
+#[diesel(treat_none_as_null = true)]
+pub struct InsertableCounter {
+    pub id: Option<i32>,
+    pub position: i32,
+    pub hunt_id: Option<i32>,
+    pub increment: i32,
+    pub count: i32,
+    pub mode: String,
+    pub chain: i32,
+    pub best_chain: i32,
+    pub broken_chains: i32,
+    pub cycle_ms: i32,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum CounterEditAction {
-    SetHunt(usize),
+    SetHunt(i32),
     UnsetHunt,
     SetIncrement(i32),
     SetCount(i32),
+    SetMode(CounterMode),
+    ResetChains,
+    SetCycle(i32),
 }
 
 impl Counter {
+    pub fn from_db_counter(db_counter: DbCounter) -> Self {
+        Self {
+            id: Some(db_counter.id),
+            hunt: db_counter.hunt_id,
+            inc: db_counter.increment,
+            count: db_counter.count,
+            mode: CounterMode::from_key(&db_counter.mode),
+            chain: db_counter.chain,
+            best_chain: db_counter.best_chain,
+            broken_chains: db_counter.broken_chains,
+            cycle_ms: db_counter.cycle_ms,
+            timer: SessionTimer::default(),
+            soft_reset: SoftResetState::default(),
+        }
+    }
+
+    pub fn copy_into_insertable(&self, position: usize) -> InsertableCounter {
+        InsertableCounter {
+            id: self.id,
+            position: position as i32,
+            hunt_id: self.hunt,
+            increment: self.inc,
+            count: self.count,
+            mode: self.mode.key().to_string(),
+            chain: self.chain,
+            best_chain: self.best_chain,
+            broken_chains: self.broken_chains,
+            cycle_ms: self.cycle_ms,
+        }
+    }
+
+    pub fn get_all(
+        db: &mut SqliteConnection,
+    ) -> Result<Vec<Counter>, Box<dyn Error + Send + Sync>> {
+        Ok(counters::table
+            .order(counters::position)
+            .select(DbCounter::as_select())
+            .load(db)?
+            .into_iter()
+            .map(Counter::from_db_counter)
+            .collect())
+    }
+
+    /// Replaces the stored counters by the given ones, in this order, and
+    /// gives their database id to the new counters.
+    pub fn save_all(
+        all_counters: &mut [Counter],
+        db: &mut SqliteConnection,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        db.transaction(|db| {
+            let kept_ids: Vec<i32> = all_counters.iter().filter_map(|c| c.id).collect();
+            diesel::delete(counters::table.filter(counters::id.ne_all(kept_ids))).execute(db)?;
+            for (position, counter) in all_counters.iter_mut().enumerate() {
+                let insertable = counter.copy_into_insertable(position);
+                let db_counter = diesel::insert_into(counters::table)
+                    .values(&insertable)
+                    .on_conflict(counters::id)
+                    .do_update()
+                    .set(&insertable)
+                    .get_result::<DbCounter>(db)?;
+                counter.id = Some(db_counter.id);
+            }
+            Ok::<(), diesel::result::Error>(())
+        })?;
+
+        Ok(())
+    }
+
     pub fn perform(&mut self, action: CounterEditAction, hunt: Option<&mut Hunt>) {
         match action {
-            CounterEditAction::SetHunt(index) => {
-                self.hunt = Some(index);
+            CounterEditAction::SetHunt(hunt_id) => {
+                self.hunt = Some(hunt_id);
             }
             CounterEditAction::UnsetHunt => {
                 self.hunt = None;
@@ -43,24 +218,133 @@ impl Counter {
                     h.phase_encounters += difference;
                 }
             }
+            CounterEditAction::SetMode(mode) => {
+                self.mode = mode;
+                if mode != CounterMode::SoftReset {
+                    self.soft_reset = SoftResetState::default();
+                }
+            }
+            CounterEditAction::SetCycle(cycle_ms) => {
+                self.cycle_ms = cycle_ms.max(MIN_CYCLE_MS);
+            }
+            CounterEditAction::ResetChains => {
+                self.chain = 0;
+                self.best_chain = 0;
+                self.broken_chains = 0;
+            }
+        }
+    }
+
+    pub fn chain_method(&self) -> Option<ChainMethod> {
+        match self.mode {
+            CounterMode::Chain(method) => Some(method),
+            CounterMode::Normal | CounterMode::SoftReset => None,
+        }
+    }
+
+    pub fn cycle(&self) -> Option<TimeDelta> {
+        (self.cycle_ms > 0).then(|| TimeDelta::milliseconds(self.cycle_ms.max(MIN_CYCLE_MS) as i64))
+    }
+
+    /// Starts measuring a cycle, or ends the measure and keeps its duration
+    pub fn measure_cycle(&mut self, now: DateTime<Local>) {
+        match self.soft_reset.measuring_since.take() {
+            Some(start) => {
+                self.cycle_ms = ((now - start).num_milliseconds() as i32).max(MIN_CYCLE_MS)
+            }
+            None => self.soft_reset.measuring_since = Some(now),
+        }
+    }
+
+    /// Starts or pauses the automatic increments, one per cycle
+    pub fn toggle_auto_reset(&mut self, now: DateTime<Local>) {
+        self.soft_reset.next_increment = match (self.soft_reset.next_increment, self.cycle()) {
+            (None, Some(cycle)) => Some(now + cycle),
+            _ => None,
+        };
+    }
+
+    /// Number of automatic increments due, scheduling the next one. When
+    /// more cycles were missed than can be caught up, the next increment is
+    /// scheduled one cycle from now.
+    pub fn auto_resets_due(&mut self, now: DateTime<Local>) -> i32 {
+        match (self.soft_reset.next_increment, self.cycle()) {
+            (Some(next), Some(cycle)) if next <= now => {
+                let missed = (now - next).num_milliseconds() / cycle.num_milliseconds();
+                if missed < MAX_AUTO_RESET_CATCH_UP as i64 {
+                    self.soft_reset.next_increment = Some(next + cycle * (missed as i32 + 1));
+                    missed as i32 + 1
+                } else {
+                    self.soft_reset.next_increment = Some(now + cycle);
+                    MAX_AUTO_RESET_CATCH_UP
+                }
+            }
+            _ => 0,
+        }
+    }
+
+    /// Resets the current chain, the encounters stay counted in the hunt
+    pub fn break_chain(&mut self) {
+        if self.chain > 0 {
+            self.chain = 0;
+            self.broken_chains += 1;
         }
     }
 
     pub fn increment(&mut self, hunt: Option<&mut Hunt>) {
         self.count += self.inc;
+        if self.chain_method().is_some() {
+            self.chain += self.inc;
+            self.best_chain = self.best_chain.max(self.chain);
+        }
         if let Some(h) = hunt {
             h.phase_encounters += self.inc;
         }
     }
 
     pub fn decrement(&mut self, hunt: Option<&mut Hunt>) {
-        if self.count > 0 {
-            self.count -= 1;
+        self.count = (self.count - self.inc).max(0);
+        if self.chain_method().is_some() {
+            self.chain = (self.chain - self.inc).max(0);
         }
         if let Some(h) = hunt {
-            if h.phase_encounters > 0 {
-                h.phase_encounters -= 1;
-            }
+            h.phase_encounters = (h.phase_encounters - self.inc).max(0);
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use chrono::TimeZone;
+
+    #[test]
+    fn missed_auto_resets_are_caught_up_within_a_limit() {
+        let start = Local.with_ymd_and_hms(2024, 5, 1, 10, 0, 0).unwrap();
+        let mut counter = Counter {
+            cycle_ms: 10_000,
+            ..Counter::default()
+        };
+        counter.toggle_auto_reset(start);
+        assert_eq!(counter.auto_resets_due(start + TimeDelta::seconds(5)), 0);
+        assert_eq!(counter.auto_resets_due(start + TimeDelta::seconds(35)), 3);
+        assert_eq!(
+            counter.soft_reset.next_increment,
+            Some(start + TimeDelta::seconds(40))
+        );
+        let wake_up = start + TimeDelta::hours(8);
+        assert_eq!(counter.auto_resets_due(wake_up), MAX_AUTO_RESET_CATCH_UP);
+        assert_eq!(
+            counter.soft_reset.next_increment,
+            Some(wake_up + TimeDelta::seconds(10))
+        );
+    }
+
+    #[test]
+    fn cycles_are_at_least_a_second_long() {
+        let mut counter = Counter::default();
+        counter.perform(CounterEditAction::SetCycle(10), None);
+        assert_eq!(counter.cycle(), Some(TimeDelta::seconds(1)));
+    }
+}
@@ -1,5 +1,5 @@
 #[repr(i32)]
-#[derive(Debug, Clone, Copy, Default)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub enum Species {
     #[default]
     Egg = 0,
@@ -4132,3 +4132,76 @@ impl std::fmt::Display for Species {
         })
     }
 }
+
+impl Species {
+    /// Every species of the national dex, in order.
+    pub fn all() -> impl Iterator<Item = Species> {
+        (1..=i32::from(Species::Pecharunt)).map(Species::from)
+    }
+
+    /// Generation that introduced the species
+    pub fn generation(&self) -> u8 {
+        match i32::from(*self) {
+            ..=151 => 1,
+            152..=251 => 2,
+            252..=386 => 3,
+            387..=493 => 4,
+            494..=649 => 5,
+            650..=721 => 6,
+            722..=809 => 7,
+            810..=905 => 8,
+            _ => 9,
+        }
+    }
+
+    /// English name of the species, such as "Mr. Mime"
+    pub fn english_name(&self) -> String {
+        let name = match self {
+            Species::NidoranF => "Nidoran♀",
+            Species::NidoranM => "Nidoran♂",
+            Species::Farfetchd => "Farfetch'd",
+            Species::MrMime => "Mr. Mime",
+            Species::HoOh => "Ho-Oh",
+            Species::MimeJr => "Mime Jr.",
+            Species::PorygonZ => "Porygon-Z",
+            Species::TypeNull => "Type: Null",
+            Species::Jangmoo => "Jangmo-o",
+            Species::Hakamoo => "Hakamo-o",
+            Species::Kommoo => "Kommo-o",
+            Species::Sirfetchd => "Sirfetch'd",
+            Species::MrRime => "Mr. Rime",
+            Species::WoChien => "Wo-Chien",
+            Species::ChienPao => "Chien-Pao",
+            Species::TingLu => "Ting-Lu",
+            Species::ChiYu => "Chi-Yu",
+            // The other names of several words only lose their spaces in the
+            // variant names, such as GreatTusk
+            _ => {
+                let mut name = String::new();
+                for c in format!("{self:?}").chars() {
+                    if c.is_uppercase() && !name.is_empty() {
+                        name.push(' ');
+                    }
+                    name.push(c);
+                }
+                return name;
+            }
+        };
+        name.to_string()
+    }
+
+    /// Looks a species up by its national dex number, its displayed name or
+    /// its variant name, ignoring case.
+    pub fn from_name(name: &str) -> Option<Species> {
+        let name = name.trim();
+        if let Ok(number) = name.parse::<i32>() {
+            return Some(Species::from(number))
+                .filter(|species| !matches!(species, Species::Unknown(_)));
+        }
+        let name = name.to_lowercase();
+        Species::all().find(|species| {
+            species.to_string().to_lowercase() == name
+                || format!("{species:?}").to_lowercase() == name
+        })
+    }
+}
@@ -0,0 +1,452 @@
+use crate::hunt::Hunt;
+use crate::shiny::Shiny;
+
+use chrono::NaiveDate;
+
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Order of the cards of a grid
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SortKey {
+    #[default]
+    Added,
+    Date,
+    Encounters,
+    Dex,
+    Name,
+}
+
+impl SortKey {
+    pub const ALL: [SortKey; 5] = [
+        SortKey::Added,
+        SortKey::Date,
+        SortKey::Encounters,
+        SortKey::Dex,
+        SortKey::Name,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            SortKey::Added => "added",
+            SortKey::Date => "date",
+            SortKey::Encounters => "encounters",
+            SortKey::Dex => "dex",
+            SortKey::Name => "name",
+        }
+    }
+}
+
+impl std::fmt::Display for SortKey {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SortKey::Added => "Ordre d'ajout",
+            SortKey::Date => "Date",
+            SortKey::Encounters => "Rencontres",
+            SortKey::Dex => "N° du Pokédex",
+            SortKey::Name => "Nom",
+        })
+    }
+}
+
+/// What can be searched, filtered and sorted in a grid
+pub trait Filterable {
+    /// Every text the search looks into
+    fn search_text(&self) -> String;
+    fn version(&self) -> Option<&str>;
+    fn method(&self) -> Option<&str>;
+    /// Whether a hunt is completed, or a shiny was found during a hunt
+    fn status(&self) -> bool;
+    fn date(&self) -> Option<NaiveDate>;
+    fn encounters(&self) -> i32;
+    fn dex_number(&self) -> i32;
+    fn name(&self) -> String;
+}
+
+impl Filterable for Hunt {
+    fn search_text(&self) -> String {
+        self.targets()
+            .flat_map(|species| [species.to_string(), species.english_name()])
+            .chain(
+                [&self.place, &self.version, &self.method, &self.notes]
+                    .into_iter()
+                    .flatten()
+                    .cloned(),
+            )
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    fn method(&self) -> Option<&str> {
+        self.method.as_deref()
+    }
+
+    fn status(&self) -> bool {
+        self.completed
+    }
+
+    fn date(&self) -> Option<NaiveDate> {
+        self.start_time.map(|time| time.date_naive())
+    }
+
+    fn encounters(&self) -> i32 {
+        self.previous_encounters + self.phase_encounters
+    }
+
+    fn dex_number(&self) -> i32 {
+        i32::from(self.target)
+    }
+
+    fn name(&self) -> String {
+        self.targets_name()
+    }
+}
+
+impl Filterable for Shiny {
+    fn search_text(&self) -> String {
+        [self.species.to_string(), self.species.english_name()]
+            .into_iter()
+            .chain(
+                [
+                    &self.name,
+                    &self.place,
+                    &self.version,
+                    &self.method,
+                    &self.notes,
+                ]
+                .into_iter()
+                .flatten()
+                .cloned(),
+            )
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    fn method(&self) -> Option<&str> {
+        self.method.as_deref()
+    }
+
+    fn status(&self) -> bool {
+        self.hunt_id.is_some()
+    }
+
+    fn date(&self) -> Option<NaiveDate> {
+        self.found_time.map(|time| time.date_naive())
+    }
+
+    fn encounters(&self) -> i32 {
+        self.total_encounters.unwrap_or(0)
+    }
+
+    fn dex_number(&self) -> i32 {
+        i32::from(self.species)
+    }
+
+    fn name(&self) -> String {
+        self.name.clone().unwrap_or(self.species.to_string())
+    }
+}
+
+/// A change of the search, filters or order of a grid
+#[derive(Debug, Clone)]
+pub enum FilterEdit {
+    Search(String),
+    Version(Option<String>),
+    Method(Option<String>),
+    Status(Option<bool>),
+    From(Option<NaiveDate>),
+    To(Option<NaiveDate>),
+    Sort(SortKey),
+    Descending(bool),
+    Reset,
+}
+
+impl FilterEdit {
+    /// Edits made at each key typed, only saved once the grid is left
+    pub fn is_typed(&self) -> bool {
+        matches!(
+            self,
+            FilterEdit::Search(_) | FilterEdit::From(_) | FilterEdit::To(_)
+        )
+    }
+}
+
+/// Search, filters and order of the hunts or shinies grid
+#[derive(Debug, Default, Clone)]
+pub struct GridFilter {
+    pub search: String,
+    pub version: Option<String>,
+    pub method: Option<String>,
+    pub status: Option<bool>,
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    pub sort: SortKey,
+    pub descending: bool,
+}
+
+impl GridFilter {
+    const DATE_FORMAT: &'static str = "%Y-%m-%d";
+
+    fn from_values(values: &HashMap<String, String>, prefix: &str) -> Self {
+        let get = |key: &str| values.get(&format!("{prefix}.{key}"));
+        let date = |key: &str| {
+            get(key).and_then(|date| NaiveDate::parse_from_str(date, Self::DATE_FORMAT).ok())
+        };
+        Self {
+            search: get("search").cloned().unwrap_or_default(),
+            version: get("version").cloned(),
+            method: get("method").cloned(),
+            status: get("status").and_then(|status| status.parse().ok()),
+            from: date("from"),
+            to: date("to"),
+            sort: get("sort")
+                .and_then(|key| SortKey::ALL.into_iter().find(|sort| sort.key() == key))
+                .unwrap_or_default(),
+            descending: get("descending").is_some_and(|descending| descending == "true"),
+        }
+    }
+
+    fn to_values(&self, prefix: &str) -> Vec<(String, String)> {
+        [
+            (
+                "search",
+                Some(self.search.clone()).filter(|s| !s.is_empty()),
+            ),
+            ("version", self.version.clone()),
+            ("method", self.method.clone()),
+            ("status", self.status.map(|status| status.to_string())),
+            (
+                "from",
+                self.from
+                    .map(|date| date.format(Self::DATE_FORMAT).to_string()),
+            ),
+            (
+                "to",
+                self.to
+                    .map(|date| date.format(Self::DATE_FORMAT).to_string()),
+            ),
+            (
+                "sort",
+                Some(self.sort)
+                    .filter(|sort| *sort != SortKey::default())
+                    .map(|sort| sort.key().to_string()),
+            ),
+            ("descending", self.descending.then(|| "true".to_string())),
+        ]
+        .into_iter()
+        .filter_map(|(key, value)| Some((format!("{prefix}.{key}"), value?)))
+        .collect()
+    }
+
+    pub fn apply(&mut self, edit: FilterEdit) {
+        match edit {
+            FilterEdit::Search(search) => self.search = search,
+            FilterEdit::Version(version) => self.version = version,
+            FilterEdit::Method(method) => self.method = method,
+            FilterEdit::Status(status) => self.status = status,
+            FilterEdit::From(from) => self.from = from,
+            FilterEdit::To(to) => self.to = to,
+            FilterEdit::Sort(sort) => self.sort = sort,
+            FilterEdit::Descending(descending) => self.descending = descending,
+            FilterEdit::Reset => *self = Self::default(),
+        }
+    }
+
+    /// Whether some items may be hidden
+    pub fn is_filtering(&self) -> bool {
+        !self.search.trim().is_empty()
+            || self.version.is_some()
+            || self.method.is_some()
+            || self.status.is_some()
+            || self.from.is_some()
+            || self.to.is_some()
+    }
+
+    fn matches(&self, item: &impl Filterable) -> bool {
+        let haystack = item.search_text().to_lowercase();
+        self.search
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| haystack.contains(word))
+            && self
+                .version
+                .as_deref()
+                .is_none_or(|version| item.version() == Some(version))
+            && self
+                .method
+                .as_deref()
+                .is_none_or(|method| item.method() == Some(method))
+            && self.status.is_none_or(|status| item.status() == status)
+            && self
+                .from
+                .is_none_or(|from| item.date().is_some_and(|date| date >= from))
+            && self
+                .to
+                .is_none_or(|to| item.date().is_some_and(|date| date <= to))
+    }
+
+    /// Indices of the matching items, in the chosen order
+    pub fn indices<T: Filterable>(&self, items: &[T]) -> Vec<usize> {
+        let mut indices: Vec<usize> = items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| self.matches(*item))
+            .map(|(index, _)| index)
+            .collect();
+        let compare = |a: &usize, b: &usize| -> Ordering {
+            let (a_item, b_item) = (&items[*a], &items[*b]);
+            match self.sort {
+                SortKey::Added => a.cmp(b),
+                SortKey::Date => a_item.date().cmp(&b_item.date()),
+                SortKey::Encounters => a_item.encounters().cmp(&b_item.encounters()),
+                SortKey::Dex => a_item.dex_number().cmp(&b_item.dex_number()),
+                SortKey::Name => a_item
+                    .name()
+                    .to_lowercase()
+                    .cmp(&b_item.name().to_lowercase()),
+            }
+        };
+        if self.descending {
+            indices.sort_by(|a, b| compare(b, a));
+        } else {
+            indices.sort_by(compare);
+        }
+        indices
+    }
+}
+
+/// Distinct values of a text field of the items, to pick a filter from
+pub fn choices<T>(items: &[T], field: impl Fn(&T) -> Option<&str>) -> Vec<String> {
+    let mut choices: Vec<String> = items
+        .iter()
+        .filter_map(field)
+        .filter(|value| !value.trim().is_empty())
+        .map(String::from)
+        .collect();
+    choices.sort_by_key(|choice| choice.to_lowercase());
+    choices.dedup();
+    choices
+}
+
+/// Remembered search, filters and order of each grid
+#[derive(Debug, Default, Clone)]
+pub struct GridFilters {
+    pub hunts: GridFilter,
+    pub shinies: GridFilter,
+}
+
+impl GridFilters {
+    /// Start of the keys of every filter setting
+    pub const KEY_PREFIX: &'static str = "filter.";
+    const HUNTS_PREFIX: &'static str = "filter.hunts";
+    const SHINIES_PREFIX: &'static str = "filter.shinies";
+
+    pub fn from_values(values: &HashMap<String, String>) -> Self {
+        Self {
+            hunts: GridFilter::from_values(values, Self::HUNTS_PREFIX),
+            shinies: GridFilter::from_values(values, Self::SHINIES_PREFIX),
+        }
+    }
+
+    pub fn to_values(&self) -> Vec<(String, String)> {
+        self.hunts
+            .to_values(Self::HUNTS_PREFIX)
+            .into_iter()
+            .chain(self.shinies.to_values(Self::SHINIES_PREFIX))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Species;
+
+    use chrono::{Local, TimeZone};
+
+    fn shiny(species: Species, day: u32, encounters: i32, version: &str) -> Shiny {
+        Shiny {
+            species,
+            total_encounters: Some(encounters),
+            found_time: Some(Local.with_ymd_and_hms(2024, 5, day, 12, 0, 0).unwrap()),
+            version: Some(version.into()),
+            ..Shiny::default()
+        }
+    }
+
+    fn shinies() -> Vec<Shiny> {
+        vec![
+            shiny(Species::Pikachu, 3, 4000, "Épée"),
+            Shiny {
+                hunt_id: Some(1),
+                ..shiny(Species::Bulbasaur, 1, 120, "Rubis")
+            },
+            shiny(Species::Eevee, 2, 900, "Épée"),
+        ]
+    }
+
+    #[test]
+    fn no_filter_keeps_the_order_of_addition() {
+        assert_eq!(GridFilter::default().indices(&shinies()), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn search_matches_every_word_in_any_language() {
+        let filter = GridFilter {
+            search: "bulbasaur rubis".into(),
+            ..GridFilter::default()
+        };
+        assert_eq!(filter.indices(&shinies()), vec![1]);
+        let filter = GridFilter {
+            search: "ÉVOLI".into(),
+            ..GridFilter::default()
+        };
+        assert_eq!(filter.indices(&shinies()), vec![2]);
+        let mut shinies = shinies();
+        shinies[0].species = Species::MrMime;
+        let filter = GridFilter {
+            search: "mr. mime".into(),
+            ..GridFilter::default()
+        };
+        assert_eq!(filter.indices(&shinies), vec![0]);
+    }
+
+    #[test]
+    fn filters_combine() {
+        let filter = GridFilter {
+            version: Some("Épée".into()),
+            from: NaiveDate::from_ymd_opt(2024, 5, 3),
+            ..GridFilter::default()
+        };
+        assert_eq!(filter.indices(&shinies()), vec![0]);
+        let filter = GridFilter {
+            status: Some(true),
+            ..GridFilter::default()
+        };
+        assert_eq!(filter.indices(&shinies()), vec![1]);
+    }
+
+    #[test]
+    fn sort_keys_order_the_items() {
+        let sorted = |sort, descending| {
+            GridFilter {
+                sort,
+                descending,
+                ..GridFilter::default()
+            }
+            .indices(&shinies())
+        };
+        assert_eq!(sorted(SortKey::Date, false), vec![1, 2, 0]);
+        assert_eq!(sorted(SortKey::Encounters, true), vec![0, 2, 1]);
+        assert_eq!(sorted(SortKey::Dex, false), vec![1, 0, 2]);
+        assert_eq!(sorted(SortKey::Added, true), vec![2, 1, 0]);
+    }
+}
@@ -0,0 +1,409 @@
+use crate::counter::Counter;
+use crate::hunt::Hunt;
+
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeDelta};
+
+use std::collections::{BTreeMap, HashMap};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GoalPeriod {
+    Day,
+    Week,
+}
+
+impl GoalPeriod {
+    pub const ALL: [GoalPeriod; 2] = [GoalPeriod::Day, GoalPeriod::Week];
+
+    fn key(&self) -> &'static str {
+        match self {
+            GoalPeriod::Day => "day",
+            GoalPeriod::Week => "week",
+        }
+    }
+
+    /// First day of the period containing `day`, weeks starting on Monday
+    pub fn start(&self, day: NaiveDate) -> NaiveDate {
+        match self {
+            GoalPeriod::Day => day,
+            GoalPeriod::Week => day - TimeDelta::days(day.weekday().num_days_from_monday() as i64),
+        }
+    }
+
+    pub fn length(&self) -> TimeDelta {
+        match self {
+            GoalPeriod::Day => TimeDelta::days(1),
+            GoalPeriod::Week => TimeDelta::weeks(1),
+        }
+    }
+}
+
+impl std::fmt::Display for GoalPeriod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            GoalPeriod::Day => "Par jour",
+            GoalPeriod::Week => "Par semaine",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GoalMeasure {
+    Encounters,
+    Minutes,
+}
+
+impl GoalMeasure {
+    pub const ALL: [GoalMeasure; 2] = [GoalMeasure::Encounters, GoalMeasure::Minutes];
+
+    fn key(&self) -> &'static str {
+        match self {
+            GoalMeasure::Encounters => "encounters",
+            GoalMeasure::Minutes => "minutes",
+        }
+    }
+}
+
+impl std::fmt::Display for GoalMeasure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            GoalMeasure::Encounters => "rencontres",
+            GoalMeasure::Minutes => "minutes",
+        })
+    }
+}
+
+/// A number of encounters or minutes to reach each day or week, on every
+/// hunt or on a single one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Goal {
+    pub hunt_id: Option<i32>,
+    pub period: GoalPeriod,
+    pub measure: GoalMeasure,
+}
+
+/// A past or current period of a goal
+#[derive(Debug, Clone, Copy)]
+pub struct GoalPeriodResult {
+    pub start: NaiveDate,
+    pub value: i64,
+    pub met: bool,
+}
+
+impl Goal {
+    /// Encounters or minutes of a session
+    fn measure(&self, time: TimeDelta, encounters: i32) -> i64 {
+        match self.measure {
+            GoalMeasure::Encounters => encounters as i64,
+            GoalMeasure::Minutes => time.num_seconds(),
+        }
+    }
+
+    /// Results of every period since the goal was set, the current one last.
+    /// Sessions count in the period they started in, and the running ones of
+    /// the counters in the current period.
+    pub fn history(
+        &self,
+        target: GoalTarget,
+        hunts: &[Hunt],
+        counters: &[Counter],
+        now: DateTime<Local>,
+    ) -> Vec<GoalPeriodResult> {
+        let current = self.period.start(now.date_naive());
+        let mut values: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+        for session in hunts
+            .iter()
+            .filter(|hunt| self.hunt_id.is_none() || hunt.id == self.hunt_id)
+            .flat_map(|hunt| &hunt.sessions)
+        {
+            *values
+                .entry(self.period.start(session.start_time.date_naive()))
+                .or_default() += self.measure(session.duration(), session.encounters);
+        }
+        for counter in counters
+            .iter()
+            .filter(|counter| counter.hunt.is_some())
+            .filter(|counter| self.hunt_id.is_none() || counter.hunt == self.hunt_id)
+        {
+            let (time, encounters) = counter.timer.current(now);
+            *values.entry(current).or_default() += self.measure(time, encounters);
+        }
+
+        let first = self.period.start(target.since).min(current);
+        std::iter::successors(Some(first), |start| {
+            Some(*start + self.period.length()).filter(|next| *next <= current)
+        })
+        .map(|start| {
+            let mut value = values.get(&start).copied().unwrap_or(0);
+            if self.measure == GoalMeasure::Minutes {
+                value /= 60;
+            }
+            GoalPeriodResult {
+                start,
+                value,
+                met: value >= target.target,
+            }
+        })
+        .collect()
+    }
+}
+
+/// Number of consecutive periods where the goal was met, ending with the
+/// current one, or with the previous one while the current one isn't met yet
+pub fn current_streak(history: &[GoalPeriodResult]) -> usize {
+    let Some((current, past)) = history.split_last() else {
+        return 0;
+    };
+    past.iter().rev().take_while(|result| result.met).count() + current.met as usize
+}
+
+pub fn best_streak(history: &[GoalPeriodResult]) -> usize {
+    history
+        .split(|result| !result.met)
+        .map(|streak| streak.len())
+        .max()
+        .unwrap_or(0)
+}
+
+/// Target of a goal, in encounters or minutes per period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GoalTarget {
+    pub target: i64,
+    /// Day the goal was set, its history starts there
+    pub since: NaiveDate,
+}
+
+#[derive(Debug, Default, Clone)]
+pub struct Goals {
+    targets: HashMap<Goal, GoalTarget>,
+}
+
+impl Goals {
+    fn setting_key(goal: &Goal) -> String {
+        format!(
+            "goal.{}.{}.{}",
+            goal.hunt_id
+                .map(|hunt_id| hunt_id.to_string())
+                .unwrap_or("all".into()),
+            goal.period.key(),
+            goal.measure.key()
+        )
+    }
+
+    pub fn from_values(values: &HashMap<String, String>) -> Self {
+        let mut targets = HashMap::new();
+        for (key, value) in values {
+            let Some(rest) = key.strip_prefix("goal.") else {
+                continue;
+            };
+            let mut parts = rest.split('.');
+            // The target, then the day the goal was set
+            let Some((target, since)) = value.split_once('@') else {
+                continue;
+            };
+            let (Some(scope), Some(period), Some(measure), Ok(target), Ok(since)) = (
+                parts.next(),
+                parts.next(),
+                parts.next(),
+                target.parse::<i64>(),
+                NaiveDate::parse_from_str(since, DATE_FORMAT),
+            ) else {
+                continue;
+            };
+            let hunt_id = match scope {
+                "all" => None,
+                hunt_id => match hunt_id.parse() {
+                    Ok(hunt_id) => Some(hunt_id),
+                    Err(_) => continue,
+                },
+            };
+            let (Some(period), Some(measure)) = (
+                GoalPeriod::ALL.into_iter().find(|p| p.key() == period),
+                GoalMeasure::ALL.into_iter().find(|m| m.key() == measure),
+            ) else {
+                continue;
+            };
+            let goal = Goal {
+                hunt_id,
+                period,
+                measure,
+            };
+            targets.insert(goal, GoalTarget { target, since });
+        }
+        Self { targets }
+    }
+
+    pub fn to_values(&self) -> Vec<(String, String)> {
+        self.targets
+            .iter()
+            .map(|(goal, target)| {
+                (
+                    Self::setting_key(goal),
+                    format!("{}@{}", target.target, target.since.format(DATE_FORMAT)),
+                )
+            })
+            .collect()
+    }
+
+    pub fn get(&self, goal: &Goal) -> Option<GoalTarget> {
+        self.targets.get(goal).copied()
+    }
+
+    /// Sets the target of a goal, a new goal starting on `today`
+    pub fn set(&mut self, goal: Goal, target: Option<i64>, today: NaiveDate) {
+        match target.filter(|target| *target > 0) {
+            Some(target) => {
+                self.targets
+                    .entry(goal)
+                    .and_modify(|goal_target| goal_target.target = target)
+                    .or_insert(GoalTarget {
+                        target,
+                        since: today,
+                    });
+            }
+            None => {
+                self.targets.remove(&goal);
+            }
+        };
+    }
+
+    /// Goals of a hunt, or the global ones, sorted by period then measure
+    pub fn of_hunt(&self, hunt_id: Option<i32>) -> Vec<(Goal, GoalTarget)> {
+        GoalPeriod::ALL
+            .into_iter()
+            .flat_map(|period| {
+                GoalMeasure::ALL.into_iter().map(move |measure| Goal {
+                    hunt_id,
+                    period,
+                    measure,
+                })
+            })
+            .filter_map(|goal| Some((goal, self.get(&goal)?)))
+            .collect()
+    }
+
+    /// Every goal, the global ones first
+    pub fn all(&self) -> Vec<(Goal, GoalTarget)> {
+        let mut goals: Vec<(Goal, GoalTarget)> =
+            self.targets.iter().map(|(g, t)| (*g, *t)).collect();
+        goals.sort_by_key(|(goal, _)| {
+            (
+                goal.hunt_id,
+                goal.period == GoalPeriod::Week,
+                goal.measure == GoalMeasure::Minutes,
+            )
+        });
+        goals
+    }
+
+    pub fn remove_hunt(&mut self, hunt_id: i32) {
+        self.targets.retain(|goal, _| goal.hunt_id != Some(hunt_id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::session::Session;
+
+    use chrono::TimeZone;
+
+    fn results(met: &[bool]) -> Vec<GoalPeriodResult> {
+        let start = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        met.iter()
+            .enumerate()
+            .map(|(day, met)| GoalPeriodResult {
+                start: start + TimeDelta::days(day as i64),
+                value: 0,
+                met: *met,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn current_streak_ends_with_the_current_period() {
+        assert_eq!(current_streak(&[]), 0);
+        assert_eq!(current_streak(&results(&[true, false, true, true])), 2);
+        assert_eq!(current_streak(&results(&[true, false, false])), 0);
+    }
+
+    #[test]
+    fn current_streak_waits_for_the_current_period() {
+        assert_eq!(current_streak(&results(&[false, true, true, false])), 2);
+        assert_eq!(current_streak(&results(&[false])), 0);
+    }
+
+    #[test]
+    fn best_streak_is_the_longest_run() {
+        assert_eq!(best_streak(&results(&[])), 0);
+        assert_eq!(
+            best_streak(&results(&[true, true, true, false, true, true])),
+            3
+        );
+    }
+
+    #[test]
+    fn history_starts_on_the_day_the_goal_was_set() {
+        let day = |day| Local.with_ymd_and_hms(2024, 5, day, 20, 0, 0).unwrap();
+        let session = |start: DateTime<Local>, encounters| Session {
+            id: None,
+            hunt_id: 1,
+            phase: 1,
+            start_time: start,
+            end_time: start + TimeDelta::minutes(30),
+            encounters,
+        };
+        let hunt = Hunt {
+            id: Some(1),
+            sessions: vec![session(day(1), 50), session(day(4), 120)],
+            ..Hunt::default()
+        };
+        let goal = Goal {
+            hunt_id: None,
+            period: GoalPeriod::Day,
+            measure: GoalMeasure::Encounters,
+        };
+
+        let target = |since| GoalTarget {
+            target: 100,
+            since: NaiveDate::from_ymd_opt(2024, 5, since).unwrap(),
+        };
+
+        let history = goal.history(target(1), std::slice::from_ref(&hunt), &[], day(5));
+        assert_eq!(history.len(), 5);
+        assert_eq!(history[0].value, 50);
+
+        let history = goal.history(target(3), &[hunt], &[], day(5));
+        let values: Vec<(i64, bool)> = history
+            .iter()
+            .map(|result| (result.value, result.met))
+            .collect();
+        assert_eq!(values, vec![(0, false), (120, true), (0, false)]);
+        assert_eq!(current_streak(&history), 1);
+    }
+
+    #[test]
+    fn settings_keep_the_day_a_goal_was_set() {
+        let goal = Goal {
+            hunt_id: Some(3),
+            period: GoalPeriod::Week,
+            measure: GoalMeasure::Minutes,
+        };
+        let today = NaiveDate::from_ymd_opt(2024, 5, 1).unwrap();
+        let mut goals = Goals::default();
+        goals.set(goal, Some(60), today);
+        goals.set(goal, Some(90), today + TimeDelta::days(1));
+
+        let values: HashMap<String, String> = goals.to_values().into_iter().collect();
+        assert_eq!(values["goal.3.week.minutes"], "90@2024-05-01");
+        let goals = Goals::from_values(&values);
+        assert_eq!(
+            goals.get(&goal),
+            Some(GoalTarget {
+                target: 90,
+                since: today
+            })
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use crate::counter::{Counter, CounterMode};
+use crate::data::Species;
+use crate::hunt::Hunt;
+use crate::shiny::Shiny;
+
+use chrono::{DateTime, Local};
+
+/// Number of changes that can be undone
+const MAX_CHANGES: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Increment,
+    Decrement,
+    SetHunt,
+    SetIncrement,
+    SetCount,
+    SetMode,
+    ResetChains,
+    SetCycle,
+    ChainBroken,
+    ShinyFound,
+}
+
+impl ChangeKind {
+    /// Whether consecutive changes of this kind on the same counter form a
+    /// single change, like the keystrokes in a text input.
+    fn coalesces(&self) -> bool {
+        matches!(
+            self,
+            ChangeKind::SetIncrement | ChangeKind::SetCount | ChangeKind::SetCycle
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CounterSnapshot {
+    pub hunt: Option<i32>,
+    pub inc: i32,
+    pub count: i32,
+    pub mode: CounterMode,
+    pub chain: i32,
+    pub best_chain: i32,
+    pub broken_chains: i32,
+    pub cycle_ms: i32,
+}
+
+impl CounterSnapshot {
+    pub fn of(counter: &Counter) -> Self {
+        Self {
+            hunt: counter.hunt,
+            inc: counter.inc,
+            count: counter.count,
+            mode: counter.mode,
+            chain: counter.chain,
+            best_chain: counter.best_chain,
+            broken_chains: counter.broken_chains,
+            cycle_ms: counter.cycle_ms,
+        }
+    }
+
+    pub fn restore(&self, counter: &mut Counter) {
+        counter.hunt = self.hunt;
+        counter.inc = self.inc;
+        counter.count = self.count;
+        counter.mode = self.mode;
+        counter.chain = self.chain;
+        counter.best_chain = self.best_chain;
+        counter.broken_chains = self.broken_chains;
+        counter.cycle_ms = self.cycle_ms;
+    }
+}
+
+/// The fields of a hunt that counter operations modify
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HuntSnapshot {
+    pub id: i32,
+    pub target: Species,
+    pub other_targets: Vec<Species>,
+    pub previous_encounters: i32,
+    pub phase_encounters: i32,
+    pub phase_count: i32,
+    pub completed: bool,
+    pub end_time: Option<DateTime<Local>>,
+}
+
+impl HuntSnapshot {
+    pub fn of(hunt: &Hunt) -> Option<Self> {
+        Some(Self {
+            id: hunt.id?,
+            target: hunt.target,
+            other_targets: hunt.other_targets.clone(),
+            previous_encounters: hunt.previous_encounters,
+            phase_encounters: hunt.phase_encounters,
+            phase_count: hunt.phase_count,
+            completed: hunt.completed,
+            end_time: hunt.end_time,
+        })
+    }
+
+    pub fn restore(&self, hunt: &mut Hunt) {
+        hunt.target = self.target;
+        hunt.other_targets = self.other_targets.clone();
+        hunt.previous_encounters = self.previous_encounters;
+        hunt.phase_encounters = self.phase_encounters;
+        hunt.phase_count = self.phase_count;
+        hunt.completed = self.completed;
+        hunt.end_time = self.end_time;
+    }
+}
+
+/// State of a counter and of its linked hunt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub counter: CounterSnapshot,
+    pub hunt: Option<HuntSnapshot>,
+}
+
+/// A change made to a counter, identified by its database id so that it
+/// survives the counters being moved around.
+#[derive(Debug, Clone)]
+pub struct Change {
+    pub kind: ChangeKind,
+    pub counter_id: i32,
+    pub before: Snapshot,
+    pub after: Snapshot,
+    /// Shiny recorded by the change, deleted when undoing it
+    pub shiny: Option<Shiny>,
+}
+
+#[derive(Debug, Default)]
+pub struct History {
+    undo: Vec<Change>,
+    redo: Vec<Change>,
+}
+
+impl History {
+    pub fn push(&mut self, change: Change) {
+        if change.before == change.after && change.shiny.is_none() {
+            return;
+        }
+        self.redo.clear();
+        if let Some(last) = self.undo.last_mut() {
+            if change.kind.coalesces()
+                && last.kind == change.kind
+                && last.counter_id == change.counter_id
+            {
+                last.after = change.after;
+                return;
+            }
+        }
+        if self.undo.len() == MAX_CHANGES {
+            self.undo.remove(0);
+        }
+        self.undo.push(change);
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Takes the last change to undo, which must be handed back with
+    /// `undone` once reverted.
+    pub fn take_undo(&mut self) -> Option<Change> {
+        self.undo.pop()
+    }
+
+    pub fn undone(&mut self, change: Change) {
+        self.redo.push(change);
+    }
+
+    /// Takes the last undone change, which must be handed back with `redone`
+    /// once applied again.
+    pub fn take_redo(&mut self) -> Option<Change> {
+        self.redo.pop()
+    }
+
+    pub fn redone(&mut self, change: Change) {
+        self.undo.push(change);
+    }
+
+    /// Forgets every change, used when the data was replaced as a whole
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
@@ -1,18 +1,26 @@
+use crate::alerts::HuntAlerts;
 use crate::data;
-use crate::models::{Hunt as DbHunt, Shiny as DbShiny};
+use crate::models::{Hunt as DbHunt, HuntTarget, Session as DbSession, Shiny as DbShiny};
+use crate::odds;
+use crate::session::{self, Session};
 use crate::shiny::Shiny;
+use crate::sprites::SpriteKey;
 
-use crate::schema::hunts;
+use crate::schema::{hunt_targets, hunts};
 
-use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
+use chrono::{DateTime, Local, NaiveDateTime, TimeDelta, TimeZone};
 use diesel::prelude::*;
+use uuid::Uuid;
 
 use std::error::Error;
 
 #[derive(Debug, Default, Clone)]
 pub struct Hunt {
     pub id: Option<i32>,
+    pub uuid: String,
     pub target: data::Species,
+    /// Species counted in the same encounters besides the main target
+    pub other_targets: Vec<data::Species>,
     pub previous_encounters: i32,
     pub phase_encounters: i32,
     pub phase_count: i32,
@@ -24,12 +32,52 @@ pub struct Hunt {
     pub place: Option<String>,
     pub notes: Option<String>,
     pub shinies: Vec<Shiny>,
+    pub sessions: Vec<Session>,
+    pub alerts: HuntAlerts,
 }
 
+#[derive(Debug, Clone)]
+pub enum HuntEditAction {
+    SetTarget(data::Species),
+    AddTarget(data::Species),
+    RemoveTarget(usize),
+    SetAlertEvery(i32),
+    SetAlertBaseOdds(bool),
+    SetAlertProbabilities(Vec<u32>),
+    SetAlertSound(bool),
+    SetPhaseEncounters(i32),
+    /// Sets the encounters of the previous phases so the total matches
+    SetTotalEncounters(i32),
+    SetPhaseCount(i32),
+    SetCompleted(bool),
+    SetStartTime(Option<DateTime<Local>>),
+    SetEndTime(Option<DateTime<Local>>),
+    SetVersion(Option<String>),
+    SetMethod(Option<String>),
+    SetPlace(Option<String>),
+    SetNotes(Option<String>),
+}
+
+impl HuntEditAction {
+    /// Whether the action sets the same field as `previous`, which then no
+    /// longer matters. The targets are edited as a list, so every one of
+    /// their edits matters.
+    pub fn overwrites(&self, previous: &HuntEditAction) -> bool {
+        !matches!(
+            self,
+            HuntEditAction::SetTarget(_)
+                | HuntEditAction::AddTarget(_)
+                | HuntEditAction::RemoveTarget(_)
+        ) && std::mem::discriminant(self) == std::mem::discriminant(previous)
+    }
+}
+
+// Fields cleared in the editor must be cleared in the database too
 #[derive(Debug, AsChangeset, Identifiable, Insertable)]
-#[diesel(table_name = crate::schema::hunts)]
+#[diesel(table_name = crate::schema::hunts, treat_none_as_null = true)]
 pub struct InsertableHunt {
     pub id: Option<i32>,
+    pub uuid: String,
     pub target: i32,
     pub previous_encounters: i32,
     pub phase_encounters: i32,
@@ -41,13 +89,37 @@ pub struct InsertableHunt {
     pub method: Option<String>,
     pub place: Option<String>,
     pub notes: Option<String>,
+    pub alert_every: i32,
+    pub alert_base_odds: bool,
+    pub alert_probabilities: String,
+    pub alert_sound: bool,
+}
+
+/// Time `encounters` should take at `rate` encounters per hour, when no
+/// encounter was made yet the time is unknown
+fn time_at_rate(encounters: f64, rate: f64) -> Option<TimeDelta> {
+    if !rate.is_finite() || rate <= 0.0 {
+        return None;
+    }
+    TimeDelta::try_seconds((encounters / rate * 3600.0).round() as i64)
 }
 
 impl Hunt {
-    pub fn from_db_hunt_and_shinies(db_hunt: DbHunt, db_shinies: Vec<DbShiny>) -> Self {
+    pub fn from_db_entities(
+        db_hunt: DbHunt,
+        db_shinies: Vec<DbShiny>,
+        db_sessions: Vec<DbSession>,
+        mut db_targets: Vec<HuntTarget>,
+    ) -> Self {
+        db_targets.sort_by_key(|target| target.position);
         Self {
             id: Some(db_hunt.id),
+            uuid: db_hunt.uuid,
             target: db_hunt.target.into(),
+            other_targets: db_targets
+                .into_iter()
+                .map(|target| target.species.into())
+                .collect(),
             previous_encounters: db_hunt.previous_encounters,
             phase_encounters: db_hunt.phase_encounters,
             phase_count: db_hunt.phase_count,
@@ -62,16 +134,29 @@ impl Hunt {
             method: db_hunt.method,
             place: db_hunt.place,
             notes: db_hunt.notes,
-            shinies: db_shinies
+            shinies: db_shinies.into_iter().map(Shiny::from_db_shiny).collect(),
+            sessions: db_sessions
                 .into_iter()
-                .map(|shiny| Shiny::from_db_shiny(shiny))
+                .map(Session::from_db_session)
                 .collect(),
+            alerts: HuntAlerts {
+                every: db_hunt.alert_every,
+                base_odds: db_hunt.alert_base_odds,
+                probabilities: HuntAlerts::parse_probabilities(&db_hunt.alert_probabilities)
+                    .unwrap_or_default(),
+                sound: db_hunt.alert_sound,
+            },
         }
     }
 
     pub fn copy_into_insertable(&self) -> InsertableHunt {
         InsertableHunt {
-            id: self.id.clone(),
+            id: self.id,
+            uuid: if self.uuid.is_empty() {
+                Uuid::new_v4().simple().to_string()
+            } else {
+                self.uuid.clone()
+            },
             target: self.target.into(),
             previous_encounters: self.previous_encounters,
             phase_encounters: self.phase_encounters,
@@ -83,7 +168,224 @@ impl Hunt {
             method: self.method.clone(),
             place: self.place.clone(),
             notes: self.notes.clone(),
+            alert_every: self.alerts.every,
+            alert_base_odds: self.alerts.base_odds,
+            alert_probabilities: self.alerts.probabilities_text(),
+            alert_sound: self.alerts.sound,
+        }
+    }
+
+    /// Main target first, then the other ones
+    pub fn targets(&self) -> impl Iterator<Item = data::Species> + '_ {
+        std::iter::once(self.target).chain(self.other_targets.iter().copied())
+    }
+
+    pub fn targets_name(&self) -> String {
+        self.targets()
+            .map(|species| species.to_string())
+            .collect::<Vec<String>>()
+            .join(" / ")
+    }
+
+    /// Records the shiny of the given species found at the current encounter
+    /// count. The hunt is completed, unless `keep_hunting` is set and other
+    /// targets remain: the species is then removed from the targets and a
+    /// new phase starts.
+    pub fn shiny_found(
+        &mut self,
+        species: data::Species,
+        found_time: DateTime<Local>,
+        keep_hunting: bool,
+    ) -> Shiny {
+        let shiny = Shiny {
+            id: None,
+            species,
+            total_encounters: Some(self.previous_encounters + self.phase_encounters),
+            phase_encounters: Some(self.phase_encounters),
+            phase_number: Some(self.phase_count),
+            found_time: Some(found_time),
+            version: self.version.clone(),
+            method: self.method.clone(),
+            place: self.place.clone(),
+            hunt_id: self.id,
+            ..Shiny::default()
+        };
+
+        if keep_hunting && !self.other_targets.is_empty() {
+            if self.target == species {
+                self.target = self.other_targets.remove(0);
+            } else {
+                self.other_targets.retain(|target| *target != species);
+            }
+            self.previous_encounters += self.phase_encounters;
+            self.phase_encounters = 0;
+            self.phase_count += 1;
+        } else {
+            self.completed = true;
+            self.end_time = Some(found_time);
         }
+        shiny
+    }
+
+    pub fn perform(&mut self, action: HuntEditAction) {
+        match action {
+            HuntEditAction::SetTarget(species) => {
+                self.other_targets.retain(|target| *target != species);
+                self.target = species;
+            }
+            HuntEditAction::AddTarget(species) => {
+                if !self.targets().any(|target| target == species) {
+                    self.other_targets.push(species);
+                }
+            }
+            HuntEditAction::RemoveTarget(index) => {
+                if index < self.other_targets.len() {
+                    self.other_targets.remove(index);
+                }
+            }
+            HuntEditAction::SetAlertEvery(every) => self.alerts.every = every.max(0),
+            HuntEditAction::SetAlertBaseOdds(base_odds) => self.alerts.base_odds = base_odds,
+            HuntEditAction::SetAlertProbabilities(probabilities) => {
+                self.alerts.probabilities = probabilities
+            }
+            HuntEditAction::SetAlertSound(sound) => self.alerts.sound = sound,
+            HuntEditAction::SetPhaseEncounters(count) => self.phase_encounters = count.max(0),
+            HuntEditAction::SetTotalEncounters(count) => {
+                self.previous_encounters = (count - self.phase_encounters).max(0)
+            }
+            HuntEditAction::SetPhaseCount(count) => self.phase_count = count.max(1),
+            HuntEditAction::SetCompleted(completed) => self.completed = completed,
+            HuntEditAction::SetStartTime(time) => self.start_time = time,
+            HuntEditAction::SetEndTime(time) => self.end_time = time,
+            HuntEditAction::SetVersion(version) => self.version = version,
+            HuntEditAction::SetMethod(method) => self.method = method,
+            HuntEditAction::SetPlace(place) => self.place = place,
+            HuntEditAction::SetNotes(notes) => self.notes = notes,
+        }
+    }
+
+    /// Shiny sprite of the main target, in the game of the hunt
+    pub fn sprite_key(&self) -> SpriteKey {
+        SpriteKey::new(self.target, true, self.version.as_deref())
+    }
+
+    /// Odds of each encounter with the version and method of the hunt
+    pub fn odds(&self) -> f64 {
+        odds::method_odds(self.version.as_deref(), self.method.as_deref())
+    }
+
+    /// How lucky the last phase of a completed hunt was
+    pub fn luck_percentile(&self) -> Option<f64> {
+        (self.completed && self.phase_encounters > 0)
+            .then(|| odds::luck_percentile(self.odds(), self.phase_encounters))
+    }
+
+    /// Total time spent on the hunt during the recorded sessions
+    pub fn time_hunted(&self) -> TimeDelta {
+        self.sessions.iter().map(Session::duration).sum()
+    }
+
+    /// Time spent and encounters during the sessions of the current phase
+    pub fn phase_sessions(&self) -> (TimeDelta, i32) {
+        self.sessions
+            .iter()
+            .filter(|session| session.phase == self.phase_count)
+            .fold((TimeDelta::zero(), 0), |(time, encounters), session| {
+                (time + session.duration(), encounters + session.encounters)
+            })
+    }
+
+    /// Encounters per hour over every session of the hunt
+    pub fn encounter_rate(&self) -> Option<f64> {
+        let encounters = self.sessions.iter().map(|session| session.encounters).sum();
+        session::encounter_rate(self.time_hunted(), encounters)
+    }
+
+    /// Encounters left before the probability of having found the shiny in
+    /// this phase reaches `probability`, and the time they should take at
+    /// the usual encounter rate
+    pub fn projection(&self, probability: f64) -> (i32, Option<TimeDelta>) {
+        let needed = odds::encounters_for_probability(self.odds(), probability);
+        let remaining = (needed.ceil() as i32 - self.phase_encounters).max(0);
+        let time = self
+            .encounter_rate()
+            .and_then(|rate| time_at_rate(remaining as f64, rate));
+        (remaining, time)
+    }
+
+    /// Time the shiny is expected to take from now. The odds of each
+    /// encounter don't depend on the previous ones, so this is the time of
+    /// as many encounters as the odds, whatever the progress of the hunt.
+    pub fn expected_time(&self) -> Option<TimeDelta> {
+        let odds = self.odds();
+        self.encounter_rate()
+            .and_then(|rate| time_at_rate(odds, rate))
+    }
+
+    /// Whether the species name, place, version or method contains every
+    /// word of the query, ignoring case.
+    pub fn matches(&self, query: &str) -> bool {
+        let haystack = [
+            Some(self.targets_name()),
+            self.place.clone(),
+            self.version.clone(),
+            self.method.clone(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<String>>()
+        .join(" ")
+        .to_lowercase();
+        query
+            .to_lowercase()
+            .split_whitespace()
+            .all(|word| haystack.contains(word))
+    }
+
+    pub fn upsert(&self, db: &mut SqliteConnection) -> QueryResult<DbHunt> {
+        let insertable = self.copy_into_insertable();
+        db.transaction(|db| {
+            let db_hunt: DbHunt = diesel::insert_into(hunts::table)
+                .values(&insertable)
+                .on_conflict(hunts::id)
+                .do_update()
+                .set(&insertable)
+                .get_result(db)?;
+            diesel::delete(hunt_targets::table.filter(hunt_targets::hunt_id.eq(db_hunt.id)))
+                .execute(db)?;
+            let targets: Vec<HuntTarget> = self
+                .other_targets
+                .iter()
+                .enumerate()
+                .map(|(position, species)| HuntTarget {
+                    hunt_id: db_hunt.id,
+                    species: (*species).into(),
+                    position: position as i32,
+                })
+                .collect();
+            diesel::insert_into(hunt_targets::table)
+                .values(&targets)
+                .execute(db)?;
+            Ok(db_hunt)
+        })
+    }
+
+    /// Adds encounters to the phase of a hunt in a single statement, so
+    /// encounters counted elsewhere in the meantime aren't lost. Returns
+    /// whether the hunt exists.
+    pub fn add_encounters(
+        hunt_id: i32,
+        amount: i32,
+        db: &mut SqliteConnection,
+    ) -> QueryResult<bool> {
+        let phase_encounters =
+            diesel::dsl::sql::<diesel::sql_types::Integer>("MAX(phase_encounters + ")
+                .bind::<diesel::sql_types::Integer, _>(amount)
+                .sql(", 0)");
+        let updated = diesel::update(hunts::table.filter(hunts::id.eq(hunt_id)))
+            .set(hunts::phase_encounters.eq(phase_encounters))
+            .execute(db)?;
+        Ok(updated > 0)
     }
 
     pub fn get_all(db: &mut SqliteConnection) -> Result<Vec<Hunt>, Box<dyn Error + Send + Sync>> {
@@ -92,11 +394,24 @@ impl Hunt {
             .select(DbShiny::as_select())
             .load(db)?;
 
+        let hunts_sessions = DbSession::belonging_to(&all_hunts)
+            .select(DbSession::as_select())
+            .order(crate::schema::sessions::start_time)
+            .load(db)?;
+
+        let hunts_targets = HuntTarget::belonging_to(&all_hunts)
+            .select(HuntTarget::as_select())
+            .load(db)?;
+
         let hunts_with_shinies = hunts_shinies
             .grouped_by(&all_hunts)
             .into_iter()
+            .zip(hunts_sessions.grouped_by(&all_hunts))
+            .zip(hunts_targets.grouped_by(&all_hunts))
             .zip(all_hunts)
-            .map(|(shinies, hunt)| Hunt::from_db_hunt_and_shinies(hunt, shinies))
+            .map(|(((shinies, sessions), targets), hunt)| {
+                Hunt::from_db_entities(hunt, shinies, sessions, targets)
+            })
             .collect();
         Ok(hunts_with_shinies)
     }
@@ -114,7 +429,21 @@ impl Hunt {
             .select(DbShiny::as_select())
             .load(db)?;
 
-        Ok(Hunt::from_db_hunt_and_shinies(db_hunt, hunt_shinies))
+        let hunt_sessions = DbSession::belonging_to(&db_hunt)
+            .select(DbSession::as_select())
+            .order(crate::schema::sessions::start_time)
+            .load(db)?;
+
+        let hunt_targets = HuntTarget::belonging_to(&db_hunt)
+            .select(HuntTarget::as_select())
+            .load(db)?;
+
+        Ok(Hunt::from_db_entities(
+            db_hunt,
+            hunt_shinies,
+            hunt_sessions,
+            hunt_targets,
+        ))
     }
 }
 
@@ -128,3 +457,72 @@ impl std::fmt::Display for Hunt {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hunt_with_session(minutes: i64, encounters: i32) -> Hunt {
+        let start = Local.with_ymd_and_hms(2024, 5, 1, 20, 0, 0).unwrap();
+        Hunt {
+            id: Some(1),
+            phase_count: 1,
+            sessions: vec![Session {
+                id: Some(1),
+                hunt_id: 1,
+                phase: 1,
+                start_time: start,
+                end_time: start + TimeDelta::minutes(minutes),
+                encounters,
+            }],
+            ..Hunt::default()
+        }
+    }
+
+    #[test]
+    fn expected_time_is_unknown_without_any_encounter() {
+        let hunt = hunt_with_session(5, 0);
+        assert_eq!(hunt.expected_time(), None);
+        assert_eq!(hunt.projection(0.5).1, None);
+    }
+
+    #[test]
+    fn expected_time_follows_the_encounter_rate() {
+        // 4096 encounters at 1024 per hour
+        let hunt = hunt_with_session(60, 1024);
+        assert_eq!(hunt.expected_time(), Some(TimeDelta::hours(4)));
+    }
+
+    #[test]
+    fn projection_counts_the_encounters_left_in_the_phase() {
+        let mut hunt = hunt_with_session(60, 1000);
+        hunt.phase_encounters = 1000;
+        let (remaining, time) = hunt.projection(0.5);
+        assert_eq!(remaining, 2839 - 1000);
+        assert_eq!(time, Some(TimeDelta::seconds(6620)));
+        hunt.phase_encounters = 5000;
+        assert_eq!(hunt.projection(0.5).0, 0);
+    }
+
+    #[test]
+    fn luck_uses_the_odds_of_the_method() {
+        let hunt = Hunt {
+            completed: true,
+            phase_encounters: 1366,
+            version: Some("Écarlate".into()),
+            method: Some("Charme chroma".into()),
+            ..Hunt::default()
+        };
+        let percentile = hunt.luck_percentile().unwrap();
+        assert!((percentile - 0.632).abs() < 0.001, "{percentile}");
+    }
+
+    #[test]
+    fn edits_overwrite_those_of_the_same_field_except_targets() {
+        let notes = HuntEditAction::SetNotes(Some("a".into()));
+        assert!(HuntEditAction::SetNotes(None).overwrites(&notes));
+        assert!(!HuntEditAction::SetPlace(None).overwrites(&notes));
+        let target = HuntEditAction::AddTarget(data::Species::Pikachu);
+        assert!(!HuntEditAction::AddTarget(data::Species::Eevee).overwrites(&target));
+    }
+}
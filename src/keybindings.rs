@@ -0,0 +1,264 @@
+use iced::keyboard::key::Named;
+use iced::keyboard::{Key, Modifiers};
+
+use std::collections::HashMap;
+
+/// Named keys that can be used in a binding, the other ones (modifiers,
+/// media keys...) are ignored.
+const NAMED_KEYS: [Named; 40] = [
+    Named::Space,
+    Named::Enter,
+    Named::Tab,
+    Named::Backspace,
+    Named::Delete,
+    Named::Insert,
+    Named::Home,
+    Named::End,
+    Named::PageUp,
+    Named::PageDown,
+    Named::ArrowUp,
+    Named::ArrowDown,
+    Named::ArrowLeft,
+    Named::ArrowRight,
+    Named::Escape,
+    Named::Pause,
+    Named::F1,
+    Named::F2,
+    Named::F3,
+    Named::F4,
+    Named::F5,
+    Named::F6,
+    Named::F7,
+    Named::F8,
+    Named::F9,
+    Named::F10,
+    Named::F11,
+    Named::F12,
+    Named::F13,
+    Named::F14,
+    Named::F15,
+    Named::F16,
+    Named::F17,
+    Named::F18,
+    Named::F19,
+    Named::F20,
+    Named::F21,
+    Named::F22,
+    Named::F23,
+    Named::F24,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyBinding {
+    key: Key,
+    modifiers: Modifiers,
+}
+
+impl KeyBinding {
+    /// Builds a binding out of a key press, or `None` for keys that can't be
+    /// bound on their own such as modifiers.
+    pub fn from_key_press(key: Key, modifiers: Modifiers) -> Option<Self> {
+        let key = match key {
+            Key::Named(named) if NAMED_KEYS.contains(&named) => Key::Named(named),
+            Key::Character(c) => Key::Character(c.to_lowercase().into()),
+            _ => return None,
+        };
+        Some(Self { key, modifiers })
+    }
+
+    pub fn named(named: Named, modifiers: Modifiers) -> Self {
+        Self {
+            key: Key::Named(named),
+            modifiers,
+        }
+    }
+
+    pub fn character(c: &str, modifiers: Modifiers) -> Self {
+        Self {
+            key: Key::Character(c.to_lowercase().into()),
+            modifiers,
+        }
+    }
+}
+
+impl std::fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.control() {
+            f.write_str("Ctrl+")?;
+        }
+        if self.modifiers.alt() {
+            f.write_str("Alt+")?;
+        }
+        if self.modifiers.shift() {
+            f.write_str("Shift+")?;
+        }
+        if self.modifiers.logo() {
+            f.write_str("Super+")?;
+        }
+        match &self.key {
+            Key::Named(named) => write!(f, "{named:?}"),
+            Key::Character(c) => f.write_str(c),
+            Key::Unidentified => f.write_str("?"),
+        }
+    }
+}
+
+impl std::str::FromStr for KeyBinding {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The key itself may be a `+`
+        let (prefix, key) = match s.strip_suffix("++") {
+            Some(prefix) => (prefix, "+"),
+            None if s == "+" => ("", s),
+            None => s.rsplit_once('+').unwrap_or(("", s)),
+        };
+
+        let mut modifiers = Modifiers::empty();
+        for modifier in prefix.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= match modifier {
+                "Ctrl" => Modifiers::CTRL,
+                "Alt" => Modifiers::ALT,
+                "Shift" => Modifiers::SHIFT,
+                "Super" => Modifiers::LOGO,
+                _ => return Err(()),
+            };
+        }
+
+        let key = match NAMED_KEYS.iter().find(|named| format!("{named:?}") == key) {
+            Some(named) => Key::Named(*named),
+            None if key.chars().count() == 1 => Key::Character(key.to_lowercase().into()),
+            None => return Err(()),
+        };
+
+        Ok(Self { key, modifiers })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CounterCommand {
+    Increment,
+    Decrement,
+    ShinyFound,
+    ChainBroken,
+    ToggleAutoReset,
+    Focus,
+}
+
+impl CounterCommand {
+    pub const ALL: [CounterCommand; 6] = [
+        CounterCommand::Increment,
+        CounterCommand::Decrement,
+        CounterCommand::ShinyFound,
+        CounterCommand::ChainBroken,
+        CounterCommand::ToggleAutoReset,
+        CounterCommand::Focus,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            CounterCommand::Increment => "increment",
+            CounterCommand::Decrement => "decrement",
+            CounterCommand::ShinyFound => "shiny_found",
+            CounterCommand::ChainBroken => "chain_broken",
+            CounterCommand::ToggleAutoReset => "toggle_auto_reset",
+            CounterCommand::Focus => "focus",
+        }
+    }
+}
+
+impl std::fmt::Display for CounterCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            CounterCommand::Increment => "Incrémenter",
+            CounterCommand::Decrement => "Décrémenter",
+            CounterCommand::ShinyFound => "Shiny trouvé",
+            CounterCommand::ChainBroken => "Chaîne brisée",
+            CounterCommand::ToggleAutoReset => "Pause du soft reset auto",
+            CounterCommand::Focus => "Sélectionner",
+        })
+    }
+}
+
+/// Keyboard shortcuts of every counter, by counter database id
+#[derive(Debug, Clone, Default)]
+pub struct CounterBindings {
+    bindings: HashMap<i32, HashMap<CounterCommand, KeyBinding>>,
+}
+
+impl CounterBindings {
+    fn setting_key(counter_id: i32, command: CounterCommand) -> String {
+        format!("counter.{}.{}", counter_id, command.key())
+    }
+
+    pub fn from_values(values: &HashMap<String, String>) -> Self {
+        let mut bindings: HashMap<i32, HashMap<CounterCommand, KeyBinding>> = HashMap::new();
+        for (key, value) in values {
+            let Some(rest) = key.strip_prefix("counter.") else {
+                continue;
+            };
+            let Some((counter_id, command)) = rest.split_once('.') else {
+                continue;
+            };
+            let (Ok(counter_id), Some(command), Ok(binding)) = (
+                counter_id.parse::<i32>(),
+                CounterCommand::ALL.into_iter().find(|c| c.key() == command),
+                value.parse::<KeyBinding>(),
+            ) else {
+                continue;
+            };
+            bindings
+                .entry(counter_id)
+                .or_default()
+                .insert(command, binding);
+        }
+        Self { bindings }
+    }
+
+    pub fn to_values(&self) -> Vec<(String, String)> {
+        self.bindings
+            .iter()
+            .flat_map(|(counter_id, commands)| {
+                commands.iter().map(|(command, binding)| {
+                    (
+                        Self::setting_key(*counter_id, *command),
+                        binding.to_string(),
+                    )
+                })
+            })
+            .collect()
+    }
+
+    pub fn get(&self, counter_id: i32, command: CounterCommand) -> Option<&KeyBinding> {
+        self.bindings.get(&counter_id)?.get(&command)
+    }
+
+    /// Binds the key to the command, removing it from any other command so
+    /// that a key press never triggers two of them.
+    pub fn set(&mut self, counter_id: i32, command: CounterCommand, binding: Option<KeyBinding>) {
+        if let Some(binding) = &binding {
+            for commands in self.bindings.values_mut() {
+                commands.retain(|_, other| other != binding);
+            }
+        }
+        let commands = self.bindings.entry(counter_id).or_default();
+        match binding {
+            Some(binding) => commands.insert(command, binding),
+            None => commands.remove(&command),
+        };
+    }
+
+    pub fn remove_counter(&mut self, counter_id: i32) {
+        self.bindings.remove(&counter_id);
+    }
+
+    /// Finds the counter and command bound to the key press
+    pub fn find(&self, binding: &KeyBinding) -> Option<(i32, CounterCommand)> {
+        self.bindings.iter().find_map(|(counter_id, commands)| {
+            commands
+                .iter()
+                .find(|(_, other)| *other == binding)
+                .map(|(command, _)| (*counter_id, *command))
+        })
+    }
+}
@@ -1,36 +1,62 @@
 use std::error::Error;
 use std::fs;
 use std::path::PathBuf;
+use std::time::Duration;
 
-use iced::widget::{button, center, column, container, mouse_area, opaque, row, stack, text};
-use iced::{Color, Element, Fill, Task};
+use iced::keyboard::key::Named;
+use iced::keyboard::{self, Key, Modifiers};
+use iced::widget::{
+    button, center, column, container, mouse_area, opaque, row, stack, text, vertical_space,
+};
+use iced::{window, Color, Element, Fill, Length, Subscription, Task};
 
-use diesel::{Connection, RunQueryDsl};
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl};
 
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 
-use dirs;
-
+use counter::CounterEditAction;
+use history::{Change, ChangeKind, CounterSnapshot, History, HuntSnapshot, Snapshot};
+use keybindings::{CounterCommand, KeyBinding};
 use screens::counters::CountersAction;
+use screens::merge::MergeAction;
 use screens::shinies::ShiniesAction;
+use screens::templates::TemplatesAction;
 use screens::{
-    Counters, CountersMessage, Hunts, HuntsAction, HuntsMessage, ScreenType, Shinies,
-    ShiniesMessage,
+    Counters, CountersMessage, Dex, DexMessage, Hunts, HuntsAction, HuntsMessage, MergeMessage,
+    MergeTool, ScreenType, Shinies, ShiniesMessage, Stats, StatsMessage, TemplatesEditor,
+    TemplatesMessage,
 };
-use theme::navbar;
+use theme::{card, navbar};
 
+pub mod alerts;
+pub mod charts;
+pub mod cli;
 pub mod counter;
 pub mod data;
+pub mod filters;
+pub mod goals;
+pub mod history;
 pub mod hunt;
+pub mod keybindings;
+pub mod merge;
 pub mod models;
+pub mod odds;
+pub mod report;
 pub mod schema;
+pub mod session;
+pub mod settings;
 pub mod shiny;
+pub mod sprites;
+pub mod stats;
 pub mod theme;
 
 mod screens;
 
 const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
 
+/// Longest time automatic soft resets stay counted without being saved
+const AUTO_RESET_SAVE_DELAY: chrono::TimeDelta = chrono::TimeDelta::seconds(30);
+
 fn run_migrations(
     connection: &mut impl MigrationHarness<diesel::sqlite::Sqlite>,
 ) -> Result<(), Box<dyn Error + Send + Sync + 'static>> {
@@ -57,59 +83,131 @@ fn main() -> iced::Result {
     if let Err(err) = fs::create_dir_all(get_database_path().parent().unwrap()) {
         panic!("Creating database directory failed: {}", err);
     }
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
     iced::application("SHUtils", State::update, State::view)
         .theme(theme::make_theme)
-        .run_with(|| {
-            let (mut state, task) = State::new();
-            if let Err(err) = run_migrations(&mut state.db_connection) {
-                panic!("Database upgrade failed: {}", err)
-            };
-            (state, task)
-        })
+        .subscription(State::subscription)
+        .exit_on_close_request(false)
+        .run_with(State::new)
 }
 
 #[derive(Debug, Clone)]
 enum Message {
-    MenuMessage(MenuMessage),
-    CountersMessage(CountersMessage),
-    HuntsMessage(HuntsMessage),
-    ShiniesMessage(ShiniesMessage),
+    Menu(MenuMessage),
+    Counters(CountersMessage),
+    Hunts(HuntsMessage),
+    Shinies(ShiniesMessage),
+    Stats(StatsMessage),
+    Dex(DexMessage),
+    Templates(TemplatesMessage),
+    Merge(MergeMessage),
+    KeyPressed(Key, Modifiers),
+    Tick,
+    CloseRequested(window::Id),
 }
 
 #[derive(Debug, Clone, Copy)]
 enum MenuMessage {
     ChangeScreen(ScreenType),
+    OpenMergeTool,
+    ResolveUnsavedEdits(UnsavedEditsChoice),
+}
+
+/// What to do with the edited hunt or shiny before leaving its editor
+#[derive(Debug, Clone, Copy)]
+enum UnsavedEditsChoice {
+    Save,
+    Discard,
+    Cancel,
+}
+
+/// Action asked while the editor had unsaved edits, done once they are saved
+/// or discarded
+#[derive(Debug, Clone)]
+enum PendingAction {
+    Menu(MenuMessage),
+    Hunts(HuntsAction),
+    Shinies(ShiniesAction),
+    Close(window::Id),
 }
 
 enum Screen {
     Counters(Counters),
     Hunts(Hunts),
     Shinies(Shinies),
+    Stats(Stats),
+    Dex(Dex),
 }
 
 impl Screen {
-    fn view<'a>(&'a self, state: &'a State) -> Element<Message> {
+    fn view<'a>(&'a self, state: &'a State) -> Element<'a, Message> {
         match &self {
-            Screen::Counters(s) => s.view(state).map(Message::CountersMessage),
-            Screen::Hunts(s) => s.view(state).map(Message::HuntsMessage),
-            Screen::Shinies(s) => s.view(state).map(Message::ShiniesMessage),
+            Screen::Counters(s) => s.view(state).map(Message::Counters),
+            Screen::Hunts(s) => s.view(state).map(Message::Hunts),
+            Screen::Shinies(s) => s.view(state).map(Message::Shinies),
+            Screen::Stats(s) => s.view(state).map(Message::Stats),
+            Screen::Dex(s) => s.view(state).map(Message::Dex),
         }
     }
 }
 
 pub struct State {
     screen: Screen,
-    pub active_counters: [crate::counter::Counter; 4],
+    pub active_counters: Vec<crate::counter::Counter>,
     pub db_connection: diesel::SqliteConnection,
     pub all_hunts: Vec<hunt::Hunt>,
     pub all_shinies: Vec<shiny::Shiny>,
     pub selected_hunt: Option<usize>,
     pub selected_shiny: Option<usize>,
     editing_counter: Option<usize>,
+    pub focused_counter: Option<usize>,
+    picking_shiny_target: Option<usize>,
+    pub history: History,
+    pub alert_banners: Vec<alerts::Banner>,
     pub editing_hunt: hunt::Hunt,
     pub editing_hunt_index: Option<usize>,
+    pub creating_hunt: bool,
     pub editing_shiny: shiny::Shiny,
     pub editing_shiny_index: Option<usize>,
+    pub creating_shiny: bool,
+    /// Last change of each field made in the hunt editor, saved over the
+    /// current hunt so the encounters counted in the meantime are kept
+    hunt_edits: Vec<hunt::HuntEditAction>,
+    /// Whether the edited hunt or shiny was changed since it was opened
+    unsaved_edits: bool,
+    pending_action: Option<PendingAction>,
+    pub settings: settings::Settings,
+    pub sprites: sprites::SpriteCache,
+    editing_templates: Option<TemplatesEditor>,
+    merge_tool: Option<MergeTool>,
+}
+
+fn unsaved_edits_modal<'a>(can_save: bool) -> Element<'a, MenuMessage> {
+    container(
+        column![
+            text("Modifications non enregistrées"),
+            text("Les modifications en cours seront perdues si elles ne sont pas enregistrées.")
+                .size(14),
+            row![
+                button("Enregistrer").on_press_maybe(
+                    can_save.then_some(MenuMessage::ResolveUnsavedEdits(UnsavedEditsChoice::Save))
+                ),
+                button("Abandonner").on_press(MenuMessage::ResolveUnsavedEdits(
+                    UnsavedEditsChoice::Discard
+                )),
+                button("Annuler")
+                    .on_press(MenuMessage::ResolveUnsavedEdits(UnsavedEditsChoice::Cancel)),
+            ]
+            .spacing(8),
+        ]
+        .spacing(16),
+    )
+    .padding(16)
+    .style(card)
+    .into()
 }
 
 fn menu<'a>() -> Element<'a, MenuMessage>
@@ -122,7 +220,11 @@ where
             button("Hunts").on_press(MenuMessage::ChangeScreen(ScreenType::Hunts)),
             button("Shinies").on_press(MenuMessage::ChangeScreen(ScreenType::Shinies)),
             button("Counters").on_press(MenuMessage::ChangeScreen(ScreenType::Counters)),
-        ]
+            button("Stats").on_press(MenuMessage::ChangeScreen(ScreenType::Stats)),
+            button("Dex").on_press(MenuMessage::ChangeScreen(ScreenType::Dex)),
+        ],
+        vertical_space(),
+        button("Fusionner une base").on_press(MenuMessage::OpenMergeTool),
     ])
     .height(Fill)
     .width(200)
@@ -131,17 +233,18 @@ where
 }
 
 impl State {
-    fn db_upsert_hunt_by_index(&mut self, index: usize) -> Result<hunt::Hunt, ()> {
-        use crate::schema::hunts::dsl::*;
+    /// Sprite from the sprite folder, or a silhouette when it's missing
+    pub fn sprite<'a, Message: 'a>(
+        &self,
+        key: sprites::SpriteKey,
+        size: impl Into<Length> + Copy,
+    ) -> Element<'a, Message> {
+        self.sprites.view(&self.settings.sprites, key, size)
+    }
 
+    fn db_upsert_hunt_by_index(&mut self, index: usize) -> Result<hunt::Hunt, ()> {
         if let Some(hunt) = self.all_hunts.get(index) {
-            let insertable = hunt.copy_into_insertable();
-            let result = diesel::insert_into(hunts)
-                .values(&insertable)
-                .on_conflict(id)
-                .do_update()
-                .set(&insertable)
-                .get_result::<models::Hunt>(&mut self.db_connection);
+            let result = hunt.upsert(&mut self.db_connection);
             if let Ok(db_hunt) = result {
                 match hunt::Hunt::get_by_id(db_hunt.id, &mut self.db_connection) {
                     Ok(hunt) => Ok(hunt),
@@ -155,16 +258,21 @@ impl State {
         }
     }
 
-    fn db_upsert_edited_hunt(&mut self) -> Result<hunt::Hunt, ()> {
-        use crate::schema::hunts::dsl::*;
+    /// Reloads a hunt before changing it, so encounters added from the
+    /// command line in the meantime aren't overwritten when it's saved. The
+    /// automatic soft resets not saved yet are saved first.
+    fn reload_hunt(&mut self, index: usize) {
+        self.save_auto_resets();
+        let Some(hunt_id) = self.all_hunts.get(index).and_then(|hunt| hunt.id) else {
+            return;
+        };
+        if let Ok(hunt) = hunt::Hunt::get_by_id(hunt_id, &mut self.db_connection) {
+            self.all_hunts[index] = hunt;
+        }
+    }
 
-        let insertable = self.editing_hunt.copy_into_insertable();
-        let result = diesel::insert_into(hunts)
-            .values(&insertable)
-            .on_conflict(id)
-            .do_update()
-            .set(&insertable)
-            .get_result::<models::Hunt>(&mut self.db_connection);
+    fn db_upsert_edited_hunt(&mut self) -> Result<hunt::Hunt, ()> {
+        let result = self.editing_hunt.upsert(&mut self.db_connection);
         if let Ok(db_hunt) = result {
             match hunt::Hunt::get_by_id(db_hunt.id, &mut self.db_connection) {
                 Ok(hunt) => Ok(hunt),
@@ -175,217 +283,1210 @@ impl State {
         }
     }
 
-    fn db_delete_hunt(&mut self, index: usize) {}
+    /// Closes the hunt editor, saving the edited hunt first if asked to
+    fn stop_edit_hunt(&mut self, save: bool) {
+        if save {
+            if let Some(index) = self.editing_hunt_index {
+                // Only the edited fields change, the hunt may have been
+                // counted in since the editor was opened
+                self.reload_hunt(index);
+                let mut hunt = self.all_hunts[index].clone();
+                for action in self.hunt_edits.drain(..) {
+                    hunt.perform(action);
+                }
+                self.editing_hunt = hunt;
+            }
+            if let Ok(hunt) = self.db_upsert_edited_hunt() {
+                match self.editing_hunt_index {
+                    Some(index) => self.all_hunts[index] = hunt,
+                    None => self.all_hunts.push(hunt),
+                }
+            }
+        }
+        self.editing_hunt_index = None;
+        self.creating_hunt = false;
+        self.hunt_edits.clear();
+        self.unsaved_edits = false;
+        self.editing_hunt = hunt::Hunt::default();
+    }
 
-    fn db_upsert_shiny_by_index(&mut self, index: usize) -> Result<shiny::Shiny, ()> {
-        use crate::schema::shinies::dsl::*;
+    fn db_delete_hunt(&mut self, index: usize) -> Result<(), ()> {
+        use crate::schema::hunts::dsl::*;
 
-        if let Some(shiny) = self.all_shinies.get(index) {
-            let insertable = shiny.copy_into_insertable();
-            let result = diesel::insert_into(shinies)
-                .values(&insertable)
-                .on_conflict(id)
-                .do_update()
-                .set(&insertable)
-                .get_result(&mut self.db_connection);
-            if let Ok(db_shiny) = result {
-                Ok(shiny::Shiny::from_db_shiny(db_shiny))
-            } else {
-                Err(())
+        if let Some(hunt_id) = self.all_hunts.get(index).and_then(|hunt| hunt.id) {
+            let result = self.db_connection.transaction(|db| {
+                diesel::update(
+                    crate::schema::shinies::table
+                        .filter(crate::schema::shinies::hunt_id.eq(hunt_id)),
+                )
+                .set(crate::schema::shinies::hunt_id.eq(None::<i32>))
+                .execute(db)?;
+                diesel::delete(
+                    crate::schema::sessions::table
+                        .filter(crate::schema::sessions::hunt_id.eq(hunt_id)),
+                )
+                .execute(db)?;
+                diesel::delete(
+                    crate::schema::hunt_targets::table
+                        .filter(crate::schema::hunt_targets::hunt_id.eq(hunt_id)),
+                )
+                .execute(db)?;
+                diesel::delete(hunts.filter(id.eq(hunt_id))).execute(db)
+            });
+            if result.is_err() {
+                return Err(());
             }
-        } else {
-            Err(())
+            for shiny in self.all_shinies.iter_mut() {
+                if shiny.hunt_id == Some(hunt_id) {
+                    shiny.hunt_id = None;
+                }
+            }
+            self.settings.goals.remove_hunt(hunt_id);
+            let _ = self.settings.save(&mut self.db_connection);
+        }
+        let removed = self.all_hunts.remove(index);
+        if removed.id.is_some() {
+            for counter in self.active_counters.iter_mut() {
+                if counter.hunt == removed.id {
+                    counter.hunt = None;
+                }
+            }
+            self.db_save_counters()?;
         }
+        Ok(())
     }
 
-    fn db_upsert_edited_shiny(&mut self) -> Result<shiny::Shiny, ()> {
+    fn db_save_counters(&mut self) -> Result<(), ()> {
+        counter::Counter::save_all(&mut self.active_counters, &mut self.db_connection)
+            .map_err(|_| ())
+    }
+
+    /// Index in `all_hunts` of the hunt attached to the given counter
+    fn counter_hunt_index(&self, counter_id: usize) -> Option<usize> {
+        let hunt_id = self.active_counters.get(counter_id)?.hunt?;
+        self.all_hunts
+            .iter()
+            .position(|hunt| hunt.id == Some(hunt_id))
+    }
+
+    /// Closes the shiny editor, saving the edited shiny first if asked to
+    fn stop_edit_shiny(&mut self, save: bool) {
+        if save {
+            if let Ok(db_shiny) = self.editing_shiny.upsert(&mut self.db_connection) {
+                let shiny = shiny::Shiny::from_db_shiny(db_shiny);
+                // Keep the shinies of the hunts in sync with the new link
+                for hunt in self.all_hunts.iter_mut() {
+                    hunt.shinies.retain(|found| found.id != shiny.id);
+                    if hunt.id.is_some() && hunt.id == shiny.hunt_id {
+                        hunt.shinies.push(shiny.clone());
+                    }
+                }
+                match self.editing_shiny_index {
+                    Some(index) => self.all_shinies[index] = shiny,
+                    None => self.all_shinies.push(shiny),
+                }
+            }
+        }
+        self.editing_shiny_index = None;
+        self.creating_shiny = false;
+        self.unsaved_edits = false;
+        self.editing_shiny = shiny::Shiny::default();
+    }
+
+    fn db_delete_shiny(&mut self, index: usize) -> Result<(), ()> {
         use crate::schema::shinies::dsl::*;
 
-        let insertable = self.editing_shiny.copy_into_insertable();
-        let result = diesel::insert_into(shinies)
-            .values(&insertable)
-            .on_conflict(id)
-            .do_update()
-            .set(&insertable)
-            .get_result(&mut self.db_connection);
-        if let Ok(db_shiny) = result {
-            Ok(shiny::Shiny::from_db_shiny(db_shiny))
-        } else {
-            Err(())
+        if let Some(shiny_id) = self.all_shinies.get(index).and_then(|shiny| shiny.id) {
+            if diesel::delete(shinies.filter(id.eq(shiny_id)))
+                .execute(&mut self.db_connection)
+                .is_err()
+            {
+                return Err(());
+            }
+            for hunt in self.all_hunts.iter_mut() {
+                hunt.shinies.retain(|shiny| shiny.id != Some(shiny_id));
+            }
         }
+        self.all_shinies.remove(index);
+        Ok(())
     }
 
-    fn db_delete_shiny(&mut self, index: usize) {}
+    /// Reloads hunts and shinies after the database was modified from the
+    /// outside of the usual upsert functions.
+    fn reload_from_db(&mut self) {
+        if let Ok(all_hunts) = hunt::Hunt::get_all(&mut self.db_connection) {
+            self.all_hunts = all_hunts;
+        }
+        if let Ok(all_shinies) = shiny::Shiny::get_all(&mut self.db_connection) {
+            self.all_shinies = all_shinies;
+        }
+        self.selected_hunt = None;
+        self.selected_shiny = None;
+        self.editing_hunt_index = None;
+        self.creating_hunt = false;
+        self.hunt_edits.clear();
+        self.editing_shiny_index = None;
+        self.creating_shiny = false;
+        self.unsaved_edits = false;
+        self.history.clear();
+        if let Screen::Stats(_) = self.screen {
+            self.screen = Screen::Stats(Stats::new(self));
+        }
+    }
 
     fn new() -> (Self, Task<Message>) {
         let mut db_connection = establish_db_connection();
+        if let Err(err) = run_migrations(&mut db_connection) {
+            panic!("Database upgrade failed: {}", err)
+        };
 
         let all_hunts = hunt::Hunt::get_all(&mut db_connection).expect("Failed to load hunts!");
         let all_shinies =
             shiny::Shiny::get_all(&mut db_connection).expect("Failed to load shinies!");
+        let active_counters =
+            counter::Counter::get_all(&mut db_connection).expect("Failed to load counters!");
+        let settings =
+            settings::Settings::load(&mut db_connection).expect("Failed to load settings!");
         (
             Self {
                 screen: Screen::Counters(screens::Counters::default()),
-                active_counters: Default::default(),
+                active_counters,
                 db_connection,
                 all_hunts,
                 all_shinies,
                 selected_hunt: None,
                 selected_shiny: None,
                 editing_counter: None,
+                focused_counter: None,
+                picking_shiny_target: None,
+                history: History::default(),
+                alert_banners: Vec::new(),
                 editing_hunt: hunt::Hunt::default(),
                 editing_hunt_index: None,
+                creating_hunt: false,
                 editing_shiny: shiny::Shiny::default(),
                 editing_shiny_index: None,
+                creating_shiny: false,
+                hunt_edits: vec![],
+                unsaved_edits: false,
+                pending_action: None,
+                settings,
+                sprites: sprites::SpriteCache::default(),
+                editing_templates: None,
+                merge_tool: None,
             },
             Task::none(),
         )
     }
 
-    fn update(&mut self, message: Message) -> Task<Message> {
-        match message {
-            Message::MenuMessage(msg) => match msg {
-                MenuMessage::ChangeScreen(screen_type) => match screen_type {
-                    screens::ScreenType::Counters => {
-                        let counters = screens::Counters::new();
-                        self.screen = Screen::Counters(counters);
-                        Task::none()
+    fn perform_counters_action(&mut self, action: CountersAction) -> Task<Message> {
+        // Changes made by hand are saved and undone after the automatic ones
+        if !matches!(action, CountersAction::None) {
+            self.save_auto_resets();
+        }
+        match action {
+            CountersAction::Increment(id) => {
+                self.focused_counter = Some(id);
+                self.increment_counter(id, chrono::Local::now());
+                Task::none()
+            }
+            CountersAction::Decrement(id) => {
+                self.focused_counter = Some(id);
+                let hunt_index = self.counter_hunt_index(id);
+                if let Some(index) = hunt_index {
+                    self.reload_hunt(index);
+                }
+                let before = self.counter_snapshot(id);
+                let c = &mut self.active_counters[id];
+                c.timer.record_encounters(-c.inc, chrono::Local::now());
+                if let Some(index) = hunt_index {
+                    c.decrement(self.all_hunts.get_mut(index));
+                    let _ = self.db_upsert_hunt_by_index(index);
+                } else {
+                    c.decrement(None);
+                }
+                let _ = self.db_save_counters();
+                self.record_change(id, ChangeKind::Decrement, before, None);
+                Task::none()
+            }
+            CountersAction::EditCounter(edit_action) => {
+                if let Some(id) = self.editing_counter {
+                    if let CounterEditAction::SetHunt(_) | CounterEditAction::UnsetHunt =
+                        edit_action
+                    {
+                        // The running session belongs to the previous hunt
+                        self.pause_counter_timer(id, chrono::Local::now());
                     }
-                    screens::ScreenType::Hunts => {
-                        let hunts = screens::Hunts::new();
-                        self.screen = Screen::Hunts(hunts);
-                        Task::none()
+                    let hunt_index = self.counter_hunt_index(id);
+                    if let Some(index) = hunt_index {
+                        self.reload_hunt(index);
                     }
-                    screens::ScreenType::Shinies => {
-                        let shinies = screens::Shinies::new();
-                        self.screen = Screen::Shinies(shinies);
-                        Task::none()
+                    let before = self.counter_snapshot(id);
+                    let c = &mut self.active_counters[id];
+                    if let Some(index) = hunt_index {
+                        c.perform(edit_action, self.all_hunts.get_mut(index));
+                        let _ = self.db_upsert_hunt_by_index(index);
+                    } else {
+                        c.perform(edit_action, None);
                     }
-                },
-            },
-            Message::CountersMessage(msg) => {
+                    let _ = self.db_save_counters();
+                    let kind = match edit_action {
+                        CounterEditAction::SetHunt(_) | CounterEditAction::UnsetHunt => {
+                            ChangeKind::SetHunt
+                        }
+                        CounterEditAction::SetIncrement(_) => ChangeKind::SetIncrement,
+                        CounterEditAction::SetCount(_) => ChangeKind::SetCount,
+                        CounterEditAction::SetMode(_) => ChangeKind::SetMode,
+                        CounterEditAction::ResetChains => ChangeKind::ResetChains,
+                        CounterEditAction::SetCycle(_) => ChangeKind::SetCycle,
+                    };
+                    self.record_change(id, kind, before, None);
+                }
+                Task::none()
+            }
+            CountersAction::StartEditCounter(id) => {
+                self.editing_counter = Some(id);
                 if let Screen::Counters(screen) = &mut self.screen {
-                    let action = screen.update(msg);
+                    screen.start_editing(
+                        self.settings.timer.idle_minutes,
+                        self.active_counters[id].cycle_ms,
+                    );
+                }
+                Task::none()
+            }
+            CountersAction::StopEditCounter => {
+                self.editing_counter = None;
+                Task::none()
+            }
+            CountersAction::AddCounter => {
+                self.active_counters.push(counter::Counter::default());
+                let _ = self.db_save_counters();
+                Task::none()
+            }
+            CountersAction::RemoveCounter(id) => {
+                if id < self.active_counters.len() {
+                    self.pause_counter_timer(id, chrono::Local::now());
+                    let removed = self.active_counters.remove(id);
+                    if let Some(counter_id) = removed.id {
+                        self.settings.counter_bindings.remove_counter(counter_id);
+                        let _ = self.settings.save(&mut self.db_connection);
+                    }
+                }
+                self.editing_counter = None;
+                self.focused_counter = None;
+                let _ = self.db_save_counters();
+                Task::none()
+            }
+            CountersAction::MoveCounter(from, to) => {
+                if from < self.active_counters.len() && to < self.active_counters.len() {
+                    let counter = self.active_counters.remove(from);
+                    self.active_counters.insert(to, counter);
+                    if self.editing_counter == Some(from) {
+                        self.editing_counter = Some(to);
+                    }
+                    if self.focused_counter == Some(from) {
+                        self.focused_counter = Some(to);
+                    }
+                }
+                let _ = self.db_save_counters();
+                Task::none()
+            }
+            CountersAction::ShinyFound(id) => {
+                self.focused_counter = Some(id);
+                match self.counter_hunt_index(id) {
+                    Some(index) if !self.all_hunts[index].other_targets.is_empty() => {
+                        self.picking_shiny_target = Some(id);
+                    }
+                    Some(index) => {
+                        let target = self.all_hunts[index].target;
+                        self.record_shiny_found(id, target, false);
+                    }
+                    None => {}
+                }
+                Task::none()
+            }
+            CountersAction::ShinyFoundTarget(species, keep_hunting) => {
+                if let Some(id) = self.picking_shiny_target.take() {
+                    self.record_shiny_found(id, species, keep_hunting);
+                }
+                Task::none()
+            }
+            CountersAction::CancelShinyFound => {
+                self.picking_shiny_target = None;
+                Task::none()
+            }
+            CountersAction::DismissAlert(index) => {
+                if index < self.alert_banners.len() {
+                    self.alert_banners.remove(index);
+                }
+                Task::none()
+            }
+            CountersAction::FocusCounter(id) => {
+                self.focused_counter = Some(id);
+                Task::none()
+            }
+            CountersAction::SetBinding(command, binding) => {
+                let counter_id = self
+                    .editing_counter
+                    .and_then(|id| self.active_counters.get(id))
+                    .and_then(|counter| counter.id);
+                if let Some(counter_id) = counter_id {
+                    self.settings
+                        .counter_bindings
+                        .set(counter_id, command, binding);
+                    let _ = self.settings.save(&mut self.db_connection);
+                }
+                Task::none()
+            }
+            CountersAction::KeyPressed(binding) => {
+                // Keys are used by the counter's modals while they are open
+                if self.editing_counter.is_some() || self.picking_shiny_target.is_some() {
+                    return Task::none();
+                }
+                let bound = self.settings.counter_bindings.find(&binding).and_then(
+                    |(counter_id, command)| {
+                        let id = self
+                            .active_counters
+                            .iter()
+                            .position(|counter| counter.id == Some(counter_id))?;
+                        Some(match command {
+                            CounterCommand::Increment => CountersAction::Increment(id),
+                            CounterCommand::Decrement => CountersAction::Decrement(id),
+                            CounterCommand::ShinyFound => CountersAction::ShinyFound(id),
+                            CounterCommand::ChainBroken => CountersAction::BreakChain(id),
+                            CounterCommand::ToggleAutoReset => CountersAction::ToggleAutoReset(id),
+                            CounterCommand::Focus => CountersAction::FocusCounter(id),
+                        })
+                    },
+                );
+                if let Some(action) = bound {
+                    return self.perform_counters_action(action);
+                }
 
-                    match action {
-                        CountersAction::Increment(id) => {
-                            let c = &mut self.active_counters[id];
-                            if let Some(index) = c.hunt {
-                                c.increment(self.all_hunts.get_mut(index));
-                                let _ = self.db_upsert_hunt_by_index(index);
-                            } else {
-                                c.increment(None);
+                // Unbound keys act on the focused counter
+                let count = self.active_counters.len();
+                if count == 0 {
+                    return Task::none();
+                }
+                let focused = self.focused_counter.filter(|id| *id < count).unwrap_or(0);
+                let action = if binding == KeyBinding::character("z", Modifiers::CTRL) {
+                    CountersAction::Undo
+                } else if binding == KeyBinding::character("y", Modifiers::CTRL)
+                    || binding == KeyBinding::character("z", Modifiers::CTRL | Modifiers::SHIFT)
+                {
+                    CountersAction::Redo
+                } else if binding == KeyBinding::named(Named::Space, Modifiers::empty()) {
+                    CountersAction::Increment(focused)
+                } else if binding == KeyBinding::named(Named::Backspace, Modifiers::empty()) {
+                    CountersAction::Decrement(focused)
+                } else if binding == KeyBinding::named(Named::Tab, Modifiers::empty()) {
+                    CountersAction::FocusCounter((focused + 1) % count)
+                } else if binding == KeyBinding::named(Named::Tab, Modifiers::SHIFT) {
+                    CountersAction::FocusCounter((focused + count - 1) % count)
+                } else {
+                    CountersAction::None
+                };
+                self.perform_counters_action(action)
+            }
+            CountersAction::CreateHunt(target) => {
+                if let Some(id) = self.editing_counter {
+                    self.pause_counter_timer(id, chrono::Local::now());
+                    let count = self.active_counters[id].count;
+                    self.all_hunts.push(hunt::Hunt {
+                        target,
+                        phase_encounters: count,
+                        phase_count: 1,
+                        start_time: Some(chrono::Local::now()),
+                        ..hunt::Hunt::default()
+                    });
+                    let index = self.all_hunts.len() - 1;
+                    match self.db_upsert_hunt_by_index(index) {
+                        Ok(hunt) => {
+                            self.active_counters[id].hunt = hunt.id;
+                            self.all_hunts[index] = hunt;
+                            let _ = self.db_save_counters();
+                        }
+                        Err(_) => {
+                            self.all_hunts.pop();
+                        }
+                    }
+                }
+                Task::none()
+            }
+            CountersAction::ToggleTimer(id) => {
+                let now = chrono::Local::now();
+                if self.active_counters[id].timer.is_running() {
+                    self.pause_counter_timer(id, now);
+                } else {
+                    self.active_counters[id].timer.start(now);
+                }
+                Task::none()
+            }
+            CountersAction::SetGoal(goal, target) => {
+                self.settings
+                    .goals
+                    .set(goal, target, chrono::Local::now().date_naive());
+                let _ = self.settings.save(&mut self.db_connection);
+                Task::none()
+            }
+            CountersAction::SetIdleMinutes(idle_minutes) => {
+                self.settings.timer.idle_minutes = idle_minutes;
+                let _ = self.settings.save(&mut self.db_connection);
+                Task::none()
+            }
+            CountersAction::MeasureCycle(id) => {
+                self.focused_counter = Some(id);
+                let before = self.counter_snapshot(id);
+                self.active_counters[id].measure_cycle(chrono::Local::now());
+                let _ = self.db_save_counters();
+                self.record_change(id, ChangeKind::SetCycle, before, None);
+                Task::none()
+            }
+            CountersAction::ToggleAutoReset(id) => {
+                self.focused_counter = Some(id);
+                let now = chrono::Local::now();
+                let c = &mut self.active_counters[id];
+                c.toggle_auto_reset(now);
+                if c.soft_reset.next_increment.is_some() {
+                    c.timer.start(now);
+                }
+                Task::none()
+            }
+            CountersAction::BreakChain(id) => {
+                self.focused_counter = Some(id);
+                let before = self.counter_snapshot(id);
+                self.active_counters[id].break_chain();
+                let _ = self.db_save_counters();
+                self.record_change(id, ChangeKind::ChainBroken, before, None);
+                Task::none()
+            }
+            CountersAction::Undo => {
+                if let Some(mut change) = self.history.take_undo() {
+                    self.restore_snapshot(change.counter_id, &change.before);
+                    if let Some(shiny_id) = change.shiny.as_ref().and_then(|shiny| shiny.id) {
+                        if let Some(index) = self
+                            .all_shinies
+                            .iter()
+                            .position(|shiny| shiny.id == Some(shiny_id))
+                        {
+                            let _ = self.db_delete_shiny(index);
+                        }
+                        if let Some(shiny) = change.shiny.as_mut() {
+                            shiny.id = None;
+                        }
+                    }
+                    self.history.undone(change);
+                }
+                Task::none()
+            }
+            CountersAction::Redo => {
+                if let Some(mut change) = self.history.take_redo() {
+                    // The shiny is recorded first so that the hunt reloaded
+                    // with the snapshot includes it.
+                    if let Some(shiny) = change.shiny.take() {
+                        change.shiny = match shiny.upsert(&mut self.db_connection) {
+                            Ok(db_shiny) => {
+                                let shiny = shiny::Shiny::from_db_shiny(db_shiny);
+                                self.all_shinies.push(shiny.clone());
+                                Some(shiny)
                             }
+                            Err(_) => Some(shiny),
+                        };
+                    }
+                    self.restore_snapshot(change.counter_id, &change.after);
+                    self.history.redone(change);
+                }
+                Task::none()
+            }
+            CountersAction::None => Task::none(),
+        }
+    }
+
+    fn perform_hunts_action(&mut self, action: HuntsAction) -> Task<Message> {
+        if self.unsaved_edits
+            && matches!(
+                action,
+                HuntsAction::StartEditHunt(_) | HuntsAction::CreateHunt
+            )
+        {
+            self.pending_action = Some(PendingAction::Hunts(action));
+            return Task::none();
+        }
+        match action {
+            HuntsAction::SelectHunt(index) => {
+                self.selected_hunt = Some(index);
+            }
+            HuntsAction::CloseSelectedHunt => {
+                self.selected_hunt = None;
+            }
+            HuntsAction::StartEditHunt(index) => {
+                self.editing_hunt_index = Some(index);
+                self.creating_hunt = false;
+                self.hunt_edits.clear();
+                self.unsaved_edits = false;
+                self.editing_hunt = match self.all_hunts.get(index) {
+                    Some(hunt) => hunt.clone(),
+                    None => hunt::Hunt::default(),
+                };
+                if let Screen::Hunts(screen) = &mut self.screen {
+                    screen.start_editing(&self.editing_hunt);
+                }
+            }
+            HuntsAction::CreateHunt => {
+                self.selected_hunt = None;
+                self.editing_hunt_index = None;
+                self.creating_hunt = true;
+                self.hunt_edits.clear();
+                self.unsaved_edits = false;
+                self.editing_hunt = hunt::Hunt {
+                    phase_count: 1,
+                    start_time: Some(chrono::Local::now()),
+                    ..hunt::Hunt::default()
+                };
+                if let Screen::Hunts(screen) = &mut self.screen {
+                    screen.start_editing(&self.editing_hunt);
+                }
+            }
+            HuntsAction::StopEditHunt(save) => self.stop_edit_hunt(save),
+            HuntsAction::DeleteHunt(index) => {
+                if self.db_delete_hunt(index).is_ok() {
+                    match self.editing_hunt_index {
+                        Some(editing) if editing == index => self.stop_edit_hunt(false),
+                        Some(editing) if editing > index => {
+                            self.editing_hunt_index = Some(editing - 1)
+                        }
+                        _ => {}
+                    }
+                }
+                self.selected_hunt = None;
+            }
+            HuntsAction::CopyReport(index, format) => {
+                if let Some(hunt) = self.all_hunts.get(index) {
+                    return iced::clipboard::write(
+                        self.settings.report_templates.hunt_report(hunt, format),
+                    );
+                }
+            }
+            HuntsAction::CopyListReport(format) => {
+                // Only the hunts shown in the grid, in their order
+                let shown = self.settings.filters.hunts.indices(&self.all_hunts);
+                return iced::clipboard::write(self.settings.report_templates.hunts_report(
+                    shown.into_iter().map(|index| &self.all_hunts[index]),
+                    format,
+                ));
+            }
+            HuntsAction::EditFilter(edit) => {
+                let typed = edit.is_typed();
+                self.settings.filters.hunts.apply(edit);
+                if !typed {
+                    let _ = self.settings.save_filters(&mut self.db_connection);
+                }
+            }
+            HuntsAction::EditHunt(edit_action) => {
+                self.editing_hunt.perform(edit_action.clone());
+                self.hunt_edits.retain(|edit| !edit_action.overwrites(edit));
+                self.hunt_edits.push(edit_action);
+                self.unsaved_edits = true;
+            }
+            HuntsAction::EditReportTemplates => {
+                self.editing_templates =
+                    Some(TemplatesEditor::new(self.settings.report_templates.clone()));
+            }
+            HuntsAction::None => {}
+        }
+        Task::none()
+    }
+
+    fn perform_shinies_action(&mut self, action: ShiniesAction) -> Task<Message> {
+        if self.unsaved_edits
+            && matches!(
+                action,
+                ShiniesAction::StartEditShiny(_) | ShiniesAction::CreateShiny
+            )
+        {
+            self.pending_action = Some(PendingAction::Shinies(action));
+            return Task::none();
+        }
+        match action {
+            ShiniesAction::SelectShiny(index) => {
+                self.selected_shiny = Some(index);
+            }
+            ShiniesAction::CloseSelectedShiny => {
+                self.selected_shiny = None;
+            }
+            ShiniesAction::CreateShiny => {
+                self.selected_shiny = None;
+                self.editing_shiny_index = None;
+                self.creating_shiny = true;
+                self.unsaved_edits = false;
+                self.editing_shiny = shiny::Shiny {
+                    found_time: Some(chrono::Local::now()),
+                    ..Default::default()
+                };
+                if let Screen::Shinies(screen) = &mut self.screen {
+                    screen.start_editing(&self.editing_shiny);
+                }
+            }
+            ShiniesAction::StartEditShiny(index) => {
+                self.editing_shiny_index = Some(index);
+                self.creating_shiny = false;
+                self.unsaved_edits = false;
+                self.editing_shiny = match self.all_shinies.get(index) {
+                    Some(shiny) => shiny.clone(),
+                    None => shiny::Shiny::default(),
+                };
+                if let Screen::Shinies(screen) = &mut self.screen {
+                    screen.start_editing(&self.editing_shiny);
+                }
+            }
+            ShiniesAction::EditShiny(action) => {
+                self.editing_shiny.perform(action);
+                self.unsaved_edits = true;
+            }
+            ShiniesAction::StopEditShiny(save) => self.stop_edit_shiny(save),
+            ShiniesAction::DeleteShiny(index) => {
+                if self.db_delete_shiny(index).is_ok() {
+                    match self.editing_shiny_index {
+                        Some(editing) if editing == index => self.stop_edit_shiny(false),
+                        Some(editing) if editing > index => {
+                            self.editing_shiny_index = Some(editing - 1)
+                        }
+                        _ => {}
+                    }
+                }
+                self.selected_shiny = None;
+            }
+            ShiniesAction::CopyReport(index, format) => {
+                if let Some(shiny) = self.all_shinies.get(index) {
+                    return iced::clipboard::write(
+                        self.settings.report_templates.shiny_report(shiny, format),
+                    );
+                }
+            }
+            ShiniesAction::CopyListReport(format) => {
+                // Only the shinies shown in the grid, in their order
+                let shown = self.settings.filters.shinies.indices(&self.all_shinies);
+                return iced::clipboard::write(self.settings.report_templates.shinies_report(
+                    shown.into_iter().map(|index| &self.all_shinies[index]),
+                    format,
+                ));
+            }
+            ShiniesAction::EditFilter(edit) => {
+                let typed = edit.is_typed();
+                self.settings.filters.shinies.apply(edit);
+                if !typed {
+                    let _ = self.settings.save_filters(&mut self.db_connection);
+                }
+            }
+            ShiniesAction::EditReportTemplates => {
+                self.editing_templates =
+                    Some(TemplatesEditor::new(self.settings.report_templates.clone()));
+            }
+            ShiniesAction::None => {}
+        }
+        Task::none()
+    }
+
+    /// Records the shiny found by the counter's hunt
+    fn record_shiny_found(&mut self, id: usize, species: data::Species, keep_hunting: bool) {
+        self.pause_counter_timer(id, chrono::Local::now());
+        let hunt_index = self.counter_hunt_index(id);
+        if let Some(index) = hunt_index {
+            self.reload_hunt(index);
+        }
+        let before = self.counter_snapshot(id);
+        if let Some(index) = hunt_index {
+            if let Some(hunt) = self.all_hunts.get_mut(index) {
+                let shiny = hunt.shiny_found(species, chrono::Local::now(), keep_hunting);
+                let recorded = shiny
+                    .upsert(&mut self.db_connection)
+                    .ok()
+                    .map(shiny::Shiny::from_db_shiny);
+                self.all_shinies.push(recorded.clone().unwrap_or(shiny));
+                if let Ok(hunt) = self.db_upsert_hunt_by_index(index) {
+                    self.all_hunts[index] = hunt;
+                }
+                self.record_change(id, ChangeKind::ShinyFound, before, recorded);
+            }
+        }
+    }
+
+    /// Adds the increment of a counter to it and to its hunt
+    fn increment_counter(&mut self, id: usize, now: chrono::DateTime<chrono::Local>) {
+        let hunt_index = self.counter_hunt_index(id);
+        if let Some(index) = hunt_index {
+            self.reload_hunt(index);
+        }
+        let before = self.counter_snapshot(id);
+        self.count_increment(id, hunt_index, now);
+        if let Some(index) = hunt_index {
+            let _ = self.db_upsert_hunt_by_index(index);
+        }
+        let _ = self.db_save_counters();
+        self.record_change(id, ChangeKind::Increment, before, None);
+    }
+
+    /// Counts an automatic soft reset like an increment, but it's only saved
+    /// with the next ones by `save_auto_resets` and can't be undone
+    fn auto_increment_counter(&mut self, id: usize, now: chrono::DateTime<chrono::Local>) {
+        let hunt_index = self.counter_hunt_index(id);
+        self.count_increment(id, hunt_index, now);
+        self.active_counters[id]
+            .soft_reset
+            .unsaved_since
+            .get_or_insert(now);
+    }
+
+    fn count_increment(
+        &mut self,
+        id: usize,
+        hunt_index: Option<usize>,
+        now: chrono::DateTime<chrono::Local>,
+    ) {
+        let before = hunt_index
+            .and_then(|index| self.all_hunts.get(index))
+            .map(|hunt| hunt.phase_encounters);
+        let c = &mut self.active_counters[id];
+        c.timer.record_encounters(c.inc, now);
+        c.increment(hunt_index.and_then(|index| self.all_hunts.get_mut(index)));
+        if let Some(index) = hunt_index {
+            self.check_alerts(index, before);
+        }
+    }
+
+    /// Saves the counters and hunts changed by automatic soft resets since
+    /// they were last saved
+    fn save_auto_resets(&mut self) {
+        let mut saved = false;
+        for id in 0..self.active_counters.len() {
+            if self.active_counters[id]
+                .soft_reset
+                .unsaved_since
+                .take()
+                .is_none()
+            {
+                continue;
+            }
+            if let Some(index) = self.counter_hunt_index(id) {
+                let _ = self.db_upsert_hunt_by_index(index);
+            }
+            saved = true;
+        }
+        if saved {
+            let _ = self.db_save_counters();
+        }
+    }
+
+    /// Shows the alerts of a hunt whose thresholds were passed since its phase
+    /// had `before` encounters
+    fn check_alerts(&mut self, hunt_index: usize, before: Option<i32>) {
+        let hunt = &self.all_hunts[hunt_index];
+        let Some(before) = before.filter(|before| *before < hunt.phase_encounters) else {
+            return;
+        };
+        let crossed = hunt
+            .alerts
+            .crossed(hunt.odds(), before, hunt.phase_encounters);
+        if crossed.is_empty() {
+            return;
+        }
+        if hunt.alerts.sound {
+            alerts::play_sound();
+        }
+        let name = hunt.targets_name();
+        self.alert_banners
+            .extend(crossed.into_iter().map(|alert| alerts::Banner {
+                hunt: name.clone(),
+                alert,
+            }));
+        let excess = self.alert_banners.len().saturating_sub(alerts::MAX_BANNERS);
+        self.alert_banners.drain(..excess);
+    }
+
+    fn counter_snapshot(&self, id: usize) -> Snapshot {
+        Snapshot {
+            counter: CounterSnapshot::of(&self.active_counters[id]),
+            hunt: self
+                .counter_hunt_index(id)
+                .and_then(|index| HuntSnapshot::of(&self.all_hunts[index])),
+        }
+    }
+
+    /// Adds the change made to a counter to the undo history
+    fn record_change(
+        &mut self,
+        id: usize,
+        kind: ChangeKind,
+        before: Snapshot,
+        shiny: Option<shiny::Shiny>,
+    ) {
+        let Some(counter_id) = self.active_counters[id].id else {
+            return;
+        };
+        let after = self.counter_snapshot(id);
+        self.history.push(Change {
+            kind,
+            counter_id,
+            before,
+            after,
+            shiny,
+        });
+    }
+
+    /// Puts a counter and its hunt back in the state of the snapshot, in the
+    /// database too. Counters and hunts deleted since are left alone.
+    fn restore_snapshot(&mut self, counter_id: i32, snapshot: &Snapshot) {
+        if let Some(id) = self
+            .active_counters
+            .iter()
+            .position(|counter| counter.id == Some(counter_id))
+        {
+            snapshot.counter.restore(&mut self.active_counters[id]);
+            self.focused_counter = Some(id);
+            let _ = self.db_save_counters();
+        }
+        if let Some(hunt_snapshot) = &snapshot.hunt {
+            if let Some(index) = self
+                .all_hunts
+                .iter()
+                .position(|hunt| hunt.id == Some(hunt_snapshot.id))
+            {
+                hunt_snapshot.restore(&mut self.all_hunts[index]);
+                if let Ok(hunt) = self.db_upsert_hunt_by_index(index) {
+                    self.all_hunts[index] = hunt;
+                }
+            }
+        }
+    }
+
+    /// Pauses the timer of the counter and stores the finished session with
+    /// the hunt of the counter.
+    fn pause_counter_timer(&mut self, id: usize, now: chrono::DateTime<chrono::Local>) {
+        let finished = self.active_counters[id].timer.pause(now);
+        self.store_session(id, finished);
+    }
+
+    fn store_session(&mut self, id: usize, finished: Option<session::FinishedSession>) {
+        let Some((start_time, end_time, encounters)) = finished else {
+            return;
+        };
+        let Some(hunt) = self
+            .counter_hunt_index(id)
+            .and_then(|index| self.all_hunts.get_mut(index))
+        else {
+            return;
+        };
+        // A session of a single encounter has no duration but still dates it
+        let (Some(hunt_id), true) = (hunt.id, end_time > start_time || encounters > 0) else {
+            return;
+        };
+        let mut session = session::Session {
+            id: None,
+            hunt_id,
+            phase: hunt.phase_count,
+            start_time,
+            end_time,
+            encounters,
+        };
+        if let Ok(db_session) = session.upsert(&mut self.db_connection) {
+            session.id = Some(db_session.id);
+            hunt.sessions.push(session);
+        }
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        let mut subscriptions = vec![
+            keyboard::on_key_press(|key, modifiers| Some(Message::KeyPressed(key, modifiers))),
+            window::close_requests().map(Message::CloseRequested),
+        ];
+        if self
+            .active_counters
+            .iter()
+            .any(|counter| counter.soft_reset.next_increment.is_some())
+        {
+            // Soft reset increments must follow the cycle closely
+            subscriptions
+                .push(iced::time::every(Duration::from_millis(100)).map(|_| Message::Tick));
+        } else if self
+            .active_counters
+            .iter()
+            .any(|counter| counter.timer.is_running())
+        {
+            subscriptions.push(iced::time::every(Duration::from_secs(1)).map(|_| Message::Tick));
+        }
+        Subscription::batch(subscriptions)
+    }
+
+    fn update(&mut self, message: Message) -> Task<Message> {
+        match message {
+            Message::Menu(msg) => match msg {
+                MenuMessage::ChangeScreen(_) | MenuMessage::OpenMergeTool if self.unsaved_edits => {
+                    self.pending_action = Some(PendingAction::Menu(msg));
+                    Task::none()
+                }
+                MenuMessage::ResolveUnsavedEdits(choice) => {
+                    let Some(pending) = self.pending_action.take() else {
+                        return Task::none();
+                    };
+                    let save = match choice {
+                        UnsavedEditsChoice::Save => true,
+                        UnsavedEditsChoice::Discard => false,
+                        UnsavedEditsChoice::Cancel => return Task::none(),
+                    };
+                    let editing_hunt = self.editing_hunt_index.is_some() || self.creating_hunt;
+                    let editing_shiny = self.editing_shiny_index.is_some() || self.creating_shiny;
+                    self.stop_edit_hunt(save && editing_hunt);
+                    self.stop_edit_shiny(save && editing_shiny);
+                    match pending {
+                        PendingAction::Menu(msg) => self.update(Message::Menu(msg)),
+                        PendingAction::Hunts(action) => self.perform_hunts_action(action),
+                        PendingAction::Shinies(action) => self.perform_shinies_action(action),
+                        PendingAction::Close(window_id) => {
+                            self.update(Message::CloseRequested(window_id))
+                        }
+                    }
+                }
+                MenuMessage::ChangeScreen(screen_type) => {
+                    self.save_auto_resets();
+                    // Searches typed in the grid are only saved now
+                    let _ = self.settings.save_filters(&mut self.db_connection);
+                    // The editors don't survive their screen
+                    self.stop_edit_hunt(false);
+                    self.stop_edit_shiny(false);
+                    match screen_type {
+                        screens::ScreenType::Counters => {
+                            let counters = screens::Counters::new();
+                            self.screen = Screen::Counters(counters);
                             Task::none()
                         }
-                        CountersAction::Decrement(id) => {
-                            let c = &mut self.active_counters[id];
-                            if let Some(index) = c.hunt {
-                                c.decrement(self.all_hunts.get_mut(index));
-                                let _ = self.db_upsert_hunt_by_index(index);
-                            } else {
-                                c.decrement(None);
-                            }
+                        screens::ScreenType::Hunts => {
+                            let hunts = screens::Hunts::new();
+                            self.screen = Screen::Hunts(hunts);
                             Task::none()
                         }
-                        CountersAction::EditCounter(edit_action) => {
-                            if let Some(id) = self.editing_counter {
-                                let c = &mut self.active_counters[id];
-                                if let Some(index) = c.hunt {
-                                    c.perform(edit_action, self.all_hunts.get_mut(index));
-                                    let _ = self.db_upsert_hunt_by_index(index);
-                                } else {
-                                    c.perform(edit_action, None);
-                                }
-                            }
+                        screens::ScreenType::Shinies => {
+                            let shinies = screens::Shinies::new();
+                            self.screen = Screen::Shinies(shinies);
                             Task::none()
                         }
-                        CountersAction::StartEditCounter(id) => {
-                            self.editing_counter = Some(id);
+                        screens::ScreenType::Stats => {
+                            let stats = screens::Stats::new(self);
+                            self.screen = Screen::Stats(stats);
                             Task::none()
                         }
-                        CountersAction::StopEditCounter => {
-                            self.editing_counter = None;
+                        screens::ScreenType::Dex => {
+                            let dex = screens::Dex::new();
+                            self.screen = Screen::Dex(dex);
                             Task::none()
                         }
-                        _ => Task::none(),
                     }
+                }
+                MenuMessage::OpenMergeTool => {
+                    self.save_auto_resets();
+                    self.merge_tool = Some(MergeTool::new());
+                    Task::none()
+                }
+            },
+            Message::Counters(msg) => {
+                if let Screen::Counters(screen) = &mut self.screen {
+                    let action = screen.update(msg);
+                    self.perform_counters_action(action)
                 } else {
                     Task::none()
                 }
             }
-            Message::HuntsMessage(msg) => {
+            Message::Tick => {
+                let now = chrono::Local::now();
+                let idle = self.settings.timer.idle_delay();
+                for id in 0..self.active_counters.len() {
+                    for _ in 0..self.active_counters[id].auto_resets_due(now) {
+                        self.auto_increment_counter(id, now);
+                    }
+                    let finished = self.active_counters[id].timer.auto_pause(now, idle);
+                    self.store_session(id, finished);
+                }
+                let save_due = self.active_counters.iter().any(|counter| {
+                    counter
+                        .soft_reset
+                        .unsaved_since
+                        .is_some_and(|since| now - since >= AUTO_RESET_SAVE_DELAY)
+                });
+                if save_due {
+                    self.save_auto_resets();
+                }
+                Task::none()
+            }
+            Message::CloseRequested(window_id) if self.unsaved_edits => {
+                self.pending_action = Some(PendingAction::Close(window_id));
+                Task::none()
+            }
+            Message::CloseRequested(window_id) => {
+                // Running sessions and automatic soft resets would be lost
+                // otherwise
+                self.save_auto_resets();
+                let _ = self.settings.save_filters(&mut self.db_connection);
+                let now = chrono::Local::now();
+                for id in 0..self.active_counters.len() {
+                    self.pause_counter_timer(id, now);
+                }
+                window::close(window_id)
+            }
+            Message::KeyPressed(key, modifiers) => {
+                // Shortcuts are disabled while a modal of the menu is open
+                if self.editing_templates.is_some() || self.merge_tool.is_some() {
+                    return Task::none();
+                }
+                if let Screen::Counters(screen) = &mut self.screen {
+                    let action = screen.update(CountersMessage::KeyPressed(key, modifiers));
+                    self.perform_counters_action(action)
+                } else {
+                    Task::none()
+                }
+            }
+            Message::Hunts(msg) => {
                 if let Screen::Hunts(screen) = &mut self.screen {
                     let action = screen.update(msg);
-
-                    match action {
-                        HuntsAction::SelectHunt(index) => {
-                            self.selected_hunt = Some(index);
+                    self.perform_hunts_action(action)
+                } else {
+                    Task::none()
+                }
+            }
+            Message::Shinies(msg) => {
+                if let Screen::Shinies(screen) = &mut self.screen {
+                    let action = screen.update(msg);
+                    self.perform_shinies_action(action)
+                } else {
+                    Task::none()
+                }
+            }
+            Message::Stats(msg) => {
+                if let Screen::Stats(screen) = &mut self.screen {
+                    match screen.update(msg) {
+                        screens::stats::StatsAction::None => {}
+                    }
+                }
+                Task::none()
+            }
+            Message::Dex(msg) => {
+                if let Screen::Dex(screen) = &mut self.screen {
+                    match screen.update(msg) {
+                        screens::dex::DexAction::None => {}
+                        screens::dex::DexAction::SetSpriteFolder(folder) => {
+                            self.settings.sprites.folder = folder;
+                            let _ = self.settings.save(&mut self.db_connection);
+                            self.sprites.clear();
                         }
-                        HuntsAction::CloseSelectedHunt => {
-                            self.selected_hunt = None;
+                        screens::dex::DexAction::SetSpriteStyle(style) => {
+                            self.settings.sprites.style = style;
+                            let _ = self.settings.save(&mut self.db_connection);
+                            self.sprites.clear();
                         }
-                        HuntsAction::StartEditHunt(index) => {
-                            self.editing_hunt_index = Some(index);
-                            self.editing_hunt = match self.all_hunts.get(index) {
-                                Some(hunt) => hunt.clone(),
-                                None => hunt::Hunt::default(),
+                    }
+                }
+                Task::none()
+            }
+            Message::Templates(msg) => {
+                if let Some(editor) = &mut self.editing_templates {
+                    if let TemplatesAction::StopEditTemplates(save) = editor.update(msg) {
+                        if save {
+                            self.settings.report_templates = editor.templates.clone();
+                            let _ = self.settings.save(&mut self.db_connection);
+                        }
+                        self.editing_templates = None;
+                    }
+                }
+                Task::none()
+            }
+            Message::Merge(msg) => {
+                if let Some(tool) = &mut self.merge_tool {
+                    match tool.update(msg) {
+                        MergeAction::Analyze => {
+                            match merge::MergePlan::prepare(
+                                &PathBuf::from(tool.path()),
+                                &mut self.db_connection,
+                            ) {
+                                Ok(plan) => {
+                                    tool.plan = Some(plan);
+                                    tool.error = None;
+                                }
+                                Err(err) => {
+                                    tool.plan = None;
+                                    tool.error = Some(err.to_string());
+                                }
                             }
                         }
-                        HuntsAction::StopEditHunt(save) => {
+                        MergeAction::StopMerge(save) => {
                             if save {
-                                match self.db_upsert_edited_hunt() {
-                                    Ok(hunt) => {
-                                        self.all_hunts[self.editing_hunt_index.unwrap()] = hunt;
+                                if let Some(plan) = &tool.plan {
+                                    if let Err(err) = plan.apply(&mut self.db_connection) {
+                                        tool.error = Some(err.to_string());
+                                        return Task::none();
                                     }
-                                    Err(()) => {}
+                                    self.reload_from_db();
                                 }
                             }
-                            self.editing_hunt_index = None;
-                            self.editing_hunt = hunt::Hunt::default();
+                            self.merge_tool = None;
                         }
-                        _ => {}
+                        MergeAction::None => {}
                     }
-                    Task::none()
-                } else {
-                    Task::none()
-                }
-            }
-            Message::ShiniesMessage(msg) => {
-                if let Screen::Shinies(screen) = &mut self.screen {
-                    let action = screen.update(msg);
-
-                    match action {
-                        ShiniesAction::SelectShiny(index) => {
-                            self.selected_shiny = Some(index);
-                        }
-                        ShiniesAction::CloseSelectedShiny => {
-                            self.selected_shiny = None;
-                        }
-                        _ => {}
-                    }
-                    Task::none()
-                } else {
-                    Task::none()
                 }
+                Task::none()
             }
-            _ => Task::none(),
         }
     }
 
-    fn view(&self) -> Element<Message> {
+    fn view(&self) -> Element<'_, Message> {
         let content = container(self.screen.view(self));
-        let modal: Option<Element<Message>> = self.editing_counter.map(|counter_id| {
-            self.active_counters[counter_id]
-                .edit_modal(counter_id, self)
-                .map(Message::CountersMessage)
-                .into()
-        });
+        let modal: Option<(Element<Message>, Message)> =
+            if let Some(editor) = &self.editing_templates {
+                Some((
+                    editor.view().map(Message::Templates),
+                    Message::Templates(TemplatesMessage::StopEditTemplates(false)),
+                ))
+            } else if let Some(tool) = &self.merge_tool {
+                Some((
+                    tool.view().map(Message::Merge),
+                    Message::Merge(MergeMessage::StopMerge(false)),
+                ))
+            } else if self.pending_action.is_some() {
+                let can_save = match &self.screen {
+                    Screen::Hunts(screen) => screen.can_save(),
+                    Screen::Shinies(screen) => screen.can_save(),
+                    _ => false,
+                };
+                Some((
+                    unsaved_edits_modal(can_save).map(Message::Menu),
+                    Message::Menu(MenuMessage::ResolveUnsavedEdits(UnsavedEditsChoice::Cancel)),
+                ))
+            } else {
+                let screen = match &self.screen {
+                    Screen::Counters(screen) => Some(screen),
+                    _ => None,
+                };
+                self.editing_counter
+                    .zip(screen)
+                    .map(|(counter_id, screen)| {
+                        (
+                            self.active_counters[counter_id]
+                                .edit_modal(counter_id, self, screen)
+                                .map(Message::Counters),
+                            Message::Counters(CountersMessage::StopEditCounter),
+                        )
+                    })
+                    .or_else(|| {
+                        let (id, screen) = self.picking_shiny_target.zip(screen)?;
+                        let hunt = &self.all_hunts[self.counter_hunt_index(id)?];
+                        Some((
+                            screen.shiny_target_modal(hunt).map(Message::Counters),
+                            Message::Counters(CountersMessage::CancelShinyFound),
+                        ))
+                    })
+            };
 
         match modal {
-            None => row![menu().map(Message::MenuMessage), content].into(),
-            Some(m) => stack![
-                row![menu().map(Message::MenuMessage), content],
+            None => row![menu().map(Message::Menu), content].into(),
+            Some((m, on_close)) => stack![
+                row![menu().map(Message::Menu), content],
                 opaque(
                     mouse_area(center(opaque(m)).style(|_theme| {
                         container::Style {
@@ -399,7 +1500,7 @@ impl State {
                             ..container::Style::default()
                         }
                     }))
-                    .on_press(Message::CountersMessage(CountersMessage::StopEditCounter))
+                    .on_press(on_close)
                 )
             ]
             .into(),
@@ -0,0 +1,419 @@
+use crate::hunt::Hunt;
+use crate::schema::sessions;
+use crate::shiny::Shiny;
+
+use chrono::{DateTime, Local};
+use diesel::prelude::*;
+
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Resolution {
+    #[default]
+    KeepLocal,
+    TakeOther,
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldDifference {
+    pub label: &'static str,
+    pub local: String,
+    pub other: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct Conflict<T> {
+    pub local: T,
+    pub other: T,
+    pub differences: Vec<FieldDifference>,
+    pub resolution: Resolution,
+}
+
+/// Everything needed to merge another database into ours, computed before
+/// touching anything so that conflicts can be reviewed first.
+#[derive(Debug, Clone, Default)]
+pub struct MergePlan {
+    pub new_hunts: Vec<Hunt>,
+    pub new_shinies: Vec<Shiny>,
+    pub hunt_conflicts: Vec<Conflict<Hunt>>,
+    pub shiny_conflicts: Vec<Conflict<Shiny>>,
+    pub identical_hunts: usize,
+    pub identical_shinies: usize,
+    /// Ids of the other database's hunts matched with one of ours
+    matched_hunt_ids: HashMap<i32, i32>,
+}
+
+fn format_datetime(datetime: Option<DateTime<Local>>) -> String {
+    datetime
+        .map(|dt| dt.format("%d/%m/%Y %H:%M").to_string())
+        .unwrap_or("Inconnue".into())
+}
+
+fn format_option<T: ToString>(value: &Option<T>) -> String {
+    value
+        .as_ref()
+        .map(|v| v.to_string())
+        .unwrap_or("Inconnue".into())
+}
+
+fn hunt_fields(hunt: &Hunt) -> Vec<(&'static str, String)> {
+    vec![
+        ("Espèce", hunt.target.to_string()),
+        (
+            "Autres cibles",
+            hunt.other_targets
+                .iter()
+                .map(|species| species.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+        ),
+        ("Rencontres (phase)", hunt.phase_encounters.to_string()),
+        (
+            "Rencontres (phases précédentes)",
+            hunt.previous_encounters.to_string(),
+        ),
+        ("Phase", hunt.phase_count.to_string()),
+        ("Débutée le", format_datetime(hunt.start_time)),
+        ("Terminée le", format_datetime(hunt.end_time)),
+        (
+            "Terminée",
+            if hunt.completed { "Oui" } else { "Non" }.into(),
+        ),
+        ("Version", format_option(&hunt.version)),
+        ("Méthode", format_option(&hunt.method)),
+        ("Zone", format_option(&hunt.place)),
+        ("Notes", hunt.notes.clone().unwrap_or_default()),
+    ]
+}
+
+fn shiny_fields(shiny: &Shiny) -> Vec<(&'static str, String)> {
+    vec![
+        ("Espèce", shiny.species.to_string()),
+        ("Surnom", shiny.name.clone().unwrap_or_default()),
+        ("Sexe", format_option(&shiny.gender)),
+        ("Rencontres (total)", format_option(&shiny.total_encounters)),
+        ("Rencontres (phase)", format_option(&shiny.phase_encounters)),
+        ("Phase", format_option(&shiny.phase_number)),
+        ("Trouvé le", format_datetime(shiny.found_time)),
+        ("Version", format_option(&shiny.version)),
+        ("Méthode", format_option(&shiny.method)),
+        ("Zone", format_option(&shiny.place)),
+        ("Notes", shiny.notes.clone().unwrap_or_default()),
+    ]
+}
+
+fn differences(
+    local: Vec<(&'static str, String)>,
+    other: Vec<(&'static str, String)>,
+) -> Vec<FieldDifference> {
+    local
+        .into_iter()
+        .zip(other)
+        .filter(|((_, local), (_, other))| local != other)
+        .map(|((label, local), (_, other))| FieldDifference {
+            label,
+            local,
+            other,
+        })
+        .collect()
+}
+
+/// Finds the local entry matching `other`: the same uuid first, then the
+/// same species on the same day among the entries that are not matched yet.
+fn find_match<T>(
+    local: &[T],
+    other: &T,
+    matched: &HashSet<usize>,
+    uuid: impl Fn(&T) -> &str,
+    species: impl Fn(&T) -> i32,
+    date: impl Fn(&T) -> Option<DateTime<Local>>,
+) -> Option<usize> {
+    let unmatched = |index: &usize| !matched.contains(index);
+    if let Some(index) = (0..local.len())
+        .filter(unmatched)
+        .find(|&index| uuid(&local[index]) == uuid(other))
+    {
+        return Some(index);
+    }
+    let day = date(other)?.date_naive();
+    (0..local.len()).filter(unmatched).find(|&index| {
+        species(&local[index]) == species(other)
+            && date(&local[index]).map(|dt| dt.date_naive()) == Some(day)
+    })
+}
+
+/// Reads the hunts and shinies of another database. The other database may
+/// come from an older version of the app, so the migrations are run on a
+/// temporary copy to leave the original file untouched.
+fn read_other_database(
+    other_path: &Path,
+) -> Result<(Vec<Hunt>, Vec<Shiny>), Box<dyn Error + Send + Sync>> {
+    let copy_path =
+        std::env::temp_dir().join(format!("shutils-merge-{}.sqlite", std::process::id()));
+    std::fs::copy(other_path, &copy_path)?;
+    let read = || -> Result<_, Box<dyn Error + Send + Sync>> {
+        let mut other_db = SqliteConnection::establish(&copy_path.to_string_lossy())?;
+        crate::run_migrations(&mut other_db)?;
+        Ok((
+            Hunt::get_all(&mut other_db)?,
+            Shiny::get_all(&mut other_db)?,
+        ))
+    };
+    let result = read();
+    let _ = std::fs::remove_file(&copy_path);
+    result
+}
+
+/// Inserts the sessions of a hunt from the other database as sessions of the
+/// hunt `hunt_id` of ours.
+fn copy_sessions(hunt: &Hunt, hunt_id: i32, db: &mut SqliteConnection) -> QueryResult<()> {
+    for session in &hunt.sessions {
+        let mut session = session.clone();
+        session.id = None;
+        session.hunt_id = hunt_id;
+        session.upsert(db)?;
+    }
+    Ok(())
+}
+
+impl MergePlan {
+    pub fn prepare(
+        other_path: &Path,
+        db: &mut SqliteConnection,
+    ) -> Result<MergePlan, Box<dyn Error + Send + Sync>> {
+        if !other_path.is_file() {
+            return Err(format!("{} n'existe pas", other_path.display()).into());
+        }
+        if other_path.canonicalize()? == crate::get_database_path().canonicalize()? {
+            return Err("Impossible de fusionner la base avec elle-même".into());
+        }
+        let (other_hunts, other_shinies) = read_other_database(other_path)?;
+        let local_hunts = Hunt::get_all(db)?;
+        let local_shinies = Shiny::get_all(db)?;
+
+        let mut plan = MergePlan::default();
+
+        let mut matched = HashSet::new();
+        for hunt in other_hunts {
+            match find_match(
+                &local_hunts,
+                &hunt,
+                &matched,
+                |h| h.uuid.as_str(),
+                |h| h.target.into(),
+                |h| h.start_time,
+            ) {
+                Some(index) => {
+                    matched.insert(index);
+                    let local = &local_hunts[index];
+                    if let (Some(other_id), Some(local_id)) = (hunt.id, local.id) {
+                        plan.matched_hunt_ids.insert(other_id, local_id);
+                    }
+                    let differences = differences(hunt_fields(local), hunt_fields(&hunt));
+                    if differences.is_empty() {
+                        plan.identical_hunts += 1;
+                    } else {
+                        plan.hunt_conflicts.push(Conflict {
+                            local: local.clone(),
+                            other: hunt,
+                            differences,
+                            resolution: Resolution::default(),
+                        });
+                    }
+                }
+                None => plan.new_hunts.push(hunt),
+            }
+        }
+
+        let mut matched = HashSet::new();
+        for shiny in other_shinies {
+            match find_match(
+                &local_shinies,
+                &shiny,
+                &matched,
+                |s| s.uuid.as_str(),
+                |s| s.species.into(),
+                |s| s.found_time,
+            ) {
+                Some(index) => {
+                    matched.insert(index);
+                    let local = &local_shinies[index];
+                    let differences = differences(shiny_fields(local), shiny_fields(&shiny));
+                    if differences.is_empty() {
+                        plan.identical_shinies += 1;
+                    } else {
+                        plan.shiny_conflicts.push(Conflict {
+                            local: local.clone(),
+                            other: shiny,
+                            differences,
+                            resolution: Resolution::default(),
+                        });
+                    }
+                }
+                None => plan.new_shinies.push(shiny),
+            }
+        }
+
+        Ok(plan)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.new_hunts.is_empty()
+            && self.new_shinies.is_empty()
+            && self.hunt_conflicts.is_empty()
+            && self.shiny_conflicts.is_empty()
+    }
+
+    /// Writes the merge result in a single transaction. Hunts are written
+    /// first so that the sessions and shinies coming from the other database
+    /// can be linked to their hunt's id in our database.
+    pub fn apply(&self, db: &mut SqliteConnection) -> Result<(), Box<dyn Error + Send + Sync>> {
+        db.transaction(|db| {
+            let mut hunt_ids = self.matched_hunt_ids.clone();
+
+            for hunt in &self.new_hunts {
+                let mut new_hunt = hunt.clone();
+                new_hunt.id = None;
+                let db_hunt = new_hunt.upsert(db)?;
+                if let Some(other_id) = hunt.id {
+                    hunt_ids.insert(other_id, db_hunt.id);
+                }
+                copy_sessions(hunt, db_hunt.id, db)?;
+            }
+
+            for conflict in &self.hunt_conflicts {
+                if conflict.resolution == Resolution::TakeOther {
+                    let mut hunt = conflict.other.clone();
+                    hunt.id = conflict.local.id;
+                    hunt.uuid = conflict.local.uuid.clone();
+                    let db_hunt = hunt.upsert(db)?;
+                    diesel::delete(sessions::table.filter(sessions::hunt_id.eq(db_hunt.id)))
+                        .execute(db)?;
+                    copy_sessions(&conflict.other, db_hunt.id, db)?;
+                }
+            }
+
+            for shiny in &self.new_shinies {
+                let mut new_shiny = shiny.clone();
+                new_shiny.id = None;
+                new_shiny.hunt_id = shiny.hunt_id.and_then(|id| hunt_ids.get(&id).copied());
+                new_shiny.upsert(db)?;
+            }
+
+            for conflict in &self.shiny_conflicts {
+                if conflict.resolution == Resolution::TakeOther {
+                    let mut shiny = conflict.other.clone();
+                    shiny.id = conflict.local.id;
+                    shiny.uuid = conflict.local.uuid.clone();
+                    shiny.hunt_id = conflict
+                        .other
+                        .hunt_id
+                        .and_then(|id| hunt_ids.get(&id).copied())
+                        .or(conflict.local.hunt_id);
+                    shiny.upsert(db)?;
+                }
+            }
+
+            Ok::<(), diesel::result::Error>(())
+        })?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Species;
+
+    use chrono::TimeZone;
+
+    fn shiny(uuid: &str, species: Species, day: u32, hour: u32) -> Shiny {
+        Shiny {
+            uuid: uuid.into(),
+            species,
+            found_time: Some(Local.with_ymd_and_hms(2024, 5, day, hour, 0, 0).unwrap()),
+            ..Shiny::default()
+        }
+    }
+
+    fn find(local: &[Shiny], other: &Shiny, matched: &HashSet<usize>) -> Option<usize> {
+        find_match(
+            local,
+            other,
+            matched,
+            |s| s.uuid.as_str(),
+            |s| s.species.into(),
+            |s| s.found_time,
+        )
+    }
+
+    #[test]
+    fn same_uuid_matches_first() {
+        let local = [
+            shiny("a", Species::Pikachu, 1, 10),
+            shiny("b", Species::Eevee, 2, 10),
+        ];
+        let other = shiny("b", Species::Pikachu, 1, 18);
+        assert_eq!(find(&local, &other, &HashSet::new()), Some(1));
+    }
+
+    #[test]
+    fn matched_uuids_are_not_matched_again() {
+        let local = [
+            shiny("a", Species::Pikachu, 1, 10),
+            shiny("b", Species::Eevee, 2, 10),
+        ];
+        let other = shiny("b", Species::Eevee, 2, 18);
+        assert_eq!(find(&local, &other, &HashSet::from([1])), None);
+    }
+
+    #[test]
+    fn same_species_on_the_same_day_matches_once() {
+        let local = [
+            shiny("a", Species::Pikachu, 1, 10),
+            shiny("b", Species::Pikachu, 1, 20),
+        ];
+        let other = shiny("c", Species::Pikachu, 1, 18);
+        assert_eq!(find(&local, &other, &HashSet::new()), Some(0));
+        assert_eq!(find(&local, &other, &HashSet::from([0])), Some(1));
+        assert_eq!(find(&local, &other, &HashSet::from([0, 1])), None);
+    }
+
+    #[test]
+    fn other_species_days_or_unknown_dates_dont_match() {
+        let local = [shiny("a", Species::Pikachu, 1, 10)];
+        assert_eq!(
+            find(&local, &shiny("b", Species::Eevee, 1, 10), &HashSet::new()),
+            None
+        );
+        assert_eq!(
+            find(
+                &local,
+                &shiny("b", Species::Pikachu, 2, 10),
+                &HashSet::new()
+            ),
+            None
+        );
+        let undated = Shiny {
+            found_time: None,
+            ..shiny("b", Species::Pikachu, 1, 10)
+        };
+        assert_eq!(find(&local, &undated, &HashSet::new()), None);
+    }
+
+    #[test]
+    fn differences_list_the_changed_fields() {
+        let local = shiny("a", Species::Pikachu, 1, 10);
+        let other = Shiny {
+            place: Some("Route 1".into()),
+            ..local.clone()
+        };
+        let differences = differences(shiny_fields(&local), shiny_fields(&other));
+        assert_eq!(differences.len(), 1);
+        assert_eq!(differences[0].label, "Zone");
+        assert_eq!(differences[0].other, "Route 1");
+    }
+}
@@ -17,6 +17,11 @@ pub struct Hunt {
     pub method: Option<String>,
     pub place: Option<String>,
     pub notes: Option<String>,
+    pub uuid: String,
+    pub alert_every: i32,
+    pub alert_base_odds: bool,
+    pub alert_probabilities: String,
+    pub alert_sound: bool,
 }
 
 #[derive(Debug, Queryable, Selectable, Identifiable, Associations, AsChangeset)]
@@ -37,4 +42,53 @@ pub struct Shiny {
     pub place: Option<String>,
     pub notes: Option<String>,
     pub hunt_id: Option<i32>,
+    pub uuid: String,
+}
+
+#[derive(Debug, Queryable, Selectable, Identifiable, Insertable, Associations)]
+#[diesel(table_name = crate::schema::hunt_targets)]
+#[diesel(primary_key(hunt_id, species))]
+#[diesel(belongs_to(Hunt))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct HuntTarget {
+    pub hunt_id: i32,
+    pub species: i32,
+    pub position: i32,
+}
+
+#[derive(Debug, Queryable, Selectable, Identifiable, Associations, AsChangeset)]
+#[diesel(table_name = crate::schema::sessions)]
+#[diesel(belongs_to(Hunt))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Session {
+    pub id: i32,
+    pub hunt_id: i32,
+    pub phase: i32,
+    pub start_time: NaiveDateTime,
+    pub end_time: NaiveDateTime,
+    pub encounters: i32,
+}
+
+#[derive(Debug, Queryable, Selectable, Insertable, AsChangeset)]
+#[diesel(table_name = crate::schema::settings)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Setting {
+    pub key: String,
+    pub value: String,
+}
+
+#[derive(Debug, Queryable, Selectable, Identifiable, AsChangeset)]
+#[diesel(table_name = crate::schema::counters)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct Counter {
+    pub id: i32,
+    pub position: i32,
+    pub hunt_id: Option<i32>,
+    pub increment: i32,
+    pub count: i32,
+    pub mode: String,
+    pub chain: i32,
+    pub best_chain: i32,
+    pub broken_chains: i32,
+    pub cycle_ms: i32,
 }
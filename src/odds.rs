@@ -0,0 +1,571 @@
+use crate::data::Species;
+
+/// Main series games, used to work out shiny odds from the free text version
+/// of hunts and shinies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameVersion {
+    Gold,
+    Silver,
+    Crystal,
+    Ruby,
+    Sapphire,
+    Emerald,
+    FireRed,
+    LeafGreen,
+    Diamond,
+    Pearl,
+    Platinum,
+    HeartGold,
+    SoulSilver,
+    Black,
+    White,
+    Black2,
+    White2,
+    X,
+    Y,
+    OmegaRuby,
+    AlphaSapphire,
+    Sun,
+    Moon,
+    UltraSun,
+    UltraMoon,
+    LetsGoPikachu,
+    LetsGoEevee,
+    Sword,
+    Shield,
+    BrilliantDiamond,
+    ShiningPearl,
+    LegendsArceus,
+    Scarlet,
+    Violet,
+}
+
+impl GameVersion {
+    pub const ALL: [GameVersion; 34] = [
+        GameVersion::Gold,
+        GameVersion::Silver,
+        GameVersion::Crystal,
+        GameVersion::Ruby,
+        GameVersion::Sapphire,
+        GameVersion::Emerald,
+        GameVersion::FireRed,
+        GameVersion::LeafGreen,
+        GameVersion::Diamond,
+        GameVersion::Pearl,
+        GameVersion::Platinum,
+        GameVersion::HeartGold,
+        GameVersion::SoulSilver,
+        GameVersion::Black,
+        GameVersion::White,
+        GameVersion::Black2,
+        GameVersion::White2,
+        GameVersion::X,
+        GameVersion::Y,
+        GameVersion::OmegaRuby,
+        GameVersion::AlphaSapphire,
+        GameVersion::Sun,
+        GameVersion::Moon,
+        GameVersion::UltraSun,
+        GameVersion::UltraMoon,
+        GameVersion::LetsGoPikachu,
+        GameVersion::LetsGoEevee,
+        GameVersion::Sword,
+        GameVersion::Shield,
+        GameVersion::BrilliantDiamond,
+        GameVersion::ShiningPearl,
+        GameVersion::LegendsArceus,
+        GameVersion::Scarlet,
+        GameVersion::Violet,
+    ];
+
+    pub fn generation(&self) -> u8 {
+        match self {
+            GameVersion::Gold | GameVersion::Silver | GameVersion::Crystal => 2,
+            GameVersion::Ruby
+            | GameVersion::Sapphire
+            | GameVersion::Emerald
+            | GameVersion::FireRed
+            | GameVersion::LeafGreen => 3,
+            GameVersion::Diamond
+            | GameVersion::Pearl
+            | GameVersion::Platinum
+            | GameVersion::HeartGold
+            | GameVersion::SoulSilver => 4,
+            GameVersion::Black | GameVersion::White | GameVersion::Black2 | GameVersion::White2 => {
+                5
+            }
+            GameVersion::X
+            | GameVersion::Y
+            | GameVersion::OmegaRuby
+            | GameVersion::AlphaSapphire => 6,
+            GameVersion::Sun
+            | GameVersion::Moon
+            | GameVersion::UltraSun
+            | GameVersion::UltraMoon
+            | GameVersion::LetsGoPikachu
+            | GameVersion::LetsGoEevee => 7,
+            GameVersion::Sword
+            | GameVersion::Shield
+            | GameVersion::BrilliantDiamond
+            | GameVersion::ShiningPearl
+            | GameVersion::LegendsArceus => 8,
+            GameVersion::Scarlet | GameVersion::Violet => 9,
+        }
+    }
+
+    /// Whether the species can be found in the game. Up to the seventh
+    /// generation, every species introduced up to the generation of the game
+    /// is considered available, except in Let's Go which only has the first
+    /// generation and Meltan. Brilliant Diamond and Shining Pearl have the
+    /// species of the fourth generation, and Legends Arceus those of the
+    /// fourth generation along with the later species of the Hisui dex.
+    /// Sword, Shield, Scarlet and Violet only have a regional dex with no
+    /// data here, so no species is left out of them.
+    pub fn has_species(&self, species: Species) -> bool {
+        match self {
+            GameVersion::LetsGoPikachu | GameVersion::LetsGoEevee => {
+                species.generation() == 1 || matches!(species, Species::Meltan | Species::Melmetal)
+            }
+            GameVersion::BrilliantDiamond | GameVersion::ShiningPearl => species.generation() <= 4,
+            GameVersion::LegendsArceus => {
+                species.generation() <= 4
+                    || matches!(
+                        i32::from(species),
+                        501..=503
+                            | 548..=550
+                            | 570..=571
+                            | 627..=628
+                            | 700
+                            | 704..=706
+                            | 712..=713
+                            | 722..=724
+                            | 899..=905
+                    )
+            }
+            GameVersion::Sword
+            | GameVersion::Shield
+            | GameVersion::Scarlet
+            | GameVersion::Violet => true,
+            _ => species.generation() <= self.generation(),
+        }
+    }
+
+    /// Full odds of the game, without any bonus
+    pub fn base_odds(&self) -> f64 {
+        if self.generation() <= 5 {
+            8192.0
+        } else {
+            4096.0
+        }
+    }
+
+    fn english_name(&self) -> &'static str {
+        match self {
+            GameVersion::Gold => "Gold",
+            GameVersion::Silver => "Silver",
+            GameVersion::Crystal => "Crystal",
+            GameVersion::Ruby => "Ruby",
+            GameVersion::Sapphire => "Sapphire",
+            GameVersion::Emerald => "Emerald",
+            GameVersion::FireRed => "FireRed",
+            GameVersion::LeafGreen => "LeafGreen",
+            GameVersion::Diamond => "Diamond",
+            GameVersion::Pearl => "Pearl",
+            GameVersion::Platinum => "Platinum",
+            GameVersion::HeartGold => "HeartGold",
+            GameVersion::SoulSilver => "SoulSilver",
+            GameVersion::Black => "Black",
+            GameVersion::White => "White",
+            GameVersion::Black2 => "Black 2",
+            GameVersion::White2 => "White 2",
+            GameVersion::X => "X",
+            GameVersion::Y => "Y",
+            GameVersion::OmegaRuby => "Omega Ruby",
+            GameVersion::AlphaSapphire => "Alpha Sapphire",
+            GameVersion::Sun => "Sun",
+            GameVersion::Moon => "Moon",
+            GameVersion::UltraSun => "Ultra Sun",
+            GameVersion::UltraMoon => "Ultra Moon",
+            GameVersion::LetsGoPikachu => "Let's Go Pikachu",
+            GameVersion::LetsGoEevee => "Let's Go Eevee",
+            GameVersion::Sword => "Sword",
+            GameVersion::Shield => "Shield",
+            GameVersion::BrilliantDiamond => "Brilliant Diamond",
+            GameVersion::ShiningPearl => "Shining Pearl",
+            GameVersion::LegendsArceus => "Legends Arceus",
+            GameVersion::Scarlet => "Scarlet",
+            GameVersion::Violet => "Violet",
+        }
+    }
+
+    /// Name of the folder of the game's sprites, such as `lets-go-pikachu`
+    pub fn folder_name(&self) -> String {
+        self.english_name()
+            .to_lowercase()
+            .replace(' ', "-")
+            .chars()
+            .filter(|c| c.is_alphanumeric() || *c == '-')
+            .collect()
+    }
+
+    /// Recognizes the French or English name of a game, with or without the
+    /// "Pokémon" prefix, ignoring case and punctuation.
+    pub fn from_name(name: &str) -> Option<GameVersion> {
+        fn normalize(name: &str) -> String {
+            let name: String = name
+                .to_lowercase()
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .collect();
+            name.strip_prefix("pokémon")
+                .or(name.strip_prefix("pokemon"))
+                .map(String::from)
+                .unwrap_or(name)
+        }
+
+        let name = normalize(name);
+        GameVersion::ALL.into_iter().find(|version| {
+            normalize(&version.to_string()) == name || normalize(version.english_name()) == name
+        })
+    }
+}
+
+impl std::fmt::Display for GameVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            GameVersion::Gold => "Or",
+            GameVersion::Silver => "Argent",
+            GameVersion::Crystal => "Cristal",
+            GameVersion::Ruby => "Rubis",
+            GameVersion::Sapphire => "Saphir",
+            GameVersion::Emerald => "Émeraude",
+            GameVersion::FireRed => "Rouge Feu",
+            GameVersion::LeafGreen => "Vert Feuille",
+            GameVersion::Diamond => "Diamant",
+            GameVersion::Pearl => "Perle",
+            GameVersion::Platinum => "Platine",
+            GameVersion::HeartGold => "Or HeartGold",
+            GameVersion::SoulSilver => "Argent SoulSilver",
+            GameVersion::Black => "Noir",
+            GameVersion::White => "Blanc",
+            GameVersion::Black2 => "Noir 2",
+            GameVersion::White2 => "Blanc 2",
+            GameVersion::X => "X",
+            GameVersion::Y => "Y",
+            GameVersion::OmegaRuby => "Rubis Oméga",
+            GameVersion::AlphaSapphire => "Saphir Alpha",
+            GameVersion::Sun => "Soleil",
+            GameVersion::Moon => "Lune",
+            GameVersion::UltraSun => "Ultra-Soleil",
+            GameVersion::UltraMoon => "Ultra-Lune",
+            GameVersion::LetsGoPikachu => "Let's Go Pikachu",
+            GameVersion::LetsGoEevee => "Let's Go Évoli",
+            GameVersion::Sword => "Épée",
+            GameVersion::Shield => "Bouclier",
+            GameVersion::BrilliantDiamond => "Diamant Étincelant",
+            GameVersion::ShiningPearl => "Perle Scintillante",
+            GameVersion::LegendsArceus => "Légendes Arceus",
+            GameVersion::Scarlet => "Écarlate",
+            GameVersion::Violet => "Violet",
+        })
+    }
+}
+
+/// Full odds for the free text version of a hunt, 1/4096 when the game isn't
+/// recognized.
+pub fn base_odds(version: Option<&str>) -> f64 {
+    version
+        .and_then(GameVersion::from_name)
+        .map(|version| version.base_odds())
+        .unwrap_or(4096.0)
+}
+
+/// Odds of an encounter for the free text version and method of a hunt. The
+/// Masuda method and the Shiny Charm add rolls, and chaining methods are
+/// counted at the chain where their odds stop improving.
+pub fn method_odds(version: Option<&str>, method: Option<&str>) -> f64 {
+    let game = version.and_then(GameVersion::from_name);
+    let base_odds = base_odds(version);
+    let method = method.unwrap_or_default().to_lowercase();
+    let words: Vec<&str> = method.split(|c: char| !c.is_alphanumeric()).collect();
+    let has = |word: &str| words.contains(&word);
+
+    let mut extra_rolls = 0;
+    if has("masuda") {
+        extra_rolls += match game.map(|game| game.generation()) {
+            Some(4) => 4,
+            _ => 5,
+        };
+    }
+    if has("charme") || has("charm") {
+        extra_rolls += 2;
+    }
+    let odds = ChainMethod::from_method(&method)
+        .map(|chain_method| chain_method.odds(chain_method.best_chain(), game))
+        .unwrap_or(base_odds);
+    with_extra_rolls(odds, base_odds, extra_rolls)
+}
+
+/// Odds of an encounter rolling `extra_rolls` times at `base_odds` on top of
+/// its roll at `odds`
+fn with_extra_rolls(odds: f64, base_odds: f64, extra_rolls: u32) -> f64 {
+    1.0 / (1.0 - (1.0 - 1.0 / odds) * (1.0 - 1.0 / base_odds).powi(extra_rolls as i32))
+}
+
+/// Odds of an encounter rolling `rolls` times at the given odds
+pub fn with_rolls(odds: f64, rolls: u32) -> f64 {
+    1.0 / (1.0 - (1.0 - 1.0 / odds).powi(rolls as i32))
+}
+
+/// Probability of having found at least one shiny after `encounters`
+/// encounters at the given odds
+pub fn cumulative_probability(odds: f64, encounters: i32) -> f64 {
+    1.0 - (1.0 - 1.0 / odds).powi(encounters.max(0))
+}
+
+/// Number of encounters after which the probability of having found a shiny
+/// reaches `probability`
+pub fn encounters_for_probability(odds: f64, probability: f64) -> f64 {
+    (1.0 - probability).ln() / (1.0 - 1.0 / odds).ln()
+}
+
+/// Percentile of `encounters` under the geometric distribution of the
+/// encounters needed to find a shiny: the share of hunts at these odds that
+/// would have ended sooner. The lower, the luckier.
+pub fn luck_percentile(odds: f64, encounters: i32) -> f64 {
+    // The shiny itself is the last encounter
+    cumulative_probability(odds, encounters - 1)
+}
+
+pub fn format_luck(percentile: f64) -> String {
+    format!(
+        "{:.0}e centile ({})",
+        percentile * 100.0,
+        if percentile < 0.5 {
+            "chanceux"
+        } else {
+            "malchanceux"
+        }
+    )
+}
+
+/// Ways of hunting where the odds depend on the length of a chain of
+/// encounters rather than on their number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChainMethod {
+    PokeRadar,
+    ChainFishing,
+    Sos,
+    LetsGoCombo,
+}
+
+impl ChainMethod {
+    pub const ALL: [ChainMethod; 4] = [
+        ChainMethod::PokeRadar,
+        ChainMethod::ChainFishing,
+        ChainMethod::Sos,
+        ChainMethod::LetsGoCombo,
+    ];
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            ChainMethod::PokeRadar => "poke_radar",
+            ChainMethod::ChainFishing => "chain_fishing",
+            ChainMethod::Sos => "sos",
+            ChainMethod::LetsGoCombo => "lets_go_combo",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<ChainMethod> {
+        ChainMethod::ALL
+            .into_iter()
+            .find(|method| method.key() == key)
+    }
+
+    /// Chaining method named in the free text method of a hunt
+    pub fn from_method(method: &str) -> Option<ChainMethod> {
+        let method = method.to_lowercase();
+        let words: Vec<&str> = method.split(|c: char| !c.is_alphanumeric()).collect();
+        if method.contains("radar") {
+            Some(ChainMethod::PokeRadar)
+        } else if ["pêche", "peche", "fishing"]
+            .into_iter()
+            .any(|word| words.contains(&word))
+        {
+            Some(ChainMethod::ChainFishing)
+        } else if words.contains(&"sos") || method.contains("appel") {
+            Some(ChainMethod::Sos)
+        } else if words.contains(&"combo") {
+            Some(ChainMethod::LetsGoCombo)
+        } else {
+            None
+        }
+    }
+
+    /// Chain from which the odds stop improving
+    pub fn best_chain(&self) -> i32 {
+        match self {
+            ChainMethod::PokeRadar => 40,
+            ChainMethod::ChainFishing => 20,
+            ChainMethod::Sos | ChainMethod::LetsGoCombo => 31,
+        }
+    }
+
+    /// Odds of the next encounter with the current chain in the game, at
+    /// 1/4096 before any chain when the game isn't known.
+    pub fn odds(&self, chain: i32, game: Option<GameVersion>) -> f64 {
+        let base_odds = game.map_or(4096.0, |game| game.base_odds());
+        match self {
+            // Odds improve steadily up to a chain of 40, where a patch has
+            // 41 chances in 65536 to be shiny in the fourth generation and
+            // about one chance in 200 from X and Y on
+            ChainMethod::PokeRadar => {
+                let best_odds = match game.map(|game| game.generation()) {
+                    Some(4) => 65536.0 / 41.0,
+                    _ => 200.0,
+                };
+                let chain = chain.clamp(0, 40) as f64;
+                base_odds - (base_odds - best_odds) * chain / 40.0
+            }
+            // Two extra rolls per hooked Pokémon, up to a chain of 20
+            ChainMethod::ChainFishing => with_rolls(base_odds, 1 + 2 * chain.clamp(0, 20) as u32),
+            ChainMethod::Sos => {
+                let extra_rolls = match chain {
+                    ..=10 => 0,
+                    11..=20 => 4,
+                    21..=30 => 8,
+                    _ => 12,
+                };
+                with_rolls(base_odds, 1 + extra_rolls)
+            }
+            ChainMethod::LetsGoCombo => {
+                let extra_rolls = match chain {
+                    ..=10 => 0,
+                    11..=20 => 3,
+                    21..=30 => 7,
+                    _ => 11,
+                };
+                with_rolls(base_odds, 1 + extra_rolls)
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ChainMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ChainMethod::PokeRadar => "Poké Radar",
+            ChainMethod::ChainFishing => "Pêche à la chaîne",
+            ChainMethod::Sos => "Appels à l'aide (SOS)",
+            ChainMethod::LetsGoCombo => "Combo Let's Go",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 0.01,
+            "{actual} instead of {expected}"
+        );
+    }
+
+    #[test]
+    fn poke_radar_odds_improve_up_to_a_chain_of_40() {
+        let method = ChainMethod::PokeRadar;
+        assert_close(method.odds(0, None), 4096.0);
+        assert_close(method.odds(20, None), 2148.0);
+        assert_close(method.odds(40, None), 200.0);
+        assert_close(method.odds(100, None), 200.0);
+        assert_close(method.odds(0, Some(GameVersion::Pearl)), 8192.0);
+        assert_close(method.odds(40, Some(GameVersion::Pearl)), 1598.44);
+    }
+
+    #[test]
+    fn rolling_chain_methods_add_rolls_by_steps() {
+        assert_close(ChainMethod::ChainFishing.odds(0, None), 4096.0);
+        assert_close(
+            ChainMethod::ChainFishing.odds(20, None),
+            with_rolls(4096.0, 41),
+        );
+        assert_close(
+            ChainMethod::ChainFishing.odds(50, None),
+            with_rolls(4096.0, 41),
+        );
+        assert_close(ChainMethod::Sos.odds(10, None), 4096.0);
+        assert_close(ChainMethod::Sos.odds(11, None), with_rolls(4096.0, 5));
+        assert_close(ChainMethod::Sos.odds(31, None), with_rolls(4096.0, 13));
+        assert_close(
+            ChainMethod::LetsGoCombo.odds(25, None),
+            with_rolls(4096.0, 8),
+        );
+    }
+
+    #[test]
+    fn encounters_for_probability_matches_the_cumulative_probability() {
+        let encounters = encounters_for_probability(4096.0, 0.5);
+        assert_close(encounters, 2838.78);
+        assert!(cumulative_probability(4096.0, encounters.ceil() as i32) >= 0.5);
+        assert!(cumulative_probability(4096.0, encounters.floor() as i32) < 0.5);
+        assert_close(encounters_for_probability(4096.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn method_odds_add_the_rolls_of_the_method() {
+        assert_close(method_odds(None, None), 4096.0);
+        assert_close(method_odds(Some("Rubis"), Some("Rencontres")), 8192.0);
+        assert_close(
+            method_odds(Some("Écarlate"), Some("Charme Chroma")),
+            with_rolls(4096.0, 3),
+        );
+        assert_close(
+            method_odds(Some("Platine"), Some("Masuda")),
+            with_rolls(8192.0, 5),
+        );
+        assert_close(
+            method_odds(Some("Soleil"), Some("Masuda + charme")),
+            with_rolls(4096.0, 8),
+        );
+        assert_close(method_odds(Some("Perle"), Some("Poké Radar")), 1598.44);
+        assert_close(method_odds(Some("Y"), Some("Poké Radar")), 200.0);
+        assert_close(
+            method_odds(Some("X"), Some("Pêche à la chaîne, charme")),
+            with_rolls(4096.0, 43),
+        );
+    }
+
+    #[test]
+    fn chain_methods_are_recognized_in_the_method_text() {
+        assert_eq!(
+            ChainMethod::from_method("PokéRadar"),
+            Some(ChainMethod::PokeRadar)
+        );
+        assert_eq!(
+            ChainMethod::from_method("Appels à l'aide"),
+            Some(ChainMethod::Sos)
+        );
+        assert_eq!(ChainMethod::from_method("SOS"), Some(ChainMethod::Sos));
+        assert_eq!(
+            ChainMethod::from_method("Combo de capture"),
+            Some(ChainMethod::LetsGoCombo)
+        );
+        assert_eq!(ChainMethod::from_method("Soft reset"), None);
+    }
+
+    #[test]
+    fn games_have_the_species_of_their_dex() {
+        assert!(GameVersion::Diamond.has_species(Species::Pikachu));
+        assert!(!GameVersion::Diamond.has_species(Species::Pecharunt));
+        assert!(!GameVersion::LetsGoPikachu.has_species(Species::Chikorita));
+        assert!(GameVersion::LetsGoPikachu.has_species(Species::Meltan));
+        assert!(GameVersion::Sword.has_species(Species::Pecharunt));
+        assert!(GameVersion::ShiningPearl.has_species(Species::Arceus));
+        assert!(!GameVersion::ShiningPearl.has_species(Species::Snivy));
+        assert!(GameVersion::LegendsArceus.has_species(Species::Rowlet));
+        assert!(GameVersion::LegendsArceus.has_species(Species::Enamorus));
+        assert!(!GameVersion::LegendsArceus.has_species(Species::Snivy));
+    }
+}
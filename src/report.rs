@@ -0,0 +1,357 @@
+use crate::hunt::Hunt;
+use crate::shiny::Shiny;
+
+use chrono::{DateTime, Local};
+
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReportFormat {
+    Markdown,
+    BBCode,
+}
+
+impl ReportFormat {
+    pub const ALL: [ReportFormat; 2] = [ReportFormat::Markdown, ReportFormat::BBCode];
+
+    fn key(&self) -> &'static str {
+        match self {
+            ReportFormat::Markdown => "markdown",
+            ReportFormat::BBCode => "bbcode",
+        }
+    }
+
+    fn wrap_list(&self, items: Vec<String>) -> String {
+        match self {
+            ReportFormat::Markdown => items.join("\n"),
+            ReportFormat::BBCode => format!("[list]\n{}\n[/list]", items.join("\n")),
+        }
+    }
+}
+
+impl std::fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ReportFormat::Markdown => "Markdown",
+            ReportFormat::BBCode => "BBCode",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReportKind {
+    Shiny,
+    Hunt,
+    ShinyListItem,
+    HuntListItem,
+}
+
+impl ReportKind {
+    pub const ALL: [ReportKind; 4] = [
+        ReportKind::Shiny,
+        ReportKind::Hunt,
+        ReportKind::ShinyListItem,
+        ReportKind::HuntListItem,
+    ];
+
+    fn key(&self) -> &'static str {
+        match self {
+            ReportKind::Shiny => "shiny",
+            ReportKind::Hunt => "hunt",
+            ReportKind::ShinyListItem => "shiny_list_item",
+            ReportKind::HuntListItem => "hunt_list_item",
+        }
+    }
+
+    /// Placeholders understood by the templates of this kind.
+    pub fn placeholders(&self) -> &'static [&'static str] {
+        match self {
+            ReportKind::Shiny | ReportKind::ShinyListItem => &[
+                "species",
+                "name",
+                "gender",
+                "encounters",
+                "phase_encounters",
+                "phase",
+                "version",
+                "method",
+                "place",
+                "found_date",
+                "notes",
+            ],
+            ReportKind::Hunt | ReportKind::HuntListItem => &[
+                "species",
+                "encounters",
+                "phase_encounters",
+                "phase",
+                "status",
+                "version",
+                "method",
+                "place",
+                "start_date",
+                "end_date",
+                "notes",
+            ],
+        }
+    }
+
+    fn default_template(&self, format: ReportFormat) -> &'static str {
+        match (self, format) {
+            (ReportKind::Shiny, ReportFormat::Markdown) => {
+                "**✨ {species}** {name}\n\
+                 - Rencontres : {encounters} (phase {phase} : {phase_encounters})\n\
+                 - Version : {version}\n\
+                 - Méthode : {method}\n\
+                 - Zone : {place}\n\
+                 - Trouvé le {found_date}"
+            }
+            (ReportKind::Shiny, ReportFormat::BBCode) => {
+                "[b]✨ {species}[/b] {name}\n\
+                 [list]\n\
+                 [*]Rencontres : {encounters} (phase {phase} : {phase_encounters})\n\
+                 [*]Version : {version}\n\
+                 [*]Méthode : {method}\n\
+                 [*]Zone : {place}\n\
+                 [*]Trouvé le {found_date}\n\
+                 [/list]"
+            }
+            (ReportKind::Hunt, ReportFormat::Markdown) => {
+                "**{species}** ({status})\n\
+                 - Rencontres : {encounters} (phase {phase} : {phase_encounters})\n\
+                 - Version : {version}\n\
+                 - Méthode : {method}\n\
+                 - Zone : {place}\n\
+                 - Débutée le {start_date}"
+            }
+            (ReportKind::Hunt, ReportFormat::BBCode) => {
+                "[b]{species}[/b] ({status})\n\
+                 [list]\n\
+                 [*]Rencontres : {encounters} (phase {phase} : {phase_encounters})\n\
+                 [*]Version : {version}\n\
+                 [*]Méthode : {method}\n\
+                 [*]Zone : {place}\n\
+                 [*]Débutée le {start_date}\n\
+                 [/list]"
+            }
+            (ReportKind::ShinyListItem, ReportFormat::Markdown) => {
+                "- ✨ **{species}** : {encounters} rencontres ({method}, {version}), le {found_date}"
+            }
+            (ReportKind::ShinyListItem, ReportFormat::BBCode) => {
+                "[*]✨ [b]{species}[/b] : {encounters} rencontres ({method}, {version}), le {found_date}"
+            }
+            (ReportKind::HuntListItem, ReportFormat::Markdown) => {
+                "- **{species}** : {encounters} rencontres, phase {phase} ({method}, {version})"
+            }
+            (ReportKind::HuntListItem, ReportFormat::BBCode) => {
+                "[*][b]{species}[/b] : {encounters} rencontres, phase {phase} ({method}, {version})"
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ReportKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            ReportKind::Shiny => "Shiny",
+            ReportKind::Hunt => "Recherche",
+            ReportKind::ShinyListItem => "Liste de shinies (ligne)",
+            ReportKind::HuntListItem => "Liste de recherches (ligne)",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ReportTemplates {
+    custom: HashMap<(ReportKind, ReportFormat), String>,
+}
+
+impl ReportTemplates {
+    fn setting_key(kind: ReportKind, format: ReportFormat) -> String {
+        format!("report.{}.{}", kind.key(), format.key())
+    }
+
+    /// Reads the customized templates out of the raw settings values.
+    pub fn from_values(values: &HashMap<String, String>) -> Self {
+        let mut custom = HashMap::new();
+        for kind in ReportKind::ALL {
+            for format in ReportFormat::ALL {
+                if let Some(template) = values.get(&Self::setting_key(kind, format)) {
+                    custom.insert((kind, format), template.clone());
+                }
+            }
+        }
+        Self { custom }
+    }
+
+    pub fn to_values(&self) -> Vec<(String, String)> {
+        self.custom
+            .iter()
+            .map(|((kind, format), template)| (Self::setting_key(*kind, *format), template.clone()))
+            .collect()
+    }
+
+    pub fn get(&self, kind: ReportKind, format: ReportFormat) -> &str {
+        self.custom
+            .get(&(kind, format))
+            .map(|template| template.as_str())
+            .unwrap_or(kind.default_template(format))
+    }
+
+    pub fn set(&mut self, kind: ReportKind, format: ReportFormat, template: String) {
+        if template == kind.default_template(format) {
+            self.custom.remove(&(kind, format));
+        } else {
+            self.custom.insert((kind, format), template);
+        }
+    }
+
+    pub fn reset(&mut self, kind: ReportKind, format: ReportFormat) {
+        self.custom.remove(&(kind, format));
+    }
+
+    pub fn shiny_report(&self, shiny: &Shiny, format: ReportFormat) -> String {
+        render(self.get(ReportKind::Shiny, format), &shiny.report_values())
+    }
+
+    pub fn hunt_report(&self, hunt: &Hunt, format: ReportFormat) -> String {
+        render(self.get(ReportKind::Hunt, format), &hunt.report_values())
+    }
+
+    pub fn shinies_report<'a>(
+        &self,
+        shinies: impl Iterator<Item = &'a Shiny>,
+        format: ReportFormat,
+    ) -> String {
+        let template = self.get(ReportKind::ShinyListItem, format);
+        format.wrap_list(
+            shinies
+                .map(|shiny| render(template, &shiny.report_values()))
+                .collect(),
+        )
+    }
+
+    pub fn hunts_report<'a>(
+        &self,
+        hunts: impl Iterator<Item = &'a Hunt>,
+        format: ReportFormat,
+    ) -> String {
+        let template = self.get(ReportKind::HuntListItem, format);
+        format.wrap_list(
+            hunts
+                .map(|hunt| render(template, &hunt.report_values()))
+                .collect(),
+        )
+    }
+}
+
+/// Replaces every `{placeholder}` of the template by its value. Unknown
+/// placeholders are left untouched so that typos stay visible in the output.
+pub fn render(template: &str, values: &HashMap<&'static str, String>) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after.find('}') {
+            Some(end) if values.contains_key(&after[..end]) => {
+                output.push_str(&values[&after[..end]]);
+                rest = &after[end + 1..];
+            }
+            _ => {
+                output.push('{');
+                rest = after;
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+fn format_date(date: Option<DateTime<Local>>) -> String {
+    date.map(|dt| {
+        dt.format_localized("%-d %B %Y", chrono::Locale::fr_FR)
+            .to_string()
+    })
+    .unwrap_or("date inconnue".into())
+}
+
+fn format_count(count: Option<i32>) -> String {
+    count.map(|c| c.to_string()).unwrap_or("???".into())
+}
+
+impl Shiny {
+    pub fn report_values(&self) -> HashMap<&'static str, String> {
+        HashMap::from([
+            ("species", self.species.to_string()),
+            ("name", self.name.clone().unwrap_or_default()),
+            (
+                "gender",
+                match self.gender {
+                    Some(0) => "♀".into(),
+                    Some(1) => "♂".into(),
+                    _ => "".into(),
+                },
+            ),
+            ("encounters", format_count(self.total_encounters)),
+            ("phase_encounters", format_count(self.phase_encounters)),
+            ("phase", format_count(self.phase_number)),
+            ("version", self.version.clone().unwrap_or("Inconnue".into())),
+            ("method", self.method.clone().unwrap_or("Inconnue".into())),
+            ("place", self.place.clone().unwrap_or("Inconnue".into())),
+            ("found_date", format_date(self.found_time)),
+            ("notes", self.notes.clone().unwrap_or_default()),
+        ])
+    }
+}
+
+impl Hunt {
+    pub fn report_values(&self) -> HashMap<&'static str, String> {
+        HashMap::from([
+            ("species", self.target.to_string()),
+            (
+                "encounters",
+                (self.previous_encounters + self.phase_encounters).to_string(),
+            ),
+            ("phase_encounters", self.phase_encounters.to_string()),
+            ("phase", self.phase_count.to_string()),
+            (
+                "status",
+                if self.completed {
+                    "terminée".into()
+                } else {
+                    "en cours".into()
+                },
+            ),
+            ("version", self.version.clone().unwrap_or("Inconnue".into())),
+            ("method", self.method.clone().unwrap_or("Inconnue".into())),
+            ("place", self.place.clone().unwrap_or("Inconnue".into())),
+            ("start_date", format_date(self.start_time)),
+            ("end_date", format_date(self.end_time)),
+            ("notes", self.notes.clone().unwrap_or_default()),
+        ])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_replaces_known_placeholders() {
+        let values = HashMap::from([("species", "Pikachu".to_string()), ("phase", "2".into())]);
+        assert_eq!(
+            render("{species} trouvé en phase {phase} !", &values),
+            "Pikachu trouvé en phase 2 !"
+        );
+    }
+
+    #[test]
+    fn render_keeps_unknown_placeholders_and_lone_braces() {
+        let values = HashMap::from([("species", "Évoli".to_string())]);
+        assert_eq!(
+            render("{{species}} {specie} {species", &values),
+            "{Évoli} {specie} {species"
+        );
+        assert_eq!(render("}{", &values), "}{");
+    }
+}
@@ -1,5 +1,28 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    counters (id) {
+        id -> Integer,
+        position -> Integer,
+        hunt_id -> Nullable<Integer>,
+        increment -> Integer,
+        count -> Integer,
+        mode -> Text,
+        chain -> Integer,
+        best_chain -> Integer,
+        broken_chains -> Integer,
+        cycle_ms -> Integer,
+    }
+}
+
+diesel::table! {
+    hunt_targets (hunt_id, species) {
+        hunt_id -> Integer,
+        species -> Integer,
+        position -> Integer,
+    }
+}
+
 diesel::table! {
     hunts (id) {
         id -> Integer,
@@ -14,6 +37,29 @@ diesel::table! {
         method -> Nullable<Text>,
         place -> Nullable<Text>,
         notes -> Nullable<Text>,
+        uuid -> Text,
+        alert_every -> Integer,
+        alert_base_odds -> Bool,
+        alert_probabilities -> Text,
+        alert_sound -> Bool,
+    }
+}
+
+diesel::table! {
+    sessions (id) {
+        id -> Integer,
+        hunt_id -> Integer,
+        phase -> Integer,
+        start_time -> Timestamp,
+        end_time -> Timestamp,
+        encounters -> Integer,
+    }
+}
+
+diesel::table! {
+    settings (key) {
+        key -> Text,
+        value -> Text,
     }
 }
 
@@ -32,12 +78,12 @@ diesel::table! {
         place -> Nullable<Text>,
         notes -> Nullable<Text>,
         hunt_id -> Nullable<Integer>,
+        uuid -> Text,
     }
 }
 
+diesel::joinable!(hunt_targets -> hunts (hunt_id));
+diesel::joinable!(sessions -> hunts (hunt_id));
 diesel::joinable!(shinies -> hunts (hunt_id));
 
-diesel::allow_tables_to_appear_in_same_query!(
-    hunts,
-    shinies,
-);
+diesel::allow_tables_to_appear_in_same_query!(hunt_targets, hunts, sessions, settings, shinies,);
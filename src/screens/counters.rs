@@ -1,11 +1,23 @@
-use crate::counter::{Counter, CounterEditAction};
-use crate::theme::{card, counter_button, navbar};
+use crate::counter::{Counter, CounterEditAction, CounterMode, MIN_CYCLE_MS};
+use crate::data::Species;
+use crate::goals::{self, Goal, GoalMeasure, GoalPeriod, GoalTarget};
+use crate::hunt::Hunt;
+use crate::keybindings::{CounterCommand, KeyBinding};
+use crate::odds::{self, GameVersion};
+use crate::session::{encounter_rate, format_duration};
+use crate::theme::{alert_banner, card, counter_button, focused_card, navbar};
 use crate::State;
-use iced::alignment::Horizontal;
+use chrono::{DateTime, Local, TimeDelta};
+use iced::alignment::{Horizontal, Vertical};
+use iced::keyboard::key::Named;
+use iced::keyboard::{Key, Modifiers};
 use iced::widget::{
-    button, column, container, horizontal_space, row, stack, svg, text, text_input, Container,
+    button, checkbox, column, container, horizontal_space, mouse_area, pick_list, progress_bar,
+    responsive, row, scrollable, stack, svg, text, text_input, Column, Container,
 };
-use iced::{Element, Length};
+use iced::{Element, Length, Size};
+
+use std::collections::HashMap;
 
 const COG_ICON: &[u8] = include_bytes!("../../assets/cog.svg");
 const STARS_ICON: &[u8] = include_bytes!("../../assets/stars.svg");
@@ -17,13 +29,38 @@ pub enum CountersMessage {
     StartEditCounter(usize),
     StopEditCounter,
     ShinyFound(usize),
-    SelectHunt(usize),
+    SelectHunt(i32),
     UnsetHunt,
     EditIncrement(String),
     EditCount(String),
+    AddCounter,
+    RemoveCounter(usize),
+    MoveCounter(usize, usize),
+    FocusCounter(usize),
+    KeyPressed(Key, Modifiers),
+    CaptureBinding(CounterCommand),
+    ClearBinding(CounterCommand),
+    EditHuntSearch(String),
+    CreateHunt(Species),
+    ToggleTimer(usize),
+    EditIdleMinutes(String),
+    Undo,
+    Redo,
+    SelectMode(CounterMode),
+    ResetChains,
+    BreakChain(usize),
+    MeasureCycle(usize),
+    ToggleAutoReset(usize),
+    EditCycle(String),
+    PickShinyTarget(Species),
+    SetKeepHunting(bool),
+    CancelShinyFound,
+    DismissAlert(usize),
+    ToggleGoals,
+    EditGoal(Goal, String),
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum CountersAction {
     None,
     Increment(usize),
@@ -32,90 +69,391 @@ pub enum CountersAction {
     EditCounter(CounterEditAction),
     StopEditCounter,
     ShinyFound(usize),
+    AddCounter,
+    RemoveCounter(usize),
+    MoveCounter(usize, usize),
+    FocusCounter(usize),
+    KeyPressed(KeyBinding),
+    SetBinding(CounterCommand, Option<KeyBinding>),
+    CreateHunt(Species),
+    ToggleTimer(usize),
+    SetIdleMinutes(i64),
+    Undo,
+    Redo,
+    BreakChain(usize),
+    MeasureCycle(usize),
+    ToggleAutoReset(usize),
+    ShinyFoundTarget(Species, bool),
+    CancelShinyFound,
+    DismissAlert(usize),
+    SetGoal(Goal, Option<i64>),
+}
+
+/// Progress bar of the current period of a goal, with the streak of periods
+/// where it was met
+fn goal_progress<'a>(
+    goal: Goal,
+    target: GoalTarget,
+    state: &State,
+    now: DateTime<Local>,
+) -> Element<'a, CountersMessage> {
+    let history = goal.history(target, &state.all_hunts, &state.active_counters, now);
+    let value = history.last().map(|result| result.value).unwrap_or(0);
+    let streak = goals::current_streak(&history);
+    row![
+        text(format!(
+            "{} : {value} / {} {}",
+            match goal.period {
+                GoalPeriod::Day => "Aujourd'hui",
+                GoalPeriod::Week => "Cette semaine",
+            },
+            target.target,
+            goal.measure
+        ))
+        .size(14)
+        .width(240),
+        progress_bar(0.0..=target.target as f32, value.min(target.target) as f32).height(12),
+        text(match (streak, goal.period) {
+            (0, _) => String::new(),
+            (streak, GoalPeriod::Day) => format!("Série : {streak} j"),
+            (streak, GoalPeriod::Week) => format!("Série : {streak} sem."),
+        })
+        .size(14)
+        .width(100),
+    ]
+    .spacing(8)
+    .align_y(Vertical::Center)
+    .into()
 }
 
 impl Counter {
-    pub fn view(&self, id: usize, state: &State) -> Container<CountersMessage> {
-        let count_display = match self.hunt {
-            Some(index) => {
-                if let Some(hunt) = state.all_hunts.get(index) {
-                    container(column![
-                        text(hunt.target.to_string()).size(24),
-                        text(hunt.phase_encounters).size(32),
-                        text(format!("Phase {}", hunt.phase_count)).size(16)
-                    ])
-                    .center(Length::Fill)
-                } else {
-                    container(text(self.count).size(48)).center(Length::Fill)
-                }
-            }
+    pub fn view<'a>(&'a self, id: usize, state: &'a State) -> Container<'a, CountersMessage> {
+        let hunt = self
+            .hunt
+            .and_then(|hunt_id| state.all_hunts.iter().find(|hunt| hunt.id == Some(hunt_id)));
+        let count_display = match hunt {
+            Some(hunt) => container(column![
+                text(hunt.targets_name()).size(24),
+                text(hunt.phase_encounters).size(32),
+                text(format!("Phase {}", hunt.phase_count)).size(16)
+            ])
+            .center(Length::Fill),
             None => container(text(self.count).size(48)).center(Length::Fill),
         };
+        let chain_display = self.chain_method().map(|method| {
+            let odds = method.odds(
+                self.chain,
+                hunt.and_then(|hunt| hunt.version.as_deref())
+                    .and_then(GameVersion::from_name),
+            );
+            container(
+                row![
+                    column![
+                        text(format!(
+                            "Chaîne : {} (record : {})",
+                            self.chain, self.best_chain
+                        ))
+                        .size(16),
+                        text(format!(
+                            "Chances : 1/{odds:.0} · Chaînes brisées : {}",
+                            self.broken_chains
+                        ))
+                        .size(14),
+                    ],
+                    button("Chaîne brisée").on_press(CountersMessage::BreakChain(id)),
+                ]
+                .spacing(16)
+                .align_y(Vertical::Center),
+            )
+            .center_x(Length::Fill)
+        });
 
-        container(stack![
+        let soft_reset_display = (self.mode == CounterMode::SoftReset).then(|| {
+            let measure_label = if self.soft_reset.measuring_since.is_some() {
+                "Fin du cycle"
+            } else {
+                "Mesurer un cycle"
+            };
+            let auto_label = if self.soft_reset.next_increment.is_some() {
+                "Pause auto"
+            } else {
+                "Lancer l'auto"
+            };
+            let estimates = match self.cycle() {
+                Some(cycle) => {
+                    let (odds, encounters) = match hunt {
+                        Some(hunt) => (hunt.odds(), hunt.phase_encounters),
+                        None => (odds::base_odds(None), self.count),
+                    };
+                    [0.5, 0.9, 0.99]
+                        .into_iter()
+                        .map(|probability| {
+                            let remaining = (odds::encounters_for_probability(odds, probability)
+                                - encounters as f64)
+                                .max(0.0);
+                            format!(
+                                "{:.0} % dans {}",
+                                probability * 100.0,
+                                format_duration(cycle * remaining.ceil() as i32)
+                            )
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" · ")
+                }
+                None => "Mesurez un cycle pour estimer le temps restant".to_string(),
+            };
             container(
                 column![
-                    count_display,
-                    container(row![
-                        container(
-                            button(container(text("-1").size(36)).center(100))
+                    row![
+                        text(format!(
+                            "Cycle : {}",
+                            self.cycle()
+                                .map(|cycle| format!(
+                                    "{:.1} s",
+                                    cycle.num_milliseconds() as f64 / 1000.0
+                                ))
+                                .unwrap_or("-".into())
+                        ))
+                        .size(16),
+                        button(measure_label).on_press(CountersMessage::MeasureCycle(id)),
+                        button(auto_label).on_press_maybe(
+                            self.cycle()
+                                .is_some()
+                                .then_some(CountersMessage::ToggleAutoReset(id))
+                        ),
+                    ]
+                    .spacing(16)
+                    .align_y(Vertical::Center),
+                    text(estimates).size(14),
+                ]
+                .spacing(4)
+                .align_x(Horizontal::Center),
+            )
+            .center_x(Length::Fill)
+        });
+
+        // Time of the whole hunt and of its current phase, or of this
+        // counter's sessions when it isn't linked to a hunt
+        let now = Local::now();
+        let hunt_goals = self
+            .hunt
+            .map(|hunt_id| state.settings.goals.of_hunt(Some(hunt_id)))
+            .filter(|goals| !goals.is_empty())
+            .map(|goals| {
+                Column::with_children(
+                    goals
+                        .into_iter()
+                        .map(|(goal, target)| goal_progress(goal, target, state, now)),
+                )
+                .spacing(4)
+            });
+        let (total_time, phase_time, phase_encounters) = match hunt {
+            Some(hunt) => {
+                let (time, encounters) = self.timer.current(now);
+                let (phase_time, phase_encounters) = hunt.phase_sessions();
+                (
+                    hunt.time_hunted() + time,
+                    phase_time + time,
+                    phase_encounters + encounters,
+                )
+            }
+            None => {
+                let (time, encounters) = self.timer.total(now);
+                (time, time, encounters)
+            }
+        };
+        let timer_label = if self.timer.is_running() {
+            "Pause"
+        } else if total_time > TimeDelta::zero() {
+            "Reprendre"
+        } else {
+            "Démarrer"
+        };
+        let timer_display = row![
+            button(text(timer_label)).on_press(CountersMessage::ToggleTimer(id)),
+            column![
+                text(format_duration(total_time)).size(16),
+                text(format!(
+                    "Phase : {} · {}",
+                    format_duration(phase_time),
+                    encounter_rate(phase_time, phase_encounters)
+                        .map(|rate| format!("{rate:.0} rencontres/h"))
+                        .unwrap_or("- rencontres/h".into())
+                ))
+                .size(14),
+            ],
+        ]
+        .spacing(8);
+
+        container(
+            mouse_area(stack![
+                container(
+                    column![
+                        count_display,
+                        column![]
+                            .push_maybe(chain_display)
+                            .push_maybe(soft_reset_display)
+                            .push_maybe(hunt_goals),
+                        container(row![
+                            container(
+                                button(
+                                    container(text(format!("-{}", self.inc)).size(36)).center(100)
+                                )
                                 .on_press(CountersMessage::Decrement(id))
                                 .padding(0)
                                 .style(counter_button)
-                        )
-                        .center_x(Length::Fill),
-                        container(
-                            button(container(text(format!("+{}", self.inc)).size(36)).center(100))
+                            )
+                            .center_x(Length::Fill),
+                            container(
+                                button(
+                                    container(text(format!("+{}", self.inc)).size(36)).center(100)
+                                )
                                 .on_press(CountersMessage::Increment(id))
                                 .padding(0)
                                 .style(counter_button)
-                        )
-                        .center_x(Length::Fill),
-                        container(
-                            button(
-                                container(
-                                    svg::Svg::new(svg::Handle::from_memory(STARS_ICON))
-                                        .height(64)
-                                        .width(64)
+                            )
+                            .center_x(Length::Fill),
+                            container(
+                                button(
+                                    container(
+                                        svg::Svg::new(svg::Handle::from_memory(STARS_ICON))
+                                            .height(64)
+                                            .width(64)
+                                    )
+                                    .center(100)
                                 )
-                                .center(100)
+                                .on_press(CountersMessage::ShinyFound(id))
+                                .padding(0)
+                                .style(counter_button)
                             )
-                            .on_press(CountersMessage::ShinyFound(id))
-                            .padding(0)
-                            .style(counter_button)
-                        )
-                        .center_x(Length::Fill)
-                    ])
-                    .center_y(Length::Fill)
+                            .center_x(Length::Fill)
+                        ])
+                        .center_y(Length::Fill)
+                    ]
+                    .spacing(8)
+                ),
+                row![
+                    timer_display,
+                    horizontal_space(),
+                    button(
+                        svg::Svg::new(svg::Handle::from_memory(COG_ICON))
+                            .height(32)
+                            .width(32)
+                    )
+                    .height(32)
+                    .width(32)
+                    .on_press(CountersMessage::StartEditCounter(id))
                 ]
-                .spacing(8)
-            ),
-            container(
-                button(
-                    svg::Svg::new(svg::Handle::from_memory(COG_ICON))
-                        .height(32)
-                        .width(32)
-                )
-                .height(32)
-                .width(32)
-                .on_press(CountersMessage::StartEditCounter(id))
-            )
-            .width(Length::Fill)
-            .align_x(Horizontal::Right)
-        ])
+            ])
+            .on_press(CountersMessage::FocusCounter(id)),
+        )
         .padding(16)
-        .style(card)
+        .style(if state.focused_counter == Some(id) {
+            focused_card
+        } else {
+            card
+        })
     }
 
-    pub fn edit_modal(&self, id: usize, _state: &State) -> Element<CountersMessage> {
+    pub fn edit_modal<'a>(
+        &'a self,
+        id: usize,
+        state: &'a State,
+        screen: &'a Counters,
+    ) -> Element<'a, CountersMessage> {
+        let capturing = screen.capturing_binding;
+        let linked_hunt = self
+            .hunt
+            .and_then(|hunt_id| state.all_hunts.iter().find(|hunt| hunt.id == Some(hunt_id)));
+        let matching_hunts = Column::with_children(
+            state
+                .all_hunts
+                .iter()
+                .filter(|hunt| !hunt.completed && hunt.matches(&screen.hunt_search))
+                .filter_map(|hunt| {
+                    let hunt_id = hunt.id?;
+                    let details = [hunt.version.as_deref(), hunt.method.as_deref()]
+                        .into_iter()
+                        .flatten()
+                        .collect::<Vec<&str>>()
+                        .join(", ");
+                    Some(
+                        button(row![
+                            text(hunt.to_string()),
+                            horizontal_space(),
+                            text(details).size(14),
+                            text(hunt.phase_encounters)
+                                .width(80)
+                                .align_x(Horizontal::Right),
+                        ])
+                        .width(Length::Fill)
+                        .style(if self.hunt == Some(hunt_id) {
+                            button::primary
+                        } else {
+                            button::secondary
+                        })
+                        .on_press(CountersMessage::SelectHunt(hunt_id))
+                        .into(),
+                    )
+                }),
+        )
+        .spacing(4);
+
+        let bindings = Column::with_children(CounterCommand::ALL.into_iter().map(|command| {
+            let binding = self
+                .id
+                .and_then(|counter_id| state.settings.counter_bindings.get(counter_id, command));
+            let label = if capturing == Some(command) {
+                "Appuyez sur une touche…".into()
+            } else {
+                binding
+                    .map(|binding| binding.to_string())
+                    .unwrap_or("Aucun".into())
+            };
+            row![
+                text(format!("{command} : ")).width(160),
+                button(text(label))
+                    .on_press_maybe(self.id.map(|_| CountersMessage::CaptureBinding(command))),
+                button(text("x"))
+                    .on_press_maybe(self.id.map(|_| CountersMessage::ClearBinding(command))),
+            ]
+            .spacing(8)
+            .into()
+        }))
+        .spacing(4);
+
         container(
             column![
                 container(text(format!("Editing counter {id}"))).center_x(Length::Fill),
                 row![
-                    text("Recherche : "),
-                    text("Pas encore implémenté"),
-                    button(text("x")).on_press(CountersMessage::UnsetHunt)
+                    text(match linked_hunt {
+                        Some(hunt) => format!("Recherche liée : {hunt}"),
+                        None => "Aucune recherche liée".into(),
+                    }),
+                    horizontal_space(),
+                    button(text("x"))
+                        .on_press_maybe(self.hunt.is_some().then_some(CountersMessage::UnsetHunt)),
+                ]
+                .spacing(8),
+                row![
+                    text("Filtrer : "),
+                    text_input("Espèce, lieu, version, méthode…", &screen.hunt_search)
+                        .on_input(CountersMessage::EditHuntSearch),
                 ],
+                scrollable(matching_hunts).height(200),
+                match Species::from_name(screen.hunt_search.trim()) {
+                    Some(species) => button(text(format!(
+                        "Créer une recherche de {species} depuis ce compteur"
+                    )))
+                    .on_press_maybe(
+                        self.hunt
+                            .is_none()
+                            .then_some(CountersMessage::CreateHunt(species))
+                    ),
+                    None => button(
+                        "Créer une recherche depuis ce compteur (espèce à saisir dans le filtre)"
+                    ),
+                },
                 row![
                     text("Incrément : "),
                     text_input("1", &self.inc.to_string()).on_input(CountersMessage::EditIncrement)
@@ -124,8 +462,69 @@ impl Counter {
                     text("Chiffre : "),
                     text_input("1234", &self.count.to_string())
                         .on_input(CountersMessage::EditCount)
+                ],
+                row![
+                    button("Déplacer avant")
+                        .on_press_maybe((id > 0).then(|| CountersMessage::MoveCounter(id, id - 1))),
+                    button("Déplacer après").on_press_maybe(
+                        (id + 1 < state.active_counters.len())
+                            .then(|| CountersMessage::MoveCounter(id, id + 1))
+                    ),
+                    horizontal_space(),
+                    button("Supprimer").on_press(CountersMessage::RemoveCounter(id)),
                 ]
+                .spacing(8),
+                row![
+                    text("Mode : "),
+                    pick_list(
+                        CounterMode::all(),
+                        Some(self.mode),
+                        CountersMessage::SelectMode
+                    ),
+                    horizontal_space(),
+                    button("Réinitialiser les chaînes").on_press_maybe(
+                        self.chain_method()
+                            .is_some()
+                            .then_some(CountersMessage::ResetChains)
+                    ),
+                ]
+                .spacing(8)
+                .align_y(Vertical::Center),
             ]
+            .push_maybe((self.mode == CounterMode::SoftReset).then(|| {
+                row![
+                    text("Durée d'un cycle : "),
+                    text_input("30", &screen.cycle_seconds)
+                        .on_input(CountersMessage::EditCycle)
+                        .width(80),
+                    text(" secondes"),
+                ]
+                .align_y(Vertical::Center)
+            }))
+            .push_maybe(self.hunt.map(|hunt_id| {
+                column![
+                    text("Objectifs de la recherche"),
+                    screen.goal_editor(Some(hunt_id), state)
+                ]
+                .spacing(8)
+            }))
+            .push(row![
+                text("Pause automatique après "),
+                text_input("5", &screen.idle_minutes)
+                    .on_input(CountersMessage::EditIdleMinutes)
+                    .width(60),
+                text(" minutes sans rencontre (tous les compteurs)"),
+            ])
+            .push(text("Raccourcis clavier"))
+            .push(bindings)
+            .push(
+                text(
+                    "Espace, Retour arrière et Tab agissent sur le compteur sélectionné \
+                     quand aucun raccourci ne les utilise. Ctrl+Z et Ctrl+Y annulent et \
+                     rétablissent la dernière modification.",
+                )
+                .size(14),
+            )
             .spacing(8)
             .padding(32),
         )
@@ -135,11 +534,30 @@ impl Counter {
 }
 
 #[derive(Default)]
-pub struct Counters {}
+pub struct Counters {
+    capturing_binding: Option<CounterCommand>,
+    hunt_search: String,
+    idle_minutes: String,
+    cycle_seconds: String,
+    keep_hunting: bool,
+    show_goals: bool,
+    /// Goal inputs being edited, which may not hold a valid target yet
+    goal_inputs: HashMap<Goal, String>,
+}
 
 impl Counters {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// Fills the inputs of the edit modal that aren't specific to a counter
+    pub fn start_editing(&mut self, idle_minutes: i64, cycle_ms: i32) {
+        self.idle_minutes = idle_minutes.to_string();
+        self.cycle_seconds = if cycle_ms > 0 {
+            (cycle_ms as f64 / 1000.0).to_string()
+        } else {
+            String::new()
+        };
     }
 
     pub fn update(&mut self, message: CountersMessage) -> CountersAction {
@@ -147,9 +565,111 @@ impl Counters {
             CountersMessage::Increment(id) => CountersAction::Increment(id),
             CountersMessage::Decrement(id) => CountersAction::Decrement(id),
             CountersMessage::StartEditCounter(id) => CountersAction::StartEditCounter(id),
-            CountersMessage::StopEditCounter => CountersAction::StopEditCounter,
-            CountersMessage::SelectHunt(index) => {
-                CountersAction::EditCounter(CounterEditAction::SetHunt(index))
+            CountersMessage::StopEditCounter => {
+                self.capturing_binding = None;
+                self.hunt_search.clear();
+                self.goal_inputs.clear();
+                CountersAction::StopEditCounter
+            }
+            CountersMessage::FocusCounter(id) => CountersAction::FocusCounter(id),
+            CountersMessage::KeyPressed(key, modifiers) => {
+                match (
+                    self.capturing_binding.take(),
+                    KeyBinding::from_key_press(key, modifiers),
+                ) {
+                    (Some(_), Some(binding))
+                        if binding == KeyBinding::named(Named::Escape, Modifiers::empty()) =>
+                    {
+                        CountersAction::None
+                    }
+                    (Some(command), Some(binding)) => {
+                        CountersAction::SetBinding(command, Some(binding))
+                    }
+                    (None, Some(binding)) => CountersAction::KeyPressed(binding),
+                    (capturing, None) => {
+                        // Keep waiting when only a modifier was pressed
+                        self.capturing_binding = capturing;
+                        CountersAction::None
+                    }
+                }
+            }
+            CountersMessage::CaptureBinding(command) => {
+                self.capturing_binding = Some(command);
+                CountersAction::None
+            }
+            CountersMessage::ClearBinding(command) => {
+                self.capturing_binding = None;
+                CountersAction::SetBinding(command, None)
+            }
+            CountersMessage::EditHuntSearch(search) => {
+                self.hunt_search = search;
+                CountersAction::None
+            }
+            CountersMessage::ToggleTimer(id) => CountersAction::ToggleTimer(id),
+            CountersMessage::Undo => CountersAction::Undo,
+            CountersMessage::SelectMode(mode) => {
+                CountersAction::EditCounter(CounterEditAction::SetMode(mode))
+            }
+            CountersMessage::ResetChains => {
+                CountersAction::EditCounter(CounterEditAction::ResetChains)
+            }
+            CountersMessage::BreakChain(id) => CountersAction::BreakChain(id),
+            CountersMessage::MeasureCycle(id) => CountersAction::MeasureCycle(id),
+            CountersMessage::ToggleAutoReset(id) => CountersAction::ToggleAutoReset(id),
+            CountersMessage::EditCycle(seconds) => {
+                let action = match seconds.replace(',', ".").parse::<f64>() {
+                    Ok(seconds) if seconds * 1000.0 >= MIN_CYCLE_MS as f64 => {
+                        CountersAction::EditCounter(CounterEditAction::SetCycle(
+                            (seconds * 1000.0).round() as i32,
+                        ))
+                    }
+                    _ => CountersAction::None,
+                };
+                self.cycle_seconds = seconds;
+                action
+            }
+            CountersMessage::PickShinyTarget(species) => {
+                CountersAction::ShinyFoundTarget(species, self.keep_hunting)
+            }
+            CountersMessage::SetKeepHunting(keep_hunting) => {
+                self.keep_hunting = keep_hunting;
+                CountersAction::None
+            }
+            CountersMessage::CancelShinyFound => CountersAction::CancelShinyFound,
+            CountersMessage::DismissAlert(index) => CountersAction::DismissAlert(index),
+            CountersMessage::Redo => CountersAction::Redo,
+            CountersMessage::ToggleGoals => {
+                self.show_goals = !self.show_goals;
+                self.goal_inputs.clear();
+                CountersAction::None
+            }
+            CountersMessage::EditGoal(goal, target) => {
+                let action = if target.trim().is_empty() {
+                    CountersAction::SetGoal(goal, None)
+                } else {
+                    match target.trim().parse::<i64>() {
+                        Ok(target) if target > 0 => CountersAction::SetGoal(goal, Some(target)),
+                        _ => CountersAction::None,
+                    }
+                };
+                self.goal_inputs.insert(goal, target);
+                action
+            }
+            CountersMessage::EditIdleMinutes(idle_minutes) => {
+                let action = match idle_minutes.parse::<i64>() {
+                    Ok(minutes) if minutes > 0 => CountersAction::SetIdleMinutes(minutes),
+                    _ => CountersAction::None,
+                };
+                self.idle_minutes = idle_minutes;
+                action
+            }
+            CountersMessage::CreateHunt(species) => CountersAction::CreateHunt(species),
+            CountersMessage::ShinyFound(id) => CountersAction::ShinyFound(id),
+            CountersMessage::AddCounter => CountersAction::AddCounter,
+            CountersMessage::RemoveCounter(id) => CountersAction::RemoveCounter(id),
+            CountersMessage::MoveCounter(from, to) => CountersAction::MoveCounter(from, to),
+            CountersMessage::SelectHunt(hunt_id) => {
+                CountersAction::EditCounter(CounterEditAction::SetHunt(hunt_id))
             }
             CountersMessage::UnsetHunt => CountersAction::EditCounter(CounterEditAction::UnsetHunt),
             CountersMessage::EditIncrement(inc_str) => {
@@ -166,30 +686,190 @@ impl Counters {
                     CountersAction::None
                 }
             }
-            _ => CountersAction::None,
         }
     }
 
-    pub fn view<'a>(&'a self, state: &'a State) -> Element<CountersMessage> {
-        column![
-            container(row![text("Mes compteurs").size(24), horizontal_space()])
-                .style(navbar)
-                .padding(16),
+    /// Inputs of the daily and weekly goals of a hunt, or of every hunt
+    fn goal_editor<'a>(
+        &'a self,
+        hunt_id: Option<i32>,
+        state: &State,
+    ) -> Column<'a, CountersMessage> {
+        Column::with_children(GoalPeriod::ALL.into_iter().map(|period| {
+            row![text(period.to_string()).width(120)]
+                .extend(GoalMeasure::ALL.into_iter().map(|measure| {
+                    let goal = Goal {
+                        hunt_id,
+                        period,
+                        measure,
+                    };
+                    let value = self.goal_inputs.get(&goal).cloned().unwrap_or_else(|| {
+                        state
+                            .settings
+                            .goals
+                            .get(&goal)
+                            .map(|target| target.target.to_string())
+                            .unwrap_or_default()
+                    });
+                    row![
+                        text_input("Aucun", &value)
+                            .on_input(move |target| CountersMessage::EditGoal(goal, target))
+                            .width(100),
+                        text(measure.to_string()),
+                    ]
+                    .spacing(8)
+                    .align_y(Vertical::Center)
+                    .into()
+                }))
+                .spacing(24)
+                .align_y(Vertical::Center)
+                .into()
+        }))
+        .spacing(4)
+    }
+
+    /// Asks which target of a multi-target hunt was found
+    pub fn shiny_target_modal<'a>(&self, hunt: &'a Hunt) -> Element<'a, CountersMessage> {
+        container(
             column![
+                container(text("Quelle cible a été trouvée ?")).center_x(Length::Fill),
+                Column::with_children(hunt.targets().map(|species| {
+                    button(text(species.to_string()))
+                        .width(Length::Fill)
+                        .on_press(CountersMessage::PickShinyTarget(species))
+                        .into()
+                }))
+                .spacing(4),
+                checkbox(
+                    "Continuer la recherche pour les autres cibles",
+                    self.keep_hunting
+                )
+                .on_toggle(CountersMessage::SetKeepHunting),
                 row![
-                    state.active_counters[0].view(0, state).center(Length::Fill),
-                    state.active_counters[1].view(1, state).center(Length::Fill)
-                ]
-                .spacing(24),
-                row![
-                    state.active_counters[2].view(2, state).center(Length::Fill),
-                    state.active_counters[3].view(3, state).center(Length::Fill)
-                ]
-                .spacing(24)
+                    horizontal_space(),
+                    button("Annuler").on_press(CountersMessage::CancelShinyFound)
+                ],
             ]
-            .spacing(24)
-            .padding(40)
-        ]
+            .spacing(8)
+            .padding(32)
+            .width(400),
+        )
+        .style(card)
         .into()
     }
+
+    pub fn view<'a>(&'a self, state: &'a State) -> Element<'a, CountersMessage> {
+        let header = container(row![
+            text("Mes compteurs").size(24),
+            horizontal_space(),
+            row![
+                button("Annuler")
+                    .on_press_maybe(state.history.can_undo().then_some(CountersMessage::Undo)),
+                button("Rétablir")
+                    .on_press_maybe(state.history.can_redo().then_some(CountersMessage::Redo)),
+                button("Objectifs").on_press(CountersMessage::ToggleGoals),
+                button("Nouveau compteur").on_press(CountersMessage::AddCounter),
+            ]
+            .spacing(8)
+        ])
+        .style(navbar)
+        .padding(16);
+
+        let now = Local::now();
+        let global_goals = state.settings.goals.of_hunt(None);
+        let goals_panel = (self.show_goals || !global_goals.is_empty()).then(|| {
+            container(
+                Column::with_children(
+                    global_goals
+                        .into_iter()
+                        .map(|(goal, target)| goal_progress(goal, target, state, now)),
+                )
+                .push_maybe(self.show_goals.then(|| {
+                    column![
+                        text("Objectifs de toutes les recherches (laisser vide pour aucun)")
+                            .size(16),
+                        self.goal_editor(None, state),
+                    ]
+                    .spacing(8)
+                }))
+                .spacing(4),
+            )
+            .padding([8, 40])
+        });
+
+        let build_grid = |size: Size| {
+            let n_columns: usize = match size.width {
+                x if x < 900.0 => 1,
+                x if x < 1400.0 => 2,
+                x if x < 1900.0 => 3,
+                _ => 4,
+            }
+            .min(state.active_counters.len().max(1));
+            let n_rows = state.active_counters.len().div_ceil(n_columns).max(1);
+            // Counters share the available height, but stay usable when there
+            // are too many of them to fit: the grid then scrolls.
+            let counter_height =
+                ((size.height - 80.0 - 24.0 * (n_rows - 1) as f32) / n_rows as f32).max(320.0);
+
+            scrollable(
+                column(state.active_counters.chunks(n_columns).enumerate().map(
+                    |(row_index, counters)| {
+                        row(counters.iter().enumerate().map(|(i, counter)| {
+                            counter
+                                .view(row_index * n_columns + i, state)
+                                .width(Length::FillPortion(1))
+                                .height(counter_height)
+                                .into()
+                        }))
+                        .extend(
+                            // Keep the last row's counters as wide as the others
+                            (counters.len()..n_columns)
+                                .map(|_| horizontal_space().width(Length::FillPortion(1)).into()),
+                        )
+                        .spacing(24)
+                        .into()
+                    },
+                ))
+                .spacing(24)
+                .padding(40),
+            )
+            .into()
+        };
+
+        let content: Element<CountersMessage> = if state.active_counters.is_empty() {
+            container(text("Aucun compteur"))
+                .center(Length::Fill)
+                .into()
+        } else {
+            responsive(build_grid).into()
+        };
+
+        let banners = column(state.alert_banners.iter().enumerate().map(|(i, banner)| {
+            container(
+                row![
+                    text(format!("{} : {}", banner.hunt, banner.alert)).size(18),
+                    horizontal_space(),
+                    button(text("x")).on_press(CountersMessage::DismissAlert(i)),
+                ]
+                .spacing(16)
+                .align_y(Vertical::Center),
+            )
+            .padding([8, 16])
+            .style(alert_banner)
+            .into()
+        }))
+        .spacing(8)
+        .padding([16, 40])
+        .max_width(800);
+
+        column![header]
+            .push_maybe(goals_panel)
+            .push(stack![
+                content,
+                container(banners)
+                    .center_x(Length::Fill)
+                    .align_top(Length::Fill)
+            ])
+            .into()
+    }
 }
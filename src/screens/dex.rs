@@ -0,0 +1,292 @@
+use crate::data::Species;
+use crate::odds::GameVersion;
+use crate::sprites::{SpriteKey, SpriteStyle};
+use crate::theme::{dex_hunting, dex_missing, dex_owned, navbar};
+use crate::State;
+
+use iced::alignment::{Horizontal, Vertical};
+use iced::widget::{
+    button, column, container, horizontal_space, pick_list, responsive, row, scrollable, text,
+    text_input,
+};
+use iced::{Element, Size};
+
+use std::collections::HashSet;
+use std::path::PathBuf;
+
+const GENERATIONS: std::ops::RangeInclusive<u8> = 1..=9;
+
+#[derive(Debug, Clone)]
+pub enum DexMessage {
+    SelectGeneration(u8),
+    SelectVersion(GameVersion),
+    ClearVersion,
+    EditSpriteFolder(String),
+    SaveSpriteFolder,
+    SelectSpriteStyle(SpriteStyle),
+}
+
+#[derive(Debug, Clone)]
+pub enum DexAction {
+    None,
+    SetSpriteFolder(Option<PathBuf>),
+    SetSpriteStyle(SpriteStyle),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DexStatus {
+    Owned,
+    Hunting,
+    Missing,
+}
+
+/// Species we have shiny, and those targeted by a running hunt
+struct DexProgress {
+    owned: HashSet<Species>,
+    hunting: HashSet<Species>,
+}
+
+impl DexProgress {
+    fn of(state: &State) -> Self {
+        Self {
+            owned: state
+                .all_shinies
+                .iter()
+                .map(|shiny| shiny.species)
+                .collect(),
+            hunting: state
+                .all_hunts
+                .iter()
+                .filter(|hunt| !hunt.completed)
+                .flat_map(|hunt| hunt.targets())
+                .collect(),
+        }
+    }
+
+    fn status(&self, species: Species) -> DexStatus {
+        if self.owned.contains(&species) {
+            DexStatus::Owned
+        } else if self.hunting.contains(&species) {
+            DexStatus::Hunting
+        } else {
+            DexStatus::Missing
+        }
+    }
+
+    /// Owned species among the given ones, and their number
+    fn completion(&self, species: impl Iterator<Item = Species>) -> (usize, usize) {
+        species.fold((0, 0), |(owned, total), species| {
+            (owned + self.owned.contains(&species) as usize, total + 1)
+        })
+    }
+}
+
+fn format_completion((owned, total): (usize, usize)) -> String {
+    if total == 0 {
+        "-".into()
+    } else {
+        format!("{:.0} %", owned as f64 * 100.0 / total as f64)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Dex {
+    generation: u8,
+    version: Option<GameVersion>,
+    /// Sprite folder being typed, until it is saved
+    sprite_folder: Option<String>,
+}
+
+impl Default for Dex {
+    fn default() -> Self {
+        Self {
+            generation: 1,
+            version: None,
+            sprite_folder: None,
+        }
+    }
+}
+
+impl Dex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, message: DexMessage) -> DexAction {
+        match message {
+            DexMessage::SelectGeneration(generation) => {
+                self.generation = generation;
+                DexAction::None
+            }
+            DexMessage::SelectVersion(version) => {
+                self.version = Some(version);
+                if !self
+                    .species()
+                    .any(|species| species.generation() == self.generation)
+                {
+                    self.generation = 1;
+                }
+                DexAction::None
+            }
+            DexMessage::ClearVersion => {
+                self.version = None;
+                DexAction::None
+            }
+            DexMessage::EditSpriteFolder(folder) => {
+                self.sprite_folder = Some(folder);
+                DexAction::None
+            }
+            DexMessage::SaveSpriteFolder => match self.sprite_folder.take() {
+                Some(folder) if folder.trim().is_empty() => DexAction::SetSpriteFolder(None),
+                Some(folder) => DexAction::SetSpriteFolder(Some(PathBuf::from(folder.trim()))),
+                None => DexAction::None,
+            },
+            DexMessage::SelectSpriteStyle(style) => DexAction::SetSpriteStyle(style),
+        }
+    }
+
+    /// Species of the dex, restricted to the ones available in the selected
+    /// game
+    fn species(&self) -> impl Iterator<Item = Species> + '_ {
+        Species::all().filter(|species| {
+            self.version
+                .is_none_or(|version| version.has_species(*species))
+        })
+    }
+
+    pub fn view<'a>(&'a self, state: &'a State) -> Element<'a, DexMessage> {
+        let progress = DexProgress::of(state);
+        let overall = progress.completion(self.species());
+
+        let header = container(
+            row![
+                text("Shinydex").size(24),
+                text(format!(
+                    "{} / {} ({})",
+                    overall.0,
+                    overall.1,
+                    format_completion(overall)
+                ))
+                .size(20),
+                horizontal_space(),
+                pick_list(
+                    &GameVersion::ALL[..],
+                    self.version,
+                    DexMessage::SelectVersion
+                )
+                .placeholder("Tous les jeux"),
+                button("x").on_press_maybe(self.version.map(|_| DexMessage::ClearVersion)),
+            ]
+            .spacing(16)
+            .align_y(Vertical::Center),
+        )
+        .style(navbar)
+        .padding(16);
+
+        let generations = row(GENERATIONS.map(|generation| {
+            let completion = progress.completion(
+                self.species()
+                    .filter(|species| species.generation() == generation),
+            );
+            let label = column![
+                text(format!("Génération {generation}")).size(16),
+                text(format_completion(completion)).size(14),
+            ]
+            .align_x(Horizontal::Center);
+            button(label)
+                .on_press_maybe(
+                    (generation != self.generation && completion.1 > 0)
+                        .then_some(DexMessage::SelectGeneration(generation)),
+                )
+                .into()
+        }))
+        .spacing(8)
+        .padding([16, 40]);
+
+        let sprite_settings = &state.settings.sprites;
+        let sprite_folder = self.sprite_folder.clone().unwrap_or_else(|| {
+            sprite_settings
+                .folder
+                .as_ref()
+                .map(|folder| folder.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        });
+        let sprites = row![
+            text("Dossier des sprites : "),
+            text_input("Aucun, silhouettes uniquement", &sprite_folder)
+                .on_input(DexMessage::EditSpriteFolder)
+                .on_submit(DexMessage::SaveSpriteFolder)
+                .width(400),
+            button("Enregistrer").on_press_maybe(
+                self.sprite_folder
+                    .is_some()
+                    .then_some(DexMessage::SaveSpriteFolder)
+            ),
+            pick_list(
+                &SpriteStyle::ALL[..],
+                Some(sprite_settings.style),
+                DexMessage::SelectSpriteStyle
+            ),
+            text("Sous-dossiers home et par jeu, puis normal et shiny : home/shiny/0025.png")
+                .size(14),
+        ]
+        .spacing(8)
+        .padding([0, 40])
+        .align_y(Vertical::Center);
+
+        let species: Vec<(Species, DexStatus)> = self
+            .species()
+            .filter(|species| species.generation() == self.generation)
+            .map(|species| (species, progress.status(species)))
+            .collect();
+        let grid = responsive(move |size: Size| {
+            let n_columns = ((size.width - 80.0) / 148.0).max(1.0) as usize;
+            scrollable(
+                column(species.chunks(n_columns).map(|species| {
+                    row(species.iter().map(|(species, status)| {
+                        container(
+                            column![
+                                state.sprite(
+                                    SpriteKey {
+                                        species: *species,
+                                        form: None,
+                                        shiny: *status == DexStatus::Owned,
+                                        game: self.version,
+                                    },
+                                    64
+                                ),
+                                text(format!("n° {:03}", i32::from(*species))).size(12),
+                                text(species.to_string()).size(14),
+                            ]
+                            .align_x(Horizontal::Center),
+                        )
+                        .center_x(140)
+                        .padding(8)
+                        .style(match status {
+                            DexStatus::Owned => dex_owned,
+                            DexStatus::Hunting => dex_hunting,
+                            DexStatus::Missing => dex_missing,
+                        })
+                        .into()
+                    }))
+                    .spacing(8)
+                    .into()
+                }))
+                .spacing(8)
+                .padding([0, 40]),
+            )
+            .into()
+        });
+
+        column![
+            header,
+            generations,
+            sprites,
+            container(text("Vert : obtenu · Bleu : en cours de recherche").size(14))
+                .padding([0, 40]),
+            grid
+        ]
+        .spacing(8)
+        .into()
+    }
+}
@@ -0,0 +1,184 @@
+use crate::filters::{FilterEdit, GridFilter, SortKey};
+
+use chrono::NaiveDate;
+use iced::alignment::Vertical;
+use iced::widget::{button, column, pick_list, row, text, text_input};
+use iced::{Element, Length};
+
+#[derive(Debug, Clone)]
+pub enum FilterBarMessage {
+    Edit(FilterEdit),
+    EditFrom(String),
+    EditTo(String),
+    ToggleFilters,
+}
+
+/// Choice of the status filter, whose meaning depends on the grid
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct StatusChoice {
+    value: Option<bool>,
+    label: &'static str,
+}
+
+impl std::fmt::Display for StatusChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.label)
+    }
+}
+
+/// Search, filters and sort options above the hunts and shinies grids. The
+/// filter itself is kept in the settings, only the dates being typed are
+/// kept here.
+#[derive(Debug, Clone, Default)]
+pub struct FilterBar {
+    from: Option<String>,
+    to: Option<String>,
+    expanded: bool,
+}
+
+fn parse_date(date: &str) -> Option<Option<NaiveDate>> {
+    match date.trim() {
+        "" => Some(None),
+        date => NaiveDate::parse_from_str(date, "%Y-%m-%d").ok().map(Some),
+    }
+}
+
+impl FilterBar {
+    pub fn update(&mut self, message: FilterBarMessage) -> Option<FilterEdit> {
+        match message {
+            FilterBarMessage::Edit(edit) => {
+                if let FilterEdit::Reset = edit {
+                    self.from = None;
+                    self.to = None;
+                }
+                Some(edit)
+            }
+            FilterBarMessage::EditFrom(from) => {
+                let edit = parse_date(&from).map(FilterEdit::From);
+                self.from = Some(from);
+                edit
+            }
+            FilterBarMessage::EditTo(to) => {
+                let edit = parse_date(&to).map(FilterEdit::To);
+                self.to = Some(to);
+                edit
+            }
+            FilterBarMessage::ToggleFilters => {
+                self.expanded = !self.expanded;
+                None
+            }
+        }
+    }
+
+    /// `status_labels` name the items whose status is true, then false
+    pub fn view<'a>(
+        &'a self,
+        filter: &'a GridFilter,
+        versions: Vec<String>,
+        methods: Vec<String>,
+        status_labels: [&'static str; 2],
+        (shown, total): (usize, usize),
+    ) -> Element<'a, FilterBarMessage> {
+        let search = row![
+            text_input("Rechercher (espèce, zone, notes…)", &filter.search)
+                .on_input(|search| FilterBarMessage::Edit(FilterEdit::Search(search)))
+                .width(Length::Fill),
+            text("Trier par : "),
+            pick_list(&SortKey::ALL[..], Some(filter.sort), |sort| {
+                FilterBarMessage::Edit(FilterEdit::Sort(sort))
+            }),
+            button(if filter.descending {
+                "Décroissant"
+            } else {
+                "Croissant"
+            })
+            .on_press(FilterBarMessage::Edit(FilterEdit::Descending(
+                !filter.descending
+            ))),
+            button(if self.expanded {
+                "Masquer les filtres"
+            } else {
+                "Filtres"
+            })
+            .on_press(FilterBarMessage::ToggleFilters),
+            button("Réinitialiser").on_press_maybe(
+                (filter.is_filtering() || filter.sort != SortKey::default())
+                    .then_some(FilterBarMessage::Edit(FilterEdit::Reset))
+            ),
+            text(format!("{shown} / {total}")),
+        ]
+        .spacing(8)
+        .align_y(Vertical::Center);
+
+        let format_date = |date: Option<NaiveDate>| {
+            date.map(|date| date.format("%Y-%m-%d").to_string())
+                .unwrap_or_default()
+        };
+        let status_choices = [
+            StatusChoice {
+                value: None,
+                label: "Tous",
+            },
+            StatusChoice {
+                value: Some(true),
+                label: status_labels[0],
+            },
+            StatusChoice {
+                value: Some(false),
+                label: status_labels[1],
+            },
+        ];
+        let selected_status = status_choices
+            .into_iter()
+            .find(|choice| choice.value == filter.status);
+        let filters = (self.expanded || filter.is_filtering()).then(|| {
+            row![
+                pick_list(versions, filter.version.clone(), |version| {
+                    FilterBarMessage::Edit(FilterEdit::Version(Some(version)))
+                })
+                .placeholder("Tous les jeux"),
+                button("x").on_press_maybe(
+                    filter
+                        .version
+                        .is_some()
+                        .then_some(FilterBarMessage::Edit(FilterEdit::Version(None)))
+                ),
+                pick_list(methods, filter.method.clone(), |method| {
+                    FilterBarMessage::Edit(FilterEdit::Method(Some(method)))
+                })
+                .placeholder("Toutes les méthodes"),
+                button("x").on_press_maybe(
+                    filter
+                        .method
+                        .is_some()
+                        .then_some(FilterBarMessage::Edit(FilterEdit::Method(None)))
+                ),
+                pick_list(status_choices, selected_status, |choice| {
+                    FilterBarMessage::Edit(FilterEdit::Status(choice.value))
+                }),
+                text("Du "),
+                text_input(
+                    "AAAA-MM-JJ",
+                    &self.from.clone().unwrap_or(format_date(filter.from))
+                )
+                .on_input(FilterBarMessage::EditFrom)
+                .width(120),
+                text(" au "),
+                text_input(
+                    "AAAA-MM-JJ",
+                    &self.to.clone().unwrap_or(format_date(filter.to))
+                )
+                .on_input(FilterBarMessage::EditTo)
+                .width(120),
+            ]
+            .spacing(8)
+            .align_y(Vertical::Center)
+        });
+
+        column![search]
+            .push_maybe(filters)
+            .spacing(8)
+            .padding([8, 40])
+            .into()
+    }
+}
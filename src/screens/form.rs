@@ -0,0 +1,136 @@
+use crate::data::Species;
+
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, TimeZone};
+use iced::alignment::{Horizontal, Vertical};
+use iced::widget::{button, column, row, text, text_input, Column};
+use iced::{Element, Length};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const TIME_FORMAT: &str = "%H:%M";
+
+/// Empty, or a number of encounters or phases
+pub fn parse_count(count: &str) -> Result<Option<i32>, &'static str> {
+    match count.trim() {
+        "" => Ok(None),
+        count => match count.parse::<i32>() {
+            Ok(count) if count >= 0 => Ok(Some(count)),
+            _ => Err("Nombre entier positif attendu"),
+        },
+    }
+}
+
+/// Empty text inputs clear optional fields
+pub fn optional_text(value: String) -> Option<String> {
+    Some(value).filter(|value| !value.trim().is_empty())
+}
+
+/// Date and time inputs, kept as typed so invalid values can be shown and
+/// fixed
+#[derive(Debug, Clone, Default)]
+pub struct DateTimeInput {
+    pub date: String,
+    pub time: String,
+}
+
+impl DateTimeInput {
+    pub fn of(date_time: Option<DateTime<Local>>) -> Self {
+        Self {
+            date: date_time
+                .map(|date_time| date_time.format(DATE_FORMAT).to_string())
+                .unwrap_or_default(),
+            time: date_time
+                .map(|date_time| date_time.format(TIME_FORMAT).to_string())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The typed date and time, midnight when only the date is known
+    pub fn parse(&self) -> Result<Option<DateTime<Local>>, &'static str> {
+        let date = match self.date.trim() {
+            "" if self.time.trim().is_empty() => return Ok(None),
+            "" => return Err("Date manquante"),
+            date => NaiveDate::parse_from_str(date, DATE_FORMAT)
+                .map_err(|_| "Date attendue au format AAAA-MM-JJ")?,
+        };
+        let time = match self.time.trim() {
+            "" => NaiveTime::MIN,
+            time => NaiveTime::parse_from_str(time, TIME_FORMAT)
+                .map_err(|_| "Heure attendue au format HH:MM")?,
+        };
+        let date_time = Local
+            .from_local_datetime(&date.and_time(time))
+            .earliest()
+            .ok_or("Heure inexistante")?;
+        if date_time > Local::now() {
+            Err("Date dans le futur")
+        } else {
+            Ok(Some(date_time))
+        }
+    }
+
+    pub fn view<'a, Message: Clone + 'a>(
+        &self,
+        on_date: impl Fn(String) -> Message + 'a,
+        on_time: impl Fn(String) -> Message + 'a,
+    ) -> Element<'a, Message> {
+        row![
+            text_input("AAAA-MM-JJ", &self.date)
+                .size(16)
+                .on_input(on_date),
+            text("à").size(16),
+            text_input("HH:MM", &self.time)
+                .size(16)
+                .on_input(on_time)
+                .width(80),
+        ]
+        .spacing(8)
+        .align_y(Vertical::Center)
+        .into()
+    }
+}
+
+/// Labelled input of an editor, with the error of its value below it
+pub fn form_row<'a, Message: 'a>(
+    label: &'a str,
+    input: impl Into<Element<'a, Message>>,
+    error: Option<&'static str>,
+) -> Element<'a, Message> {
+    row![
+        text(label)
+            .size(16)
+            .width(Length::Fill)
+            .align_x(Horizontal::Right),
+        column![input.into()]
+            .push_maybe(error.map(|error| text(error).size(14).style(text::danger)))
+            .spacing(4)
+            .width(Length::Fill),
+    ]
+    .spacing(8)
+    .into()
+}
+
+/// Species whose French or English name contains the typed text, to pick
+/// from while it isn't an exact match
+pub fn species_suggestions<'a, Message: Clone + 'a>(
+    typed: &str,
+    on_pick: impl Fn(Species) -> Message,
+) -> Option<Column<'a, Message>> {
+    let search = typed.trim().to_lowercase();
+    (search.len() >= 2).then(|| {
+        Column::with_children(
+            Species::all()
+                .filter(|species| {
+                    species.to_string().to_lowercase().contains(&search)
+                        || species.english_name().to_lowercase().contains(&search)
+                })
+                .take(8)
+                .map(|species| {
+                    button(text(format!("{} (n° {:03})", species, i32::from(species))).size(14))
+                        .on_press(on_pick(species))
+                        .style(button::secondary)
+                        .into()
+                }),
+        )
+        .spacing(4)
+    })
+}
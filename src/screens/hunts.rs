@@ -1,12 +1,22 @@
+use crate::alerts::HuntAlerts;
+use crate::charts::EncountersChart;
 use crate::data::Species;
-use crate::hunt::Hunt;
-use crate::theme::{card, navbar, side_view};
+use crate::filters::{self, FilterEdit};
+use crate::hunt::{Hunt, HuntEditAction};
+use crate::odds;
+use crate::report::ReportFormat;
+use crate::screens::filter_bar::{FilterBar, FilterBarMessage};
+use crate::screens::form::{
+    form_row, optional_text, parse_count, species_suggestions, DateTimeInput,
+};
+use crate::session::{encounter_rate, format_duration};
+use crate::theme::{card, focused_card, navbar, side_view};
 use crate::State;
 
 use iced::alignment::{Horizontal, Vertical};
 use iced::widget::{
-    button, column, container, horizontal_space, mouse_area, responsive, row, scrollable, stack,
-    svg, text, text_input, Container,
+    button, canvas, checkbox, column, container, horizontal_space, mouse_area, pick_list,
+    responsive, row, scrollable, stack, svg, text, text_editor, text_input, Container,
 };
 use iced::{Element, Length, Pixels, Size};
 
@@ -19,19 +29,39 @@ pub enum HuntsMessage {
     SelectHunt(usize),
     CloseSelectedHunt,
     StartEditHunt(usize),
-    EditTarget(Species),
+    EditTarget(String),
+    PickTarget(Species),
     EditTotalEncounterCount(String),
     EditPhaseEncounterCount(String),
     EditPhaseCount(String),
     EditCompleted(bool),
+    EditStartDate(String),
+    EditStartTime(String),
+    EditEndDate(String),
+    EditEndTime(String),
     EditVersion(String),
     EditMethod(String),
     EditPlace(String),
-    EditNotes(String),
+    EditNotes(text_editor::Action),
     StopEditHunt(bool),
+    SelectReportFormat(ReportFormat),
+    CopyReport(usize),
+    CopyListReport,
+    EditReportTemplates,
+    EditTargetSearch(String),
+    AddTarget,
+    RemoveTarget(usize),
+    EditAlertEvery(String),
+    EditAlertBaseOdds(bool),
+    EditAlertProbabilities(String),
+    EditAlertSound(bool),
+    ToggleCompareMode,
+    ToggleCompared(i32),
+    ShowComparison(bool),
+    Filter(FilterBarMessage),
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum HuntsAction {
     None,
     CreateHunt,
@@ -40,13 +70,96 @@ pub enum HuntsAction {
     CloseSelectedHunt,
     StartEditHunt(usize),
     StopEditHunt(bool),
+    CopyReport(usize, ReportFormat),
+    CopyListReport(ReportFormat),
+    EditReportTemplates,
+    EditHunt(HuntEditAction),
+    EditFilter(FilterEdit),
+}
+
+/// Inputs of the hunt editor that need to be parsed, kept as typed so
+/// invalid values can be shown and fixed
+#[derive(Debug, Default)]
+struct HuntForm {
+    target: String,
+    phase_encounters: String,
+    total_encounters: String,
+    /// Encounters of the previous phases, kept when the phase count changes
+    previous_encounters: i32,
+    phase_count: String,
+    start_time: DateTimeInput,
+    end_time: DateTimeInput,
+    notes: text_editor::Content,
+}
+
+/// A number of encounters or phases that can't be left empty
+fn required_count(count: &str) -> Result<i32, &'static str> {
+    parse_count(count)?.ok_or("Valeur requise")
+}
+
+impl HuntForm {
+    fn of(hunt: &Hunt) -> Self {
+        Self {
+            target: hunt.target.to_string(),
+            phase_encounters: hunt.phase_encounters.to_string(),
+            total_encounters: (hunt.previous_encounters + hunt.phase_encounters).to_string(),
+            previous_encounters: hunt.previous_encounters,
+            phase_count: hunt.phase_count.to_string(),
+            start_time: DateTimeInput::of(hunt.start_time),
+            end_time: DateTimeInput::of(hunt.end_time),
+            notes: text_editor::Content::with_text(hunt.notes.as_deref().unwrap_or_default()),
+        }
+    }
+
+    fn target_error(&self) -> Option<&'static str> {
+        match Species::from_name(&self.target) {
+            Some(Species::Egg) | None => Some("Espèce inconnue"),
+            Some(_) => None,
+        }
+    }
+
+    fn total_encounters_error(&self) -> Option<&'static str> {
+        match (
+            required_count(&self.total_encounters),
+            required_count(&self.phase_encounters),
+        ) {
+            (Err(error), _) => Some(error),
+            (Ok(total), Ok(phase)) if total < phase => Some("Moins que les rencontres de la phase"),
+            _ => None,
+        }
+    }
+
+    fn phase_count_error(&self) -> Option<&'static str> {
+        match required_count(&self.phase_count) {
+            Ok(0) => Some("Les phases commencent à 1"),
+            Ok(_) => None,
+            Err(error) => Some(error),
+        }
+    }
+
+    fn end_time_error(&self) -> Option<&'static str> {
+        match (self.start_time.parse(), self.end_time.parse()) {
+            (_, Err(error)) => Some(error),
+            (Ok(Some(start)), Ok(Some(end))) if end < start => Some("Fin avant le début"),
+            _ => None,
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        self.target_error().is_none()
+            && required_count(&self.phase_encounters).is_ok()
+            && self.total_encounters_error().is_none()
+            && self.phase_count_error().is_none()
+            && self.start_time.parse().is_ok()
+            && self.end_time_error().is_none()
+    }
 }
 
 fn make_row<'a>(
-    label: &'a str,
+    label: impl iced::widget::text::IntoFragment<'a>,
     value: impl iced::widget::text::IntoFragment<'a>,
     text_size: impl Into<Pixels> + std::marker::Copy,
-) -> iced::widget::Row<HuntsMessage> {
+) -> iced::widget::Row<'a, HuntsMessage> {
     row![
         text(label)
             .size(text_size)
@@ -58,89 +171,160 @@ fn make_row<'a>(
 }
 
 impl Hunt {
-    pub fn view_card(&self, index: usize) -> Container<HuntsMessage> {
+    /// Card of the hunt in the grid. While picking hunts to compare,
+    /// `compared` tells whether this one is picked and clicking it toggles it.
+    pub fn view_card<'a>(
+        &'a self,
+        index: usize,
+        compared: Option<bool>,
+        state: &'a State,
+    ) -> Container<'a, HuntsMessage> {
+        let on_press = match (compared, self.id) {
+            (Some(_), Some(hunt_id)) => HuntsMessage::ToggleCompared(hunt_id),
+            _ => HuntsMessage::SelectHunt(index),
+        };
         container(
-            mouse_area(stack![
-                container(
-                    column![
-                        row![
-                            container(text("sprite here").width(100).height(100))
-                                .align_right(Length::Fill),
+            mouse_area(
+                stack![
+                    container(
+                        column![
+                            row![
+                                container(state.sprite(self.sprite_key(), 100))
+                                    .align_right(Length::Fill),
+                                column![
+                                    text(self.targets_name()).size(20),
+                                    text(self.phase_encounters).size(24),
+                                    text(format!("Phase {}", self.phase_count)).size(16)
+                                ]
+                                .width(Length::Fill)
+                                .spacing(8)
+                            ]
+                            .align_y(Vertical::Center)
+                            .spacing(16)
+                            .padding(8),
                             column![
-                                text(self.target.to_string()).size(20),
-                                text(self.phase_encounters).size(24),
-                                text(format!("Phase {}", self.phase_count)).size(16)
+                                make_row(
+                                    "Version :",
+                                    self.version.clone().unwrap_or("Inconnue".into()),
+                                    14
+                                ),
+                                make_row(
+                                    "Méthode :",
+                                    self.method.clone().unwrap_or("Inconnue".into()),
+                                    14
+                                ),
+                                make_row(
+                                    "Zone :",
+                                    self.place.clone().unwrap_or("Inconnue".into()),
+                                    14
+                                ),
+                                make_row(
+                                    "Débutée le",
+                                    self.start_time
+                                        .map(|dt| dt
+                                            .format_localized("%-d %B %Y", chrono::Locale::fr_FR)
+                                            .to_string())
+                                        .unwrap_or("Inconnue".into()),
+                                    14
+                                ),
                             ]
-                            .width(Length::Fill)
-                            .spacing(8)
-                        ]
-                        .align_y(Vertical::Center)
-                        .spacing(16)
-                        .padding(8),
-                        column![
-                            make_row(
-                                "Version :",
-                                self.version.clone().unwrap_or("Inconnue".into()),
-                                14
-                            ),
-                            make_row(
-                                "Méthode :",
-                                self.method.clone().unwrap_or("Inconnue".into()),
-                                14
-                            ),
-                            make_row(
-                                "Zone :",
-                                self.place.clone().unwrap_or("Inconnue".into()),
-                                14
-                            ),
-                            make_row(
-                                "Débutée le",
-                                self.start_time
-                                    .map(|dt| dt
-                                        .format_localized("%-d %B %Y", chrono::Locale::fr_FR)
-                                        .to_string())
-                                    .unwrap_or("Inconnue".into()),
-                                14
-                            ),
+                            .spacing(12)
+                            .padding([8, 16])
                         ]
-                        .spacing(12)
-                        .padding([8, 16])
-                    ]
-                    .spacing(8)
-                    .padding(32)
-                ),
-                container(
-                    button(
-                        svg::Svg::new(svg::Handle::from_memory(COG_ICON))
-                            .height(32)
-                            .width(32)
+                        .spacing(8)
+                        .padding(32)
+                    ),
+                    container(
+                        button(
+                            svg::Svg::new(svg::Handle::from_memory(COG_ICON))
+                                .height(32)
+                                .width(32)
+                        )
+                        .height(32)
+                        .width(32)
+                        .on_press(HuntsMessage::StartEditHunt(index))
                     )
-                    .height(32)
-                    .width(32)
-                    .on_press(HuntsMessage::StartEditHunt(index))
-                )
-                .width(Length::Fill)
-                .align_x(Horizontal::Right)
-            ])
-            .on_press(HuntsMessage::SelectHunt(index)),
+                    .width(Length::Fill)
+                    .align_x(Horizontal::Right)
+                ]
+                .push_maybe(compared.map(|compared| {
+                    container(
+                        checkbox("Comparer", compared).on_toggle_maybe(
+                            self.id
+                                .map(|hunt_id| move |_| HuntsMessage::ToggleCompared(hunt_id)),
+                        ),
+                    )
+                    .padding(8)
+                })),
+            )
+            .on_press(on_press),
         )
         .width(Length::Fill)
-        .style(card)
+        .style(if compared == Some(true) {
+            focused_card
+        } else {
+            card
+        })
     }
 
-    pub fn view_detailed(&self, index: usize) -> Container<HuntsMessage> {
+    /// Encounters and time left before reaching usual probabilities of
+    /// having found the shiny
+    fn view_projection(&self) -> iced::widget::Column<'_, HuntsMessage> {
+        let expected = make_row(
+            "Shiny attendu dans :",
+            self.expected_time()
+                .map(format_duration)
+                .unwrap_or("Rythme inconnu".into()),
+            16,
+        );
+        column(
+            std::iter::once(expected.into()).chain([0.5, 0.9, 0.99].into_iter().map(
+                |probability| {
+                    let (encounters, time) = self.projection(probability);
+                    make_row(
+                        format!("{:.0} % de chances dans :", probability * 100.0),
+                        match (encounters, time) {
+                            (0, _) => "Atteint".to_string(),
+                            (encounters, Some(time)) => {
+                                format!("{encounters} rencontres, {}", format_duration(time))
+                            }
+                            (encounters, None) => format!("{encounters} rencontres"),
+                        },
+                        16,
+                    )
+                    .into()
+                },
+            )),
+        )
+        .spacing(12)
+    }
+
+    pub fn view_detailed<'a>(
+        &'a self,
+        index: usize,
+        state: &'a State,
+    ) -> Container<'a, HuntsMessage> {
         container(scrollable(
             column![
                 row![
                     horizontal_space(),
+                    button("Copier").on_press(HuntsMessage::CopyReport(index)),
                     button("Modifier").on_press(HuntsMessage::StartEditHunt(index)),
                     button("Fermer").on_press(HuntsMessage::CloseSelectedHunt)
                 ]
                 .spacing(8),
-                container(text("sprite here").width(100).height(100))
+                container(state.sprite(self.sprite_key(), 100))
                     .width(Length::Fill)
                     .align_x(Horizontal::Center),
-                make_row("Espèce :", self.target.to_string(), 16),
+                make_row(
+                    if self.other_targets.is_empty() {
+                        "Espèce :"
+                    } else {
+                        "Espèces :"
+                    },
+                    self.targets_name(),
+                    16
+                ),
                 make_row("Rencontres (phase) :", self.phase_encounters, 16),
                 make_row(
                     "Rencontres (total) :",
@@ -152,6 +336,22 @@ impl Hunt {
                     format!("Phase {}", self.phase_count),
                     16
                 ),
+                make_row(
+                    "Temps de recherche :",
+                    format_duration(self.time_hunted()),
+                    16
+                ),
+                make_row(
+                    "Rencontres par heure (phase) :",
+                    {
+                        let (time, encounters) = self.phase_sessions();
+                        encounter_rate(time, encounters)
+                            .map(|rate| format!("{rate:.0}"))
+                            .unwrap_or("Inconnues".into())
+                    },
+                    16
+                ),
+                column![].push_maybe((!self.completed).then(|| self.view_projection())),
                 make_row(
                     "Version :",
                     self.version.clone().unwrap_or("Inconnue".into()),
@@ -176,7 +376,23 @@ impl Hunt {
                         .unwrap_or("Inconnue".into()),
                     16
                 ),
+                make_row(
+                    "Chance :",
+                    match self.luck_percentile() {
+                        Some(percentile) => odds::format_luck(percentile),
+                        None if self.completed => "Inconnue".into(),
+                        None => "Recherche en cours".into(),
+                    },
+                    16
+                ),
                 column![text("Notes"), text(self.notes.clone().unwrap_or("".into())),],
+                column![
+                    text("Rencontres au fil du temps"),
+                    canvas(EncountersChart::of_hunt(self))
+                        .width(Length::Fill)
+                        .height(200),
+                ]
+                .spacing(8),
             ]
             .spacing(12)
             .padding(16),
@@ -186,92 +402,173 @@ impl Hunt {
         .style(side_view)
     }
 
-    pub fn view_editing(&self) -> Container<HuntsMessage> {
+    fn view_editing<'a>(
+        &'a self,
+        form: &'a HuntForm,
+        target_search: &'a str,
+        alert_probabilities: &'a str,
+        state: &'a State,
+    ) -> Container<'a, HuntsMessage> {
+        let other_targets = column(self.other_targets.iter().enumerate().map(|(i, species)| {
+            row![
+                text(species.to_string()).size(16),
+                button(text("x")).on_press(HuntsMessage::RemoveTarget(i)),
+            ]
+            .spacing(8)
+            .align_y(Vertical::Center)
+            .into()
+        }))
+        .spacing(4);
+
+        let suggestions = form
+            .target_error()
+            .and_then(|_| species_suggestions(&form.target, HuntsMessage::PickTarget));
+
+        let text_field = |value: &Option<String>| value.clone().unwrap_or_default();
+
         container(scrollable(
             column![
                 row![
                     horizontal_space(),
-                    button("Enregistrer").on_press(HuntsMessage::StopEditHunt(true)),
+                    button("Enregistrer").on_press_maybe(
+                        form.is_valid().then_some(HuntsMessage::StopEditHunt(true))
+                    ),
                     button("Annuler").on_press(HuntsMessage::StopEditHunt(false))
                 ]
                 .spacing(8),
-                container(text("sprite here").width(100).height(100))
+                container(state.sprite(self.sprite_key(), 100))
                     .width(Length::Fill)
                     .align_x(Horizontal::Center),
-                make_row("Espèce :", self.target.to_string(), 16),
-                row![
-                    text("Rencontres (phase) :")
+                form_row(
+                    "Espèce :",
+                    column![text_input("Nom ou n° du Pokédex", &form.target)
                         .size(16)
-                        .width(Length::Fill)
-                        .align_x(Horizontal::Right),
-                    text_input("", &self.phase_encounters.to_string())
-                        .size(16)
-                        .on_input(HuntsMessage::EditPhaseEncounterCount)
-                ]
-                .spacing(8),
+                        .on_input(HuntsMessage::EditTarget)]
+                    .push_maybe(suggestions)
+                    .spacing(4),
+                    form.target_error(),
+                ),
                 row![
-                    text("Rencontres (total) :")
+                    text("Autres cibles :")
                         .size(16)
                         .width(Length::Fill)
                         .align_x(Horizontal::Right),
-                    text_input(
-                        "",
-                        &(self.phase_encounters + self.previous_encounters).to_string()
-                    )
-                    .size(16)
-                    .on_input(HuntsMessage::EditTotalEncounterCount)
+                    column![
+                        other_targets,
+                        row![
+                            text_input("Espèce ou n° du Pokédex", target_search)
+                                .size(16)
+                                .on_input(HuntsMessage::EditTargetSearch)
+                                .on_submit(HuntsMessage::AddTarget),
+                            button("Ajouter").on_press_maybe(
+                                Species::from_name(target_search.trim())
+                                    .map(|_| HuntsMessage::AddTarget)
+                            ),
+                        ]
+                        .spacing(8),
+                    ]
+                    .width(Length::Fill)
+                    .spacing(4),
                 ]
                 .spacing(8),
-                row![
-                    text("Phase actuelle :")
+                form_row(
+                    "Rencontres (phase) :",
+                    text_input("0", &form.phase_encounters)
                         .size(16)
-                        .width(Length::Fill)
-                        .align_x(Horizontal::Right),
-                    text_input("", &self.phase_count.to_string())
+                        .on_input(HuntsMessage::EditPhaseEncounterCount),
+                    required_count(&form.phase_encounters).err(),
+                ),
+                form_row(
+                    "Rencontres (total) :",
+                    text_input("0", &form.total_encounters)
                         .size(16)
-                        .on_input(HuntsMessage::EditPhaseCount)
-                ]
-                .spacing(8),
-                row![
-                    text("Version :")
+                        .on_input(HuntsMessage::EditTotalEncounterCount),
+                    form.total_encounters_error(),
+                ),
+                form_row(
+                    "Phase actuelle :",
+                    text_input("1", &form.phase_count)
                         .size(16)
-                        .width(Length::Fill)
-                        .align_x(Horizontal::Right),
-                    text_input("", &self.version.clone().unwrap_or("".into()))
+                        .on_input(HuntsMessage::EditPhaseCount),
+                    form.phase_count_error(),
+                ),
+                form_row(
+                    "Statut :",
+                    checkbox("Terminée", self.completed).on_toggle(HuntsMessage::EditCompleted),
+                    None,
+                ),
+                form_row(
+                    "Version :",
+                    text_input("Inconnue", &text_field(&self.version))
+                        .size(16)
+                        .on_input(HuntsMessage::EditVersion),
+                    None,
+                ),
+                form_row(
+                    "Méthode :",
+                    text_input("Inconnue", &text_field(&self.method))
+                        .size(16)
+                        .on_input(HuntsMessage::EditMethod),
+                    None,
+                ),
+                form_row(
+                    "Zone :",
+                    text_input("Inconnue", &text_field(&self.place))
                         .size(16)
-                        .on_input(HuntsMessage::EditVersion)
+                        .on_input(HuntsMessage::EditPlace),
+                    None,
+                ),
+                form_row(
+                    "Débutée le :",
+                    form.start_time
+                        .view(HuntsMessage::EditStartDate, HuntsMessage::EditStartTime),
+                    form.start_time.parse().err(),
+                ),
+                form_row(
+                    "Terminée le :",
+                    form.end_time
+                        .view(HuntsMessage::EditEndDate, HuntsMessage::EditEndTime),
+                    form.end_time_error(),
+                ),
+                column![
+                    text("Notes"),
+                    text_editor(&form.notes)
+                        .on_action(HuntsMessage::EditNotes)
+                        .height(120),
                 ]
                 .spacing(8),
+                text("Alertes").size(20),
                 row![
-                    text("Méthode :")
+                    text("Toutes les :")
                         .size(16)
                         .width(Length::Fill)
                         .align_x(Horizontal::Right),
-                    text_input("", &self.method.clone().unwrap_or("".into()))
-                        .size(16)
-                        .on_input(HuntsMessage::EditMethod)
+                    text_input(
+                        "Jamais",
+                        &if self.alerts.every > 0 {
+                            self.alerts.every.to_string()
+                        } else {
+                            String::new()
+                        }
+                    )
+                    .size(16)
+                    .on_input(HuntsMessage::EditAlertEvery),
+                    text("rencontres").size(16),
                 ]
                 .spacing(8),
                 row![
-                    text("Zone :")
+                    text("Probabilités (%) :")
                         .size(16)
                         .width(Length::Fill)
                         .align_x(Horizontal::Right),
-                    text_input("", &self.method.clone().unwrap_or("".into()))
+                    text_input("50, 90, 99", alert_probabilities)
                         .size(16)
-                        .on_input(HuntsMessage::EditMethod)
+                        .on_input(HuntsMessage::EditAlertProbabilities),
                 ]
                 .spacing(8),
-                make_row(
-                    "Débutée le",
-                    self.start_time
-                        .map(|dt| dt
-                            .format_localized("%-d %B %Y", chrono::Locale::fr_FR)
-                            .to_string())
-                        .unwrap_or("Inconnue".into()),
-                    16
-                ),
-                column![text("Notes"), text(self.notes.clone().unwrap_or("".into())),],
+                checkbox("Au passage de la cote de base", self.alerts.base_odds)
+                    .on_toggle(HuntsMessage::EditAlertBaseOdds),
+                checkbox("Jouer un son", self.alerts.sound).on_toggle(HuntsMessage::EditAlertSound),
             ]
             .spacing(12)
             .padding(16),
@@ -282,12 +579,139 @@ impl Hunt {
     }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
-pub struct Hunts {}
+/// Hunts side by side, one column each
+fn view_comparison<'a>(hunts: Vec<&'a Hunt>) -> Element<'a, HuntsMessage> {
+    type Field = fn(&Hunt) -> String;
+    let fields: [(&str, Field); 15] = [
+        ("Espèces", |hunt| hunt.targets_name()),
+        ("Statut", |hunt| {
+            if hunt.completed {
+                "Terminée".into()
+            } else {
+                "En cours".into()
+            }
+        }),
+        ("Version", |hunt| {
+            hunt.version.clone().unwrap_or("Inconnue".into())
+        }),
+        ("Méthode", |hunt| {
+            hunt.method.clone().unwrap_or("Inconnue".into())
+        }),
+        ("Zone", |hunt| {
+            hunt.place.clone().unwrap_or("Inconnue".into())
+        }),
+        ("Rencontres (phase)", |hunt| {
+            hunt.phase_encounters.to_string()
+        }),
+        ("Rencontres (total)", |hunt| {
+            (hunt.previous_encounters + hunt.phase_encounters).to_string()
+        }),
+        ("Phases", |hunt| hunt.phase_count.to_string()),
+        ("Temps de recherche", |hunt| {
+            format_duration(hunt.time_hunted())
+        }),
+        ("Rencontres par heure", |hunt| {
+            hunt.encounter_rate()
+                .map(|rate| format!("{rate:.0}"))
+                .unwrap_or("Inconnues".into())
+        }),
+        ("Chances", |hunt| format!("1/{:.0}", hunt.odds())),
+        ("Temps moyen par shiny", |hunt| {
+            hunt.expected_time()
+                .map(format_duration)
+                .unwrap_or("Rythme inconnu".into())
+        }),
+        ("Probabilité atteinte (phase)", |hunt| {
+            format!(
+                "{:.0} %",
+                odds::cumulative_probability(hunt.odds(), hunt.phase_encounters) * 100.0
+            )
+        }),
+        ("Chance", |hunt| match hunt.luck_percentile() {
+            Some(percentile) => odds::format_luck(percentile),
+            None if hunt.completed => "Inconnue".into(),
+            None => "Recherche en cours".into(),
+        }),
+        ("Débutée le", |hunt| {
+            hunt.start_time
+                .map(|dt| {
+                    dt.format_localized("%-d %B %Y", chrono::Locale::fr_FR)
+                        .to_string()
+                })
+                .unwrap_or("Inconnue".into())
+        }),
+    ];
+
+    let labels = column(fields.iter().map(|(label, _)| {
+        text(format!("{label} :"))
+            .size(16)
+            .align_x(Horizontal::Right)
+            .width(Length::Fill)
+            .into()
+    }))
+    .spacing(12)
+    .width(220);
+    let columns = hunts.into_iter().map(|hunt| {
+        column(
+            fields
+                .iter()
+                .map(|(_, field)| text(field(hunt)).size(16).into()),
+        )
+        .spacing(12)
+        .width(Length::Fill)
+        .into()
+    });
+    container(scrollable(
+        row(std::iter::once(labels.into()).chain(columns))
+            .spacing(24)
+            .padding(16),
+    ))
+    .padding(24)
+    .style(card)
+    .into()
+}
+
+#[derive(Debug)]
+pub struct Hunts {
+    report_format: ReportFormat,
+    target_search: String,
+    alert_probabilities: String,
+    /// Hunts picked for the comparison, while picking them
+    compared: Option<Vec<i32>>,
+    show_comparison: bool,
+    filter_bar: FilterBar,
+    form: HuntForm,
+}
+
+impl Default for Hunts {
+    fn default() -> Self {
+        Self {
+            report_format: ReportFormat::Markdown,
+            target_search: String::new(),
+            alert_probabilities: String::new(),
+            compared: None,
+            show_comparison: false,
+            filter_bar: FilterBar::default(),
+            form: HuntForm::default(),
+        }
+    }
+}
 
 impl Hunts {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// Fills the inputs of the editor that don't map directly to a field
+    pub fn start_editing(&mut self, hunt: &Hunt) {
+        self.form = HuntForm::of(hunt);
+        self.target_search.clear();
+        self.alert_probabilities = hunt.alerts.probabilities_text();
+    }
+
+    /// Whether the edited hunt can be saved as it is
+    pub fn can_save(&self) -> bool {
+        self.form.is_valid()
     }
 
     pub fn update(&mut self, message: HuntsMessage) -> HuntsAction {
@@ -295,21 +719,282 @@ impl Hunts {
             HuntsMessage::SelectHunt(id) => HuntsAction::SelectHunt(id),
             HuntsMessage::CloseSelectedHunt => HuntsAction::CloseSelectedHunt,
             HuntsMessage::StartEditHunt(id) => HuntsAction::StartEditHunt(id),
-            HuntsMessage::StopEditHunt(save) => HuntsAction::StopEditHunt(save),
-            _ => HuntsAction::None,
+            HuntsMessage::StopEditHunt(save) => {
+                HuntsAction::StopEditHunt(save && self.form.is_valid())
+            }
+            HuntsMessage::CreateHunt => HuntsAction::CreateHunt,
+            HuntsMessage::DeleteHunt(id) => HuntsAction::DeleteHunt(id),
+            HuntsMessage::SelectReportFormat(format) => {
+                self.report_format = format;
+                HuntsAction::None
+            }
+            HuntsMessage::CopyReport(id) => HuntsAction::CopyReport(id, self.report_format),
+            HuntsMessage::CopyListReport => HuntsAction::CopyListReport(self.report_format),
+            HuntsMessage::EditReportTemplates => HuntsAction::EditReportTemplates,
+            HuntsMessage::EditTargetSearch(search) => {
+                self.target_search = search;
+                HuntsAction::None
+            }
+            HuntsMessage::AddTarget => match Species::from_name(self.target_search.trim()) {
+                Some(species) => {
+                    self.target_search.clear();
+                    HuntsAction::EditHunt(HuntEditAction::AddTarget(species))
+                }
+                None => HuntsAction::None,
+            },
+            HuntsMessage::RemoveTarget(index) => {
+                HuntsAction::EditHunt(HuntEditAction::RemoveTarget(index))
+            }
+            HuntsMessage::EditAlertEvery(every) => match every.trim() {
+                "" => HuntsAction::EditHunt(HuntEditAction::SetAlertEvery(0)),
+                every => match every.parse() {
+                    Ok(every) => HuntsAction::EditHunt(HuntEditAction::SetAlertEvery(every)),
+                    Err(_) => HuntsAction::None,
+                },
+            },
+            HuntsMessage::EditAlertBaseOdds(base_odds) => {
+                HuntsAction::EditHunt(HuntEditAction::SetAlertBaseOdds(base_odds))
+            }
+            HuntsMessage::EditAlertProbabilities(probabilities) => {
+                let action = match HuntAlerts::parse_probabilities(&probabilities) {
+                    Some(probabilities) => {
+                        HuntsAction::EditHunt(HuntEditAction::SetAlertProbabilities(probabilities))
+                    }
+                    None => HuntsAction::None,
+                };
+                self.alert_probabilities = probabilities;
+                action
+            }
+            HuntsMessage::EditAlertSound(sound) => {
+                HuntsAction::EditHunt(HuntEditAction::SetAlertSound(sound))
+            }
+            HuntsMessage::ToggleCompareMode => {
+                self.compared = match self.compared {
+                    Some(_) => None,
+                    None => Some(Vec::new()),
+                };
+                self.show_comparison = false;
+                HuntsAction::CloseSelectedHunt
+            }
+            HuntsMessage::ToggleCompared(hunt_id) => {
+                if let Some(compared) = &mut self.compared {
+                    match compared.iter().position(|id| *id == hunt_id) {
+                        Some(position) => {
+                            compared.remove(position);
+                        }
+                        None => compared.push(hunt_id),
+                    }
+                }
+                HuntsAction::None
+            }
+            HuntsMessage::ShowComparison(show) => {
+                self.show_comparison = show;
+                HuntsAction::None
+            }
+            HuntsMessage::Filter(message) => match self.filter_bar.update(message) {
+                Some(edit) => HuntsAction::EditFilter(edit),
+                None => HuntsAction::None,
+            },
+            HuntsMessage::EditTarget(target) => {
+                self.form.target = target;
+                match Species::from_name(&self.form.target) {
+                    Some(species) if self.form.target_error().is_none() => {
+                        HuntsAction::EditHunt(HuntEditAction::SetTarget(species))
+                    }
+                    _ => HuntsAction::None,
+                }
+            }
+            HuntsMessage::PickTarget(species) => {
+                self.form.target = species.to_string();
+                HuntsAction::EditHunt(HuntEditAction::SetTarget(species))
+            }
+            HuntsMessage::EditPhaseEncounterCount(count) => {
+                self.form.phase_encounters = count;
+                match required_count(&self.form.phase_encounters) {
+                    Ok(count) => {
+                        // The previous phases are kept, so the total follows
+                        self.form.total_encounters =
+                            (self.form.previous_encounters + count).to_string();
+                        HuntsAction::EditHunt(HuntEditAction::SetPhaseEncounters(count))
+                    }
+                    Err(_) => HuntsAction::None,
+                }
+            }
+            HuntsMessage::EditTotalEncounterCount(count) => {
+                self.form.total_encounters = count;
+                match (
+                    required_count(&self.form.total_encounters),
+                    required_count(&self.form.phase_encounters),
+                ) {
+                    (Ok(total), Ok(phase)) if total >= phase => {
+                        self.form.previous_encounters = total - phase;
+                        HuntsAction::EditHunt(HuntEditAction::SetTotalEncounters(total))
+                    }
+                    _ => HuntsAction::None,
+                }
+            }
+            HuntsMessage::EditPhaseCount(count) => {
+                self.form.phase_count = count;
+                match required_count(&self.form.phase_count) {
+                    Ok(count) if count > 0 => {
+                        HuntsAction::EditHunt(HuntEditAction::SetPhaseCount(count))
+                    }
+                    _ => HuntsAction::None,
+                }
+            }
+            HuntsMessage::EditCompleted(completed) => {
+                HuntsAction::EditHunt(HuntEditAction::SetCompleted(completed))
+            }
+            HuntsMessage::EditStartDate(date) => {
+                self.form.start_time.date = date;
+                self.edit_start_time()
+            }
+            HuntsMessage::EditStartTime(time) => {
+                self.form.start_time.time = time;
+                self.edit_start_time()
+            }
+            HuntsMessage::EditEndDate(date) => {
+                self.form.end_time.date = date;
+                self.edit_end_time()
+            }
+            HuntsMessage::EditEndTime(time) => {
+                self.form.end_time.time = time;
+                self.edit_end_time()
+            }
+            HuntsMessage::EditVersion(version) => {
+                HuntsAction::EditHunt(HuntEditAction::SetVersion(optional_text(version)))
+            }
+            HuntsMessage::EditMethod(method) => {
+                HuntsAction::EditHunt(HuntEditAction::SetMethod(optional_text(method)))
+            }
+            HuntsMessage::EditPlace(place) => {
+                HuntsAction::EditHunt(HuntEditAction::SetPlace(optional_text(place)))
+            }
+            HuntsMessage::EditNotes(action) => {
+                let is_edit = action.is_edit();
+                self.form.notes.perform(action);
+                if is_edit {
+                    HuntsAction::EditHunt(HuntEditAction::SetNotes(optional_text(
+                        self.form.notes.text().trim_end().to_string(),
+                    )))
+                } else {
+                    HuntsAction::None
+                }
+            }
+        }
+    }
+
+    fn edit_start_time(&self) -> HuntsAction {
+        match self.form.start_time.parse() {
+            Ok(time) => HuntsAction::EditHunt(HuntEditAction::SetStartTime(time)),
+            Err(_) => HuntsAction::None,
+        }
+    }
+
+    fn edit_end_time(&self) -> HuntsAction {
+        match self.form.end_time.parse() {
+            Ok(time) => HuntsAction::EditHunt(HuntEditAction::SetEndTime(time)),
+            Err(_) => HuntsAction::None,
         }
     }
 
-    pub fn view<'a>(&'a self, state: &'a State) -> Element<HuntsMessage> {
-        let header = container(row![
-            text("Mes recherches").size(24),
-            horizontal_space(),
-            button("Nouvelle recherche").on_press(HuntsMessage::CreateHunt)
-        ])
+    pub fn view<'a>(&'a self, state: &'a State) -> Element<'a, HuntsMessage> {
+        let header = container(
+            row![
+                text("Mes recherches").size(24),
+                horizontal_space(),
+                pick_list(
+                    &ReportFormat::ALL[..],
+                    Some(self.report_format),
+                    HuntsMessage::SelectReportFormat
+                ),
+                button("Copier la liste").on_press(HuntsMessage::CopyListReport),
+                button("Modèles de rapport").on_press(HuntsMessage::EditReportTemplates),
+                button(if self.compared.is_some() {
+                    "Terminer la comparaison"
+                } else {
+                    "Comparer"
+                })
+                .on_press(HuntsMessage::ToggleCompareMode),
+                button("Nouvelle recherche").on_press_maybe(
+                    (state.editing_hunt_index.is_none() && !state.creating_hunt)
+                        .then_some(HuntsMessage::CreateHunt)
+                )
+            ]
+            .spacing(8),
+        )
         .style(navbar)
         .padding(16);
 
-        let build_columns = |size: Size| {
+        let compared = self.compared.as_ref().map(|compared| {
+            compared
+                .iter()
+                .filter_map(|hunt_id| {
+                    state
+                        .all_hunts
+                        .iter()
+                        .find(|hunt| hunt.id == Some(*hunt_id))
+                })
+                .collect::<Vec<&Hunt>>()
+        });
+        let compare_bar = compared.as_ref().map(|compared| {
+            container(
+                row![
+                    text(if self.show_comparison {
+                        format!("Comparaison de {} recherches", compared.len())
+                    } else {
+                        format!(
+                            "Sélectionnez au moins deux recherches à comparer ({} sélectionnées)",
+                            compared.len()
+                        )
+                    }),
+                    horizontal_space(),
+                    if self.show_comparison {
+                        button("Modifier la sélection")
+                            .on_press(HuntsMessage::ShowComparison(false))
+                    } else {
+                        button("Afficher la comparaison").on_press_maybe(
+                            (compared.len() >= 2).then_some(HuntsMessage::ShowComparison(true)),
+                        )
+                    },
+                ]
+                .spacing(8)
+                .align_y(Vertical::Center),
+            )
+            .padding([8, 40])
+        });
+        let is_compared = |hunt: &Hunt| {
+            self.compared
+                .as_ref()
+                .map(|compared| hunt.id.is_some_and(|hunt_id| compared.contains(&hunt_id)))
+        };
+
+        let filter = &state.settings.filters.hunts;
+        let shown = filter.indices(&state.all_hunts);
+        let filter_bar = self
+            .filter_bar
+            .view(
+                filter,
+                filters::choices(&state.all_hunts, |hunt| hunt.version.as_deref()),
+                filters::choices(&state.all_hunts, |hunt| hunt.method.as_deref()),
+                ["Terminées", "En cours"],
+                (shown.len(), state.all_hunts.len()),
+            )
+            .map(HuntsMessage::Filter);
+        let list = || {
+            scrollable(
+                column(shown.iter().map(|index| {
+                    state.all_hunts[*index]
+                        .view_card(*index, None, state)
+                        .into()
+                }))
+                .spacing(20)
+                .padding(40),
+            )
+        };
+
+        let grid_shown = shown.clone();
+        let build_columns = move |size: Size| {
             let n_columns: usize = match size.width {
                 x if x < 400.0 => 1,
                 x if x < 800.0 => 1,
@@ -319,12 +1004,9 @@ impl Hunts {
             };
             scrollable(
                 row((0..n_columns).map(|i| {
-                    column((i..state.all_hunts.len()).step_by(n_columns).map(|index| {
-                        state
-                            .all_hunts
-                            .get(index)
-                            .map(|hunt| hunt.view_card(index).into())
-                            .unwrap()
+                    column(grid_shown.iter().skip(i).step_by(n_columns).map(|index| {
+                        let hunt = &state.all_hunts[*index];
+                        hunt.view_card(*index, is_compared(hunt), state).into()
                     }))
                     .spacing(20)
                     .into()
@@ -335,52 +1017,29 @@ impl Hunts {
             .into()
         };
 
-        let content = if let Some(index) = state.editing_hunt_index {
-            state
-                .all_hunts
-                .get(index)
-                .map(|hunt| {
-                    container(row![
-                        scrollable(
-                            column(
-                                state
-                                    .all_hunts
-                                    .iter()
-                                    .enumerate()
-                                    .map(|(index, hunt)| hunt.view_card(index).into())
-                            )
-                            .spacing(20)
-                            .padding(40)
-                        ),
-                        hunt.view_editing()
-                    ])
-                    .width(Length::Fill)
-                })
-                .unwrap_or(
-                    container(responsive(build_columns))
-                        .height(Length::Fill)
-                        .width(Length::Fill),
+        let content = if let Some(compared) = compared.filter(|_| self.show_comparison) {
+            container(view_comparison(compared)).padding([16, 40])
+        } else if self.compared.is_some() {
+            container(responsive(build_columns))
+                .height(Length::Fill)
+                .width(Length::Fill)
+        } else if state.editing_hunt_index.is_some() || state.creating_hunt {
+            container(row![
+                list(),
+                state.editing_hunt.view_editing(
+                    &self.form,
+                    &self.target_search,
+                    &self.alert_probabilities,
+                    state
                 )
+            ])
+            .width(Length::Fill)
         } else if let Some(index) = state.selected_hunt {
             state
                 .all_hunts
                 .get(index)
                 .map(|hunt| {
-                    container(row![
-                        scrollable(
-                            column(
-                                state
-                                    .all_hunts
-                                    .iter()
-                                    .enumerate()
-                                    .map(|(index, hunt)| hunt.view_card(index).into())
-                            )
-                            .spacing(20)
-                            .padding(40)
-                        ),
-                        hunt.view_detailed(index)
-                    ])
-                    .width(Length::Fill)
+                    container(row![list(), hunt.view_detailed(index, state)]).width(Length::Fill)
                 })
                 .unwrap_or(
                     container(responsive(build_columns))
@@ -393,6 +1052,9 @@ impl Hunts {
                 .width(Length::Fill)
         };
 
-        column![header, content].into()
+        column![header, filter_bar]
+            .push_maybe(compare_bar)
+            .push(content)
+            .into()
     }
 }
@@ -0,0 +1,226 @@
+use crate::merge::{Conflict, MergePlan, Resolution};
+use crate::theme::card;
+
+use iced::widget::{
+    button, column, container, horizontal_space, radio, row, scrollable, text, text_input, Column,
+};
+use iced::{Element, Length};
+
+#[derive(Debug, Clone)]
+pub enum MergeMessage {
+    EditPath(String),
+    Analyze,
+    ResolveHunt(usize, Resolution),
+    ResolveShiny(usize, Resolution),
+    StopMerge(bool),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum MergeAction {
+    None,
+    Analyze,
+    StopMerge(bool),
+}
+
+#[derive(Default)]
+pub struct MergeTool {
+    path: String,
+    pub plan: Option<MergePlan>,
+    pub error: Option<String>,
+}
+
+fn view_conflict<'a, T: std::fmt::Display>(
+    conflict: &'a Conflict<T>,
+    on_resolve: impl Fn(Resolution) -> MergeMessage,
+) -> Element<'a, MergeMessage> {
+    let side = |title: &'a str, values: Vec<&'a str>| {
+        column![text(title).size(14)]
+            .extend(
+                conflict
+                    .differences
+                    .iter()
+                    .zip(values)
+                    .map(|(difference, value)| {
+                        text(format!("{} : {}", difference.label, value))
+                            .size(14)
+                            .into()
+                    }),
+            )
+            .spacing(4)
+            .width(Length::Fill)
+    };
+
+    container(
+        column![
+            text(conflict.local.to_string()).size(16),
+            row![
+                side(
+                    "Cette base",
+                    conflict
+                        .differences
+                        .iter()
+                        .map(|d| d.local.as_str())
+                        .collect()
+                ),
+                side(
+                    "Autre base",
+                    conflict
+                        .differences
+                        .iter()
+                        .map(|d| d.other.as_str())
+                        .collect()
+                ),
+            ]
+            .spacing(16),
+            row![
+                radio(
+                    "Garder cette base",
+                    Resolution::KeepLocal,
+                    Some(conflict.resolution),
+                    &on_resolve
+                ),
+                radio(
+                    "Prendre l'autre base",
+                    Resolution::TakeOther,
+                    Some(conflict.resolution),
+                    &on_resolve
+                ),
+            ]
+            .spacing(16),
+        ]
+        .spacing(8)
+        .padding(16),
+    )
+    .width(Length::Fill)
+    .style(card)
+    .into()
+}
+
+impl MergeTool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn update(&mut self, message: MergeMessage) -> MergeAction {
+        match message {
+            MergeMessage::EditPath(path) => {
+                self.path = path;
+                self.plan = None;
+                self.error = None;
+                MergeAction::None
+            }
+            MergeMessage::Analyze => MergeAction::Analyze,
+            MergeMessage::ResolveHunt(index, resolution) => {
+                if let Some(conflict) = self
+                    .plan
+                    .as_mut()
+                    .and_then(|plan| plan.hunt_conflicts.get_mut(index))
+                {
+                    conflict.resolution = resolution;
+                }
+                MergeAction::None
+            }
+            MergeMessage::ResolveShiny(index, resolution) => {
+                if let Some(conflict) = self
+                    .plan
+                    .as_mut()
+                    .and_then(|plan| plan.shiny_conflicts.get_mut(index))
+                {
+                    conflict.resolution = resolution;
+                }
+                MergeAction::None
+            }
+            MergeMessage::StopMerge(save) => MergeAction::StopMerge(save),
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, MergeMessage> {
+        let mut content = column![
+            container(text("Fusionner une autre base")).center_x(Length::Fill),
+            row![
+                text_input("Chemin du fichier db.sqlite", &self.path)
+                    .on_input(MergeMessage::EditPath)
+                    .on_submit(MergeMessage::Analyze),
+                button("Analyser").on_press(MergeMessage::Analyze),
+            ]
+            .spacing(8),
+        ]
+        .spacing(8);
+
+        if let Some(error) = &self.error {
+            content = content.push(text(error.clone()));
+        }
+
+        if let Some(plan) = &self.plan {
+            content = content.push(
+                column![
+                    text(format!(
+                        "{} recherche(s) et {} shiny(s) à ajouter",
+                        plan.new_hunts.len(),
+                        plan.new_shinies.len()
+                    )),
+                    text(format!(
+                        "{} recherche(s) et {} shiny(s) identiques",
+                        plan.identical_hunts, plan.identical_shinies
+                    )),
+                    text(format!(
+                        "{} conflit(s)",
+                        plan.hunt_conflicts.len() + plan.shiny_conflicts.len()
+                    )),
+                ]
+                .spacing(4),
+            );
+
+            let conflicts = Column::new()
+                .extend(
+                    plan.hunt_conflicts
+                        .iter()
+                        .enumerate()
+                        .map(|(index, conflict)| {
+                            view_conflict(conflict, move |resolution| {
+                                MergeMessage::ResolveHunt(index, resolution)
+                            })
+                        }),
+                )
+                .extend(
+                    plan.shiny_conflicts
+                        .iter()
+                        .enumerate()
+                        .map(|(index, conflict)| {
+                            view_conflict(conflict, move |resolution| {
+                                MergeMessage::ResolveShiny(index, resolution)
+                            })
+                        }),
+                )
+                .spacing(12);
+            content = content.push(scrollable(conflicts).height(400));
+        }
+
+        let merge_button = button("Fusionner").on_press_maybe(
+            self.plan
+                .as_ref()
+                .filter(|plan| !plan.is_empty())
+                .map(|_| MergeMessage::StopMerge(true)),
+        );
+
+        container(
+            content
+                .push(
+                    row![
+                        horizontal_space(),
+                        merge_button,
+                        button("Annuler").on_press(MergeMessage::StopMerge(false))
+                    ]
+                    .spacing(8),
+                )
+                .padding(32)
+                .width(800),
+        )
+        .style(card)
+        .into()
+    }
+}
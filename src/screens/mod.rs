@@ -1,14 +1,26 @@
 pub mod counters;
+pub mod dex;
+pub mod filter_bar;
+pub mod form;
 pub mod hunts;
+pub mod merge;
 pub mod shinies;
+pub mod stats;
+pub mod templates;
 
 pub use counters::{Counters, CountersMessage};
+pub use dex::{Dex, DexMessage};
 pub use hunts::{Hunts, HuntsAction, HuntsMessage};
+pub use merge::{MergeMessage, MergeTool};
 pub use shinies::{Shinies, ShiniesMessage};
+pub use stats::{Stats, StatsMessage};
+pub use templates::{TemplatesEditor, TemplatesMessage};
 
 #[derive(Debug, Clone, Copy)]
 pub enum ScreenType {
     Counters,
     Hunts,
     Shinies,
+    Stats,
+    Dex,
 }
@@ -1,27 +1,55 @@
-use crate::shiny::Shiny;
+use crate::data::Species;
+use crate::filters::{self, FilterEdit};
+use crate::odds;
+use crate::report::ReportFormat;
+use crate::screens::filter_bar::{FilterBar, FilterBarMessage};
+use crate::screens::form::{
+    form_row, optional_text, parse_count, species_suggestions, DateTimeInput,
+};
+use crate::shiny::{Shiny, ShinyEditAction};
 use crate::theme::{card, navbar, side_view};
 use crate::State;
 
 use iced::alignment::{Horizontal, Vertical};
 use iced::widget::{
-    button, column, container, horizontal_space, mouse_area, responsive, row, scrollable, stack,
-    svg, text, Container,
+    button, column, container, horizontal_space, mouse_area, pick_list, radio, responsive, row,
+    scrollable, stack, svg, text, text_editor, text_input, Container,
 };
 use iced::{Element, Length, Pixels, Size};
 
 const COG_ICON: &[u8] = include_bytes!("../../assets/cog.svg");
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum ShiniesMessage {
     CreateShiny,
     DeleteShiny(usize),
     SelectShiny(usize),
     CloseSelectedShiny,
     StartEditShiny(usize),
-    StopEditShiny,
+    StopEditShiny(bool),
+    SelectReportFormat(ReportFormat),
+    CopyReport(usize),
+    CopyListReport,
+    EditReportTemplates,
+    Filter(FilterBarMessage),
+    EditSpecies(String),
+    PickSpecies(Species),
+    EditGender(Option<i32>),
+    EditName(String),
+    EditTotalEncounters(String),
+    EditPhaseEncounters(String),
+    EditPhaseNumber(String),
+    EditDate(String),
+    EditTime(String),
+    EditVersion(String),
+    EditMethod(String),
+    EditPlace(String),
+    EditNotes(text_editor::Action),
+    LinkHunt(HuntChoice),
+    UnlinkHunt,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum ShiniesAction {
     None,
     CreateShiny,
@@ -29,7 +57,87 @@ pub enum ShiniesAction {
     SelectShiny(usize),
     CloseSelectedShiny,
     StartEditShiny(usize),
-    StopEditShiny,
+    StopEditShiny(bool),
+    CopyReport(usize, ReportFormat),
+    CopyListReport(ReportFormat),
+    EditReportTemplates,
+    EditFilter(FilterEdit),
+    EditShiny(ShinyEditAction),
+}
+
+/// Hunt the edited shiny can be linked to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HuntChoice {
+    id: i32,
+    label: String,
+}
+
+impl std::fmt::Display for HuntChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.label)
+    }
+}
+
+/// Inputs of the shiny editor that need to be parsed, kept as typed so
+/// invalid values can be shown and fixed
+#[derive(Debug, Default)]
+struct ShinyForm {
+    species: String,
+    total_encounters: String,
+    phase_encounters: String,
+    phase_number: String,
+    found_time: DateTimeInput,
+    notes: text_editor::Content,
+}
+
+impl ShinyForm {
+    fn of(shiny: &Shiny) -> Self {
+        let count = |count: Option<i32>| count.map(|count| count.to_string()).unwrap_or_default();
+        Self {
+            species: shiny.species.to_string(),
+            total_encounters: count(shiny.total_encounters),
+            phase_encounters: count(shiny.phase_encounters),
+            phase_number: count(shiny.phase_number),
+            found_time: DateTimeInput::of(shiny.found_time),
+            notes: text_editor::Content::with_text(shiny.notes.as_deref().unwrap_or_default()),
+        }
+    }
+
+    fn species_error(&self) -> Option<&'static str> {
+        match Species::from_name(&self.species) {
+            Some(Species::Egg) | None => Some("Espèce inconnue"),
+            Some(_) => None,
+        }
+    }
+
+    fn phase_encounters_error(&self) -> Option<&'static str> {
+        match (
+            parse_count(&self.phase_encounters),
+            parse_count(&self.total_encounters),
+        ) {
+            (Err(error), _) => Some(error),
+            (Ok(Some(phase)), Ok(Some(total))) if phase > total => {
+                Some("Plus de rencontres que le total")
+            }
+            _ => None,
+        }
+    }
+
+    fn phase_number_error(&self) -> Option<&'static str> {
+        match parse_count(&self.phase_number) {
+            Ok(Some(0)) => Some("Les phases commencent à 1"),
+            Ok(_) => None,
+            Err(error) => Some(error),
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        self.species_error().is_none()
+            && parse_count(&self.total_encounters).is_ok()
+            && self.phase_encounters_error().is_none()
+            && self.phase_number_error().is_none()
+            && self.found_time.parse().is_ok()
+    }
 }
 
 fn make_row<'a>(
@@ -48,13 +156,17 @@ fn make_row<'a>(
 }
 
 impl Shiny {
-    pub fn view_card(&self, index: usize) -> Container<ShiniesMessage> {
+    pub fn view_card<'a>(
+        &'a self,
+        index: usize,
+        state: &'a State,
+    ) -> Container<'a, ShiniesMessage> {
         container(
             mouse_area(stack![
                 container(
                     column![
                         row![
-                            container(text("sprite here").width(100).height(100))
+                            container(state.sprite(self.sprite_key(), 100))
                                 .align_right(Length::Fill),
                             column![
                                 text(if let Some(name) = self.name.clone() {
@@ -108,6 +220,13 @@ impl Shiny {
                                     .unwrap_or("Inconnue".into()),
                                 14
                             ),
+                            make_row(
+                                "Chance :",
+                                self.luck_percentile()
+                                    .map(odds::format_luck)
+                                    .unwrap_or("Inconnue".into()),
+                                14
+                            ),
                         ]
                         .spacing(12)
                         .padding([8, 16])
@@ -134,16 +253,21 @@ impl Shiny {
         .style(card)
     }
 
-    pub fn view_detailed(&self, index: usize) -> Container<ShiniesMessage> {
+    pub fn view_detailed<'a>(
+        &'a self,
+        index: usize,
+        state: &'a State,
+    ) -> Container<'a, ShiniesMessage> {
         container(scrollable(
             column![
                 row![
                     horizontal_space(),
+                    button("Copier").on_press(ShiniesMessage::CopyReport(index)),
                     button("Modifier").on_press(ShiniesMessage::StartEditShiny(index)),
                     button("Fermer").on_press(ShiniesMessage::CloseSelectedShiny)
                 ]
                 .spacing(8),
-                container(text("sprite here").width(100).height(100)),
+                container(state.sprite(self.sprite_key(), 100)),
                 make_row("Espèce :", self.species.to_string(), 16),
                 make_row(
                     "Rencontres (phase) :",
@@ -190,6 +314,13 @@ impl Shiny {
                         .unwrap_or("Inconnue".into()),
                     16
                 ),
+                make_row(
+                    "Chance :",
+                    self.luck_percentile()
+                        .map(odds::format_luck)
+                        .unwrap_or("Inconnue".into()),
+                    16
+                ),
                 column![text("Notes"), text(self.notes.clone().unwrap_or("".into())),],
             ]
             .spacing(12)
@@ -199,62 +330,365 @@ impl Shiny {
         .height(Length::Fill)
         .style(side_view)
     }
+
+    fn view_editing<'a>(
+        &'a self,
+        form: &'a ShinyForm,
+        state: &'a State,
+    ) -> Container<'a, ShiniesMessage> {
+        let suggestions = form
+            .species_error()
+            .and_then(|_| species_suggestions(&form.species, ShiniesMessage::PickSpecies));
+
+        let hunts: Vec<HuntChoice> = state
+            .all_hunts
+            .iter()
+            .filter_map(|hunt| {
+                Some(HuntChoice {
+                    id: hunt.id?,
+                    label: hunt.to_string(),
+                })
+            })
+            .collect();
+        let linked_hunt = hunts
+            .iter()
+            .find(|hunt| Some(hunt.id) == self.hunt_id)
+            .cloned();
+
+        let text_field = |value: &Option<String>| value.clone().unwrap_or_default();
+        let gender = |label, value| {
+            radio(label, value, Some(self.gender), ShiniesMessage::EditGender).size(16)
+        };
+
+        container(scrollable(
+            column![
+                row![
+                    horizontal_space(),
+                    button("Enregistrer").on_press_maybe(
+                        form.is_valid()
+                            .then_some(ShiniesMessage::StopEditShiny(true))
+                    ),
+                    button("Annuler").on_press(ShiniesMessage::StopEditShiny(false))
+                ]
+                .spacing(8),
+                container(state.sprite(self.sprite_key(), 100))
+                    .width(Length::Fill)
+                    .align_x(Horizontal::Center),
+                form_row(
+                    "Espèce :",
+                    column![text_input("Nom ou n° du Pokédex", &form.species)
+                        .size(16)
+                        .on_input(ShiniesMessage::EditSpecies)]
+                    .push_maybe(suggestions)
+                    .spacing(4),
+                    form.species_error(),
+                ),
+                form_row(
+                    "Sexe :",
+                    row![
+                        gender("Inconnu", None),
+                        gender("Femelle", Some(0)),
+                        gender("Mâle", Some(1)),
+                    ]
+                    .spacing(16),
+                    None,
+                ),
+                form_row(
+                    "Surnom :",
+                    text_input("Aucun", &text_field(&self.name))
+                        .size(16)
+                        .on_input(ShiniesMessage::EditName),
+                    None,
+                ),
+                form_row(
+                    "Rencontres (phase) :",
+                    text_input("Inconnues", &form.phase_encounters)
+                        .size(16)
+                        .on_input(ShiniesMessage::EditPhaseEncounters),
+                    form.phase_encounters_error(),
+                ),
+                form_row(
+                    "Rencontres (total) :",
+                    text_input("Inconnues", &form.total_encounters)
+                        .size(16)
+                        .on_input(ShiniesMessage::EditTotalEncounters),
+                    parse_count(&form.total_encounters).err(),
+                ),
+                form_row(
+                    "Phase :",
+                    text_input("Inconnue", &form.phase_number)
+                        .size(16)
+                        .on_input(ShiniesMessage::EditPhaseNumber),
+                    form.phase_number_error(),
+                ),
+                form_row(
+                    "Trouvé le :",
+                    form.found_time
+                        .view(ShiniesMessage::EditDate, ShiniesMessage::EditTime),
+                    form.found_time.parse().err(),
+                ),
+                form_row(
+                    "Version :",
+                    text_input("Inconnue", &text_field(&self.version))
+                        .size(16)
+                        .on_input(ShiniesMessage::EditVersion),
+                    None,
+                ),
+                form_row(
+                    "Méthode :",
+                    text_input("Inconnue", &text_field(&self.method))
+                        .size(16)
+                        .on_input(ShiniesMessage::EditMethod),
+                    None,
+                ),
+                form_row(
+                    "Zone :",
+                    text_input("Inconnue", &text_field(&self.place))
+                        .size(16)
+                        .on_input(ShiniesMessage::EditPlace),
+                    None,
+                ),
+                form_row(
+                    "Recherche :",
+                    row![
+                        pick_list(hunts, linked_hunt, ShiniesMessage::LinkHunt)
+                            .placeholder("Aucune")
+                            .text_size(16),
+                        button(text("x"))
+                            .on_press_maybe(self.hunt_id.map(|_| ShiniesMessage::UnlinkHunt)),
+                    ]
+                    .spacing(8),
+                    None,
+                ),
+                column![
+                    text("Notes"),
+                    text_editor(&form.notes)
+                        .on_action(ShiniesMessage::EditNotes)
+                        .height(120),
+                ]
+                .spacing(8),
+            ]
+            .spacing(12)
+            .padding(16),
+        ))
+        .width(Length::Fill)
+        .height(Length::Fill)
+        .style(side_view)
+    }
 }
 
-#[derive(Debug, Clone, Copy, Default)]
-pub struct Shinies {}
+#[derive(Debug)]
+pub struct Shinies {
+    report_format: ReportFormat,
+    filter_bar: FilterBar,
+    form: ShinyForm,
+}
+
+impl Default for Shinies {
+    fn default() -> Self {
+        Self {
+            report_format: ReportFormat::Markdown,
+            filter_bar: FilterBar::default(),
+            form: ShinyForm::default(),
+        }
+    }
+}
 
 impl Shinies {
     pub fn new() -> Self {
-        Self {}
+        Self::default()
+    }
+
+    /// Fills the inputs of the editor with the shiny being edited
+    pub fn start_editing(&mut self, shiny: &Shiny) {
+        self.form = ShinyForm::of(shiny);
+    }
+
+    /// Whether the edited shiny can be saved as it is
+    pub fn can_save(&self) -> bool {
+        self.form.is_valid()
     }
 
     pub fn update(&mut self, message: ShiniesMessage) -> ShiniesAction {
         match message {
             ShiniesMessage::SelectShiny(id) => ShiniesAction::SelectShiny(id),
             ShiniesMessage::CloseSelectedShiny => ShiniesAction::CloseSelectedShiny,
-            _ => ShiniesAction::None,
+            ShiniesMessage::CreateShiny => ShiniesAction::CreateShiny,
+            ShiniesMessage::DeleteShiny(id) => ShiniesAction::DeleteShiny(id),
+            ShiniesMessage::StartEditShiny(id) => ShiniesAction::StartEditShiny(id),
+            ShiniesMessage::StopEditShiny(save) => {
+                ShiniesAction::StopEditShiny(save && self.form.is_valid())
+            }
+            ShiniesMessage::SelectReportFormat(format) => {
+                self.report_format = format;
+                ShiniesAction::None
+            }
+            ShiniesMessage::CopyReport(id) => ShiniesAction::CopyReport(id, self.report_format),
+            ShiniesMessage::CopyListReport => ShiniesAction::CopyListReport(self.report_format),
+            ShiniesMessage::EditReportTemplates => ShiniesAction::EditReportTemplates,
+            ShiniesMessage::Filter(message) => match self.filter_bar.update(message) {
+                Some(edit) => ShiniesAction::EditFilter(edit),
+                None => ShiniesAction::None,
+            },
+            ShiniesMessage::EditSpecies(species) => {
+                self.form.species = species;
+                match Species::from_name(&self.form.species) {
+                    Some(species) if self.form.species_error().is_none() => {
+                        ShiniesAction::EditShiny(ShinyEditAction::SetSpecies(species))
+                    }
+                    _ => ShiniesAction::None,
+                }
+            }
+            ShiniesMessage::PickSpecies(species) => {
+                self.form.species = species.to_string();
+                ShiniesAction::EditShiny(ShinyEditAction::SetSpecies(species))
+            }
+            ShiniesMessage::EditGender(gender) => {
+                ShiniesAction::EditShiny(ShinyEditAction::SetGender(gender))
+            }
+            ShiniesMessage::EditName(name) => {
+                ShiniesAction::EditShiny(ShinyEditAction::SetName(optional_text(name)))
+            }
+            ShiniesMessage::EditTotalEncounters(count) => {
+                self.form.total_encounters = count;
+                match parse_count(&self.form.total_encounters) {
+                    Ok(count) => {
+                        ShiniesAction::EditShiny(ShinyEditAction::SetTotalEncounters(count))
+                    }
+                    Err(_) => ShiniesAction::None,
+                }
+            }
+            ShiniesMessage::EditPhaseEncounters(count) => {
+                self.form.phase_encounters = count;
+                match parse_count(&self.form.phase_encounters) {
+                    Ok(count) => {
+                        ShiniesAction::EditShiny(ShinyEditAction::SetPhaseEncounters(count))
+                    }
+                    Err(_) => ShiniesAction::None,
+                }
+            }
+            ShiniesMessage::EditPhaseNumber(number) => {
+                self.form.phase_number = number;
+                match parse_count(&self.form.phase_number) {
+                    Ok(number) => ShiniesAction::EditShiny(ShinyEditAction::SetPhaseNumber(number)),
+                    Err(_) => ShiniesAction::None,
+                }
+            }
+            ShiniesMessage::EditDate(date) => {
+                self.form.found_time.date = date;
+                match self.form.found_time.parse() {
+                    Ok(time) => ShiniesAction::EditShiny(ShinyEditAction::SetFoundTime(time)),
+                    Err(_) => ShiniesAction::None,
+                }
+            }
+            ShiniesMessage::EditTime(time) => {
+                self.form.found_time.time = time;
+                match self.form.found_time.parse() {
+                    Ok(time) => ShiniesAction::EditShiny(ShinyEditAction::SetFoundTime(time)),
+                    Err(_) => ShiniesAction::None,
+                }
+            }
+            ShiniesMessage::EditVersion(version) => {
+                ShiniesAction::EditShiny(ShinyEditAction::SetVersion(optional_text(version)))
+            }
+            ShiniesMessage::EditMethod(method) => {
+                ShiniesAction::EditShiny(ShinyEditAction::SetMethod(optional_text(method)))
+            }
+            ShiniesMessage::EditPlace(place) => {
+                ShiniesAction::EditShiny(ShinyEditAction::SetPlace(optional_text(place)))
+            }
+            ShiniesMessage::EditNotes(action) => {
+                let is_edit = action.is_edit();
+                self.form.notes.perform(action);
+                if is_edit {
+                    ShiniesAction::EditShiny(ShinyEditAction::SetNotes(optional_text(
+                        self.form.notes.text().trim_end().to_string(),
+                    )))
+                } else {
+                    ShiniesAction::None
+                }
+            }
+            ShiniesMessage::LinkHunt(hunt) => {
+                ShiniesAction::EditShiny(ShinyEditAction::SetHunt(Some(hunt.id)))
+            }
+            ShiniesMessage::UnlinkHunt => ShiniesAction::EditShiny(ShinyEditAction::SetHunt(None)),
         }
     }
 
-    pub fn view<'a>(&'a self, state: &'a State) -> Element<ShiniesMessage> {
-        let header = container(row![
-            text("Mes shinies").size(24),
-            horizontal_space(),
-            button("Nouveau shiny").on_press(ShiniesMessage::CreateShiny)
-        ])
+    pub fn view<'a>(&'a self, state: &'a State) -> Element<'a, ShiniesMessage> {
+        let header = container(
+            row![
+                text("Mes shinies").size(24),
+                horizontal_space(),
+                pick_list(
+                    &ReportFormat::ALL[..],
+                    Some(self.report_format),
+                    ShiniesMessage::SelectReportFormat
+                ),
+                button("Copier la liste").on_press(ShiniesMessage::CopyListReport),
+                button("Modèles de rapport").on_press(ShiniesMessage::EditReportTemplates),
+                button("Nouveau shiny").on_press_maybe(
+                    (state.editing_shiny_index.is_none() && !state.creating_shiny)
+                        .then_some(ShiniesMessage::CreateShiny)
+                )
+            ]
+            .spacing(8),
+        )
         .style(navbar)
         .padding(16);
 
-        let build_columns = |size: Size| {
-            let n_columns: usize = match size.width {
-                x if x < 400.0 => 1,
-                x if x < 800.0 => 1,
-                x if x < 1200.0 => 2,
-                x if x < 1600.0 => 3,
-                _ => 4,
-            };
-            scrollable(
-                row((0..n_columns).map(|i| {
-                    column(
-                        (i..state.all_shinies.len())
-                            .step_by(n_columns)
-                            .map(|index| {
-                                state
-                                    .all_shinies
-                                    .get(index)
-                                    .map(|shiny| shiny.view_card(index).into())
-                                    .unwrap()
+        let filter = &state.settings.filters.shinies;
+        let shown = filter.indices(&state.all_shinies);
+        let filter_bar = self
+            .filter_bar
+            .view(
+                filter,
+                filters::choices(&state.all_shinies, |shiny| shiny.version.as_deref()),
+                filters::choices(&state.all_shinies, |shiny| shiny.method.as_deref()),
+                ["Avec recherche", "Sans recherche"],
+                (shown.len(), state.all_shinies.len()),
+            )
+            .map(ShiniesMessage::Filter);
+
+        let grid_shown = shown.clone();
+        let build_columns =
+            move |size: Size| {
+                let n_columns: usize = match size.width {
+                    x if x < 400.0 => 1,
+                    x if x < 800.0 => 1,
+                    x if x < 1200.0 => 2,
+                    x if x < 1600.0 => 3,
+                    _ => 4,
+                };
+                scrollable(
+                    row((0..n_columns).map(|i| {
+                        column(
+                            grid_shown.iter().skip(i).step_by(n_columns).map(|index| {
+                                state.all_shinies[*index].view_card(*index, state).into()
                             }),
-                    )
+                        )
+                        .spacing(20)
+                        .into()
+                    }))
                     .spacing(20)
-                    .into()
-                }))
-                .spacing(20)
-                .padding(40),
-            )
-            .into()
-        };
+                    .padding(40),
+                )
+                .into()
+            };
+
+        let editing = (state.editing_shiny_index.is_some() || state.creating_shiny).then(|| {
+            container(row![
+                scrollable(
+                    column(shown.iter().map(|index| {
+                        state.all_shinies[*index].view_card(*index, state).into()
+                    }))
+                    .spacing(20)
+                    .padding(40)
+                ),
+                state.editing_shiny.view_editing(&self.form, state)
+            ])
+            .width(Length::Fill)
+        });
 
         let content = match state.selected_shiny {
             Some(index) => state
@@ -263,17 +697,13 @@ impl Shinies {
                 .map(|shiny| {
                     container(row![
                         scrollable(
-                            column(
-                                state
-                                    .all_shinies
-                                    .iter()
-                                    .enumerate()
-                                    .map(|(index, shiny)| shiny.view_card(index).into())
-                            )
+                            column(shown.iter().map(|index| {
+                                state.all_shinies[*index].view_card(*index, state).into()
+                            }))
                             .spacing(20)
                             .padding(40)
                         ),
-                        shiny.view_detailed(index)
+                        shiny.view_detailed(index, state)
                     ])
                     .width(Length::Fill)
                 })
@@ -287,6 +717,6 @@ impl Shinies {
                 .width(Length::Fill),
         };
 
-        column![header, content].into()
+        column![header, filter_bar, editing.unwrap_or(content)].into()
     }
 }
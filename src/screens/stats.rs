@@ -0,0 +1,442 @@
+use crate::charts::{BarChart, CalendarHeatmap};
+use crate::goals::{self, GoalPeriod};
+use crate::session::format_duration;
+use crate::stats::{Breakdown, HuntSummary, Statistics};
+use crate::theme::{card, chart_bar, goal_met, goal_missed, navbar};
+use crate::State;
+
+use chrono::{Local, NaiveDate, TimeDelta};
+use iced::alignment::{Horizontal, Vertical};
+use iced::widget::{
+    canvas, column, container, horizontal_space, pick_list, row, scrollable, text, Column,
+};
+use iced::{Element, Length};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakdownKind {
+    Version,
+    Method,
+    Place,
+    Year,
+}
+
+impl BreakdownKind {
+    const ALL: [BreakdownKind; 4] = [
+        BreakdownKind::Version,
+        BreakdownKind::Method,
+        BreakdownKind::Place,
+        BreakdownKind::Year,
+    ];
+}
+
+impl std::fmt::Display for BreakdownKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            BreakdownKind::Version => "Version",
+            BreakdownKind::Method => "Méthode",
+            BreakdownKind::Place => "Zone",
+            BreakdownKind::Year => "Année",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum StatsMessage {
+    SelectBreakdown(BreakdownKind),
+    SelectDay(NaiveDate),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum StatsAction {
+    None,
+}
+
+fn format_mean(value: Option<f64>) -> String {
+    value.map(|v| format!("{v:.0}")).unwrap_or("-".into())
+}
+
+fn summary_card<'a>(label: &'a str, value: String) -> Element<'a, StatsMessage> {
+    container(column![text(value).size(32), text(label).size(16)].align_x(Horizontal::Center))
+        .padding(16)
+        .center_x(Length::Fill)
+        .style(card)
+        .into()
+}
+
+fn hunt_row<'a>(hunt: &HuntSummary) -> iced::widget::Row<'a, StatsMessage> {
+    row![
+        text(hunt.name.clone()).size(16).width(Length::Fill),
+        text(format!("{} rencontres", hunt.encounters))
+            .size(16)
+            .width(Length::Fill),
+        text(format!("Phase {}", hunt.phase_count))
+            .size(16)
+            .width(Length::Fill),
+        text(
+            hunt.expected_hours
+                .map(|hours| format!(
+                    "Shiny attendu dans {}",
+                    format_duration(TimeDelta::seconds((hours * 3600.0) as i64))
+                ))
+                .unwrap_or_default()
+        )
+        .size(16)
+        .width(Length::FillPortion(2)),
+    ]
+    .spacing(8)
+}
+
+fn section<'a>(
+    title: impl text::IntoFragment<'a>,
+    content: impl Into<Element<'a, StatsMessage>>,
+) -> Element<'a, StatsMessage> {
+    container(column![text(title).size(20), content.into()].spacing(12))
+        .padding(16)
+        .width(Length::Fill)
+        .style(card)
+        .into()
+}
+
+pub struct Stats {
+    statistics: Box<Statistics>,
+    breakdown: BreakdownKind,
+    selected_day: Option<NaiveDate>,
+}
+
+impl Stats {
+    pub fn new(state: &State) -> Self {
+        Self {
+            statistics: Box::new(Statistics::compute(&state.all_hunts, &state.all_shinies)),
+            breakdown: BreakdownKind::Version,
+            selected_day: None,
+        }
+    }
+
+    pub fn update(&mut self, message: StatsMessage) -> StatsAction {
+        match message {
+            StatsMessage::SelectBreakdown(kind) => {
+                self.breakdown = kind;
+                StatsAction::None
+            }
+            StatsMessage::SelectDay(day) => {
+                self.selected_day = (self.selected_day != Some(day)).then_some(day);
+                StatsAction::None
+            }
+        }
+    }
+
+    fn breakdowns(&self) -> &[Breakdown] {
+        match self.breakdown {
+            BreakdownKind::Version => &self.statistics.by_version,
+            BreakdownKind::Method => &self.statistics.by_method,
+            BreakdownKind::Place => &self.statistics.by_place,
+            BreakdownKind::Year => &self.statistics.by_year,
+        }
+    }
+
+    pub fn view<'a>(&'a self, state: &'a State) -> Element<'a, StatsMessage> {
+        let statistics = &self.statistics;
+        let header = container(row![text("Statistiques").size(24), horizontal_space()])
+            .style(navbar)
+            .padding(16);
+
+        let summary = row![
+            summary_card("Shinies", statistics.shiny_count.to_string()),
+            summary_card(
+                "Recherches en cours",
+                format!(
+                    "{} / {}",
+                    statistics.active_hunt_count, statistics.hunt_count
+                )
+            ),
+            summary_card("Rencontres", statistics.total_encounters.to_string()),
+            summary_card(
+                "Rencontres par shiny (moyenne)",
+                format_mean(statistics.mean_encounters_per_shiny)
+            ),
+            summary_card(
+                "Rencontres par shiny (médiane)",
+                format_mean(statistics.median_encounters_per_shiny)
+            ),
+        ]
+        .spacing(16);
+
+        let breakdown_rows = Column::with_children(
+            std::iter::once(
+                row![
+                    text(self.breakdown.to_string()).width(Length::Fill),
+                    text("Shinies").width(Length::Fill),
+                    text("Rencontres").width(Length::Fill),
+                    text("Moyenne").width(Length::Fill),
+                ]
+                .spacing(8)
+                .into(),
+            )
+            .chain(self.breakdowns().iter().map(|breakdown| {
+                row![
+                    text(breakdown.label.clone()).size(16).width(Length::Fill),
+                    text(breakdown.shiny_count).size(16).width(Length::Fill),
+                    text(breakdown.total_encounters)
+                        .size(16)
+                        .width(Length::Fill),
+                    text(format_mean(breakdown.mean_encounters))
+                        .size(16)
+                        .width(Length::Fill),
+                ]
+                .spacing(8)
+                .into()
+            })),
+        )
+        .spacing(4);
+        let breakdown = section(
+            "Répartition des shinies",
+            column![
+                row![
+                    text("Par : "),
+                    pick_list(
+                        &BreakdownKind::ALL[..],
+                        Some(self.breakdown),
+                        StatsMessage::SelectBreakdown
+                    ),
+                ]
+                .spacing(8),
+                breakdown_rows,
+            ]
+            .spacing(12),
+        );
+
+        let records = section(
+            "Recherches terminées",
+            match (&statistics.longest_hunt, &statistics.shortest_hunt) {
+                (Some(longest), Some(shortest)) => column![
+                    row![text("Plus longue :").size(16), hunt_row(longest)].spacing(8),
+                    row![text("Plus courte :").size(16), hunt_row(shortest)].spacing(8),
+                ]
+                .spacing(4),
+                _ => column![text("Aucune recherche terminée").size(16)],
+            },
+        );
+
+        let running = section(
+            format!(
+                "Recherches en cours ({}), par temps estimé jusqu'au shiny",
+                statistics.running_hunts.len()
+            ),
+            if statistics.running_hunts.is_empty() {
+                column![text("Aucune recherche en cours").size(16)]
+            } else {
+                Column::with_children(
+                    statistics
+                        .running_hunts
+                        .iter()
+                        .map(|hunt| hunt_row(hunt).into()),
+                )
+                .spacing(4)
+            },
+        );
+
+        let max_count = statistics
+            .luck_histogram
+            .iter()
+            .max()
+            .copied()
+            .unwrap_or(0)
+            .max(1);
+        let histogram = row(statistics
+            .luck_histogram
+            .iter()
+            .enumerate()
+            .map(|(i, count)| {
+                column![
+                    text(*count).size(14),
+                    container(horizontal_space())
+                        .width(Length::Fill)
+                        .height(120.0 * *count as f32 / max_count as f32)
+                        .style(chart_bar),
+                    text(format!("{}-{} %", i * 10, i * 10 + 10)).size(12),
+                ]
+                .spacing(4)
+                .width(Length::Fill)
+                .align_x(Horizontal::Center)
+                .into()
+            }))
+        .spacing(8)
+        .height(180)
+        .align_y(Vertical::Bottom);
+        let luck = section(
+            "Chance",
+            column![
+                text(match statistics.luck_score {
+                    Some(score) => format!(
+                        "Score de chance : {score:.0}/100 ({})",
+                        if score >= 50.0 {
+                            "plus chanceux que la moyenne"
+                        } else {
+                            "moins chanceux que la moyenne"
+                        }
+                    ),
+                    None => "Aucun shiny avec un nombre de rencontres connu".into(),
+                })
+                .size(16),
+                text("Shinies par centile de rencontres (les plus chanceux à gauche)").size(14),
+                histogram,
+            ]
+            .spacing(12),
+        );
+
+        let activity = row![
+            section(
+                "Rencontres par jour (30 derniers jours)",
+                canvas(BarChart::encounters_per_day(&state.all_hunts, 30))
+                    .width(Length::Fill)
+                    .height(200),
+            ),
+            section(
+                "Shinies par mois (12 derniers mois)",
+                canvas(BarChart::shinies_per_month(&state.all_shinies, 12))
+                    .width(Length::Fill)
+                    .height(200),
+            ),
+        ]
+        .spacing(24);
+
+        let day_details = self.selected_day.map(|day| {
+            let hunts = state.all_hunts.iter().filter_map(|hunt| {
+                let encounters: i32 = hunt
+                    .sessions
+                    .iter()
+                    .filter(|session| session.start_time.date_naive() == day)
+                    .map(|session| session.encounters)
+                    .sum();
+                let found_shiny = hunt.shinies.iter().any(|shiny| {
+                    shiny
+                        .found_time
+                        .is_some_and(|time| time.date_naive() == day)
+                });
+                let worked_on = found_shiny
+                    || hunt
+                        .sessions
+                        .iter()
+                        .any(|session| session.start_time.date_naive() == day);
+                worked_on.then(|| {
+                    text(format!("{} : {encounters} rencontres", hunt.targets_name()))
+                        .size(16)
+                        .into()
+                })
+            });
+            let shinies = state
+                .all_shinies
+                .iter()
+                .filter(|shiny| {
+                    shiny
+                        .found_time
+                        .is_some_and(|time| time.date_naive() == day)
+                })
+                .map(|shiny| {
+                    text(format!(
+                        "Shiny trouvé : {}",
+                        shiny.name.clone().unwrap_or(shiny.species.to_string())
+                    ))
+                    .size(16)
+                    .into()
+                });
+            let lines: Vec<Element<StatsMessage>> = hunts.chain(shinies).collect();
+            column![text(
+                day.format_localized("%A %-d %B %Y", chrono::Locale::fr_FR)
+                    .to_string()
+            )
+            .size(18)]
+            .push(if lines.is_empty() {
+                Element::from(text("Aucune activité").size(16))
+            } else {
+                Column::with_children(lines).spacing(4).into()
+            })
+            .spacing(8)
+        });
+        let calendar = section(
+            "Activité de l'année",
+            column![
+                canvas(CalendarHeatmap::new(
+                    &state.all_hunts,
+                    &state.all_shinies,
+                    self.selected_day,
+                    StatsMessage::SelectDay
+                ))
+                .width(Length::Fill)
+                .height(150),
+                text("Cliquez sur un jour pour voir les recherches et shinies correspondants")
+                    .size(14),
+            ]
+            .push_maybe(day_details)
+            .spacing(12),
+        );
+
+        let now = Local::now();
+        let all_goals = state.settings.goals.all();
+        let no_goals = all_goals.is_empty();
+        let goal_rows = all_goals.into_iter().map(|(goal, target)| {
+            let name = match goal.hunt_id {
+                None => "Toutes les recherches".to_string(),
+                Some(hunt_id) => state
+                    .all_hunts
+                    .iter()
+                    .find(|hunt| hunt.id == Some(hunt_id))
+                    .map(|hunt| hunt.targets_name())
+                    .unwrap_or_default(),
+            };
+            let history = goal.history(target, &state.all_hunts, &state.active_counters, now);
+            let met_count = history.iter().filter(|result| result.met).count();
+            // Last month of days, or last quarter of weeks
+            let recent = match goal.period {
+                GoalPeriod::Day => 30,
+                GoalPeriod::Week => 13,
+            };
+            let squares = row(history
+                .iter()
+                .skip(history.len().saturating_sub(recent))
+                .map(|result| {
+                    container(horizontal_space())
+                        .width(16)
+                        .height(16)
+                        .style(if result.met { goal_met } else { goal_missed })
+                        .into()
+                }))
+            .spacing(4);
+            column![
+                text(format!(
+                    "{name} · {} : {} {}",
+                    goal.period, target.target, goal.measure
+                ))
+                .size(16),
+                text(format!(
+                    "Série actuelle : {} · Record : {} · Atteint {met_count} fois sur {}",
+                    goals::current_streak(&history),
+                    goals::best_streak(&history),
+                    history.len()
+                ))
+                .size(14),
+                squares,
+            ]
+            .spacing(4)
+            .into()
+        });
+        let goals = section(
+            "Objectifs",
+            if no_goals {
+                column![text("Aucun objectif, ajoutez-en depuis l'écran des compteurs").size(16)]
+            } else {
+                Column::with_children(goal_rows).spacing(16)
+            },
+        );
+
+        column![
+            header,
+            scrollable(
+                column![summary, calendar, activity, goals, breakdown, luck, records, running]
+                    .spacing(24)
+                    .padding(40)
+            )
+        ]
+        .into()
+    }
+}
@@ -0,0 +1,134 @@
+use crate::report::{ReportFormat, ReportKind, ReportTemplates};
+use crate::theme::card;
+
+use iced::widget::{
+    button, column, container, horizontal_space, pick_list, row, text, text_editor,
+};
+use iced::{Element, Length};
+
+#[derive(Debug, Clone)]
+pub enum TemplatesMessage {
+    SelectKind(ReportKind),
+    SelectFormat(ReportFormat),
+    Edit(text_editor::Action),
+    ResetTemplate,
+    StopEditTemplates(bool),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum TemplatesAction {
+    None,
+    StopEditTemplates(bool),
+}
+
+pub struct TemplatesEditor {
+    kind: ReportKind,
+    format: ReportFormat,
+    content: text_editor::Content,
+    pub templates: ReportTemplates,
+}
+
+impl TemplatesEditor {
+    pub fn new(templates: ReportTemplates) -> Self {
+        let kind = ReportKind::Shiny;
+        let format = ReportFormat::Markdown;
+        Self {
+            kind,
+            format,
+            content: text_editor::Content::with_text(templates.get(kind, format)),
+            templates,
+        }
+    }
+
+    fn store_content(&mut self) {
+        let mut template = self.content.text();
+        // The editor always ends its content with a line break
+        if template.ends_with('\n') {
+            template.pop();
+        }
+        self.templates.set(self.kind, self.format, template);
+    }
+
+    fn load_content(&mut self) {
+        self.content = text_editor::Content::with_text(self.templates.get(self.kind, self.format));
+    }
+
+    pub fn update(&mut self, message: TemplatesMessage) -> TemplatesAction {
+        match message {
+            TemplatesMessage::SelectKind(kind) => {
+                self.store_content();
+                self.kind = kind;
+                self.load_content();
+                TemplatesAction::None
+            }
+            TemplatesMessage::SelectFormat(format) => {
+                self.store_content();
+                self.format = format;
+                self.load_content();
+                TemplatesAction::None
+            }
+            TemplatesMessage::Edit(action) => {
+                self.content.perform(action);
+                TemplatesAction::None
+            }
+            TemplatesMessage::ResetTemplate => {
+                self.templates.reset(self.kind, self.format);
+                self.load_content();
+                TemplatesAction::None
+            }
+            TemplatesMessage::StopEditTemplates(save) => {
+                if save {
+                    self.store_content();
+                }
+                TemplatesAction::StopEditTemplates(save)
+            }
+        }
+    }
+
+    pub fn view(&self) -> Element<'_, TemplatesMessage> {
+        container(
+            column![
+                container(text("Modèles de rapport")).center_x(Length::Fill),
+                row![
+                    pick_list(
+                        &ReportKind::ALL[..],
+                        Some(self.kind),
+                        TemplatesMessage::SelectKind
+                    ),
+                    pick_list(
+                        &ReportFormat::ALL[..],
+                        Some(self.format),
+                        TemplatesMessage::SelectFormat
+                    ),
+                    horizontal_space(),
+                    button("Par défaut").on_press(TemplatesMessage::ResetTemplate),
+                ]
+                .spacing(8),
+                text_editor(&self.content)
+                    .height(240)
+                    .on_action(TemplatesMessage::Edit),
+                text(format!(
+                    "Champs disponibles : {}",
+                    self.kind
+                        .placeholders()
+                        .iter()
+                        .map(|placeholder| format!("{{{placeholder}}}"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ))
+                .size(14),
+                row![
+                    horizontal_space(),
+                    button("Enregistrer").on_press(TemplatesMessage::StopEditTemplates(true)),
+                    button("Annuler").on_press(TemplatesMessage::StopEditTemplates(false))
+                ]
+                .spacing(8),
+            ]
+            .spacing(8)
+            .padding(32)
+            .width(640),
+        )
+        .style(card)
+        .into()
+    }
+}
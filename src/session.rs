@@ -0,0 +1,211 @@
+use crate::models::Session as DbSession;
+use crate::schema::sessions;
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeDelta, TimeZone};
+use diesel::prelude::*;
+
+use std::collections::HashMap;
+
+/// A period of time actively spent on a hunt
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub id: Option<i32>,
+    pub hunt_id: i32,
+    pub phase: i32,
+    pub start_time: DateTime<Local>,
+    pub end_time: DateTime<Local>,
+    pub encounters: i32,
+}
+
+#[derive(Debug, AsChangeset, Identifiable, Insertable)]
+#[diesel(table_name = crate::schema::sessions)]
+pub struct InsertableSession {
+    pub id: Option<i32>,
+    pub hunt_id: i32,
+    pub phase: i32,
+    pub start_time: NaiveDateTime,
+    pub end_time: NaiveDateTime,
+    pub encounters: i32,
+}
+
+/// Local time of a stored time. Times repeated when clocks go back are taken
+/// at their first occurrence. Times skipped when they go forward can only come
+/// from a change of time zone and are read as UTC.
+fn local_time(naive: NaiveDateTime) -> DateTime<Local> {
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .unwrap_or_else(|| Local.from_utc_datetime(&naive))
+}
+
+impl Session {
+    pub fn from_db_session(db_session: DbSession) -> Self {
+        Self {
+            id: Some(db_session.id),
+            hunt_id: db_session.hunt_id,
+            phase: db_session.phase,
+            start_time: local_time(db_session.start_time),
+            end_time: local_time(db_session.end_time),
+            encounters: db_session.encounters,
+        }
+    }
+
+    pub fn copy_into_insertable(&self) -> InsertableSession {
+        InsertableSession {
+            id: self.id,
+            hunt_id: self.hunt_id,
+            phase: self.phase,
+            start_time: self.start_time.naive_local(),
+            end_time: self.end_time.naive_local(),
+            encounters: self.encounters,
+        }
+    }
+
+    pub fn upsert(&self, db: &mut SqliteConnection) -> QueryResult<DbSession> {
+        let insertable = self.copy_into_insertable();
+        diesel::insert_into(sessions::table)
+            .values(&insertable)
+            .on_conflict(sessions::id)
+            .do_update()
+            .set(&insertable)
+            .get_result(db)
+    }
+
+    pub fn duration(&self) -> TimeDelta {
+        self.end_time - self.start_time
+    }
+}
+
+/// Tracks the hunting time of a counter. Finished sessions are handed over
+/// to the caller, which stores them with the hunt of the counter.
+#[derive(Debug, Default, Clone)]
+pub struct SessionTimer {
+    start: Option<DateTime<Local>>,
+    last_activity: Option<DateTime<Local>>,
+    encounters: i32,
+    /// Time and encounters of the finished sessions since the app started,
+    /// for counters that are not linked to a hunt
+    elapsed: TimeDelta,
+    elapsed_encounters: i32,
+}
+
+/// A finished session: start, end and encounters
+pub type FinishedSession = (DateTime<Local>, DateTime<Local>, i32);
+
+impl SessionTimer {
+    pub fn is_running(&self) -> bool {
+        self.start.is_some()
+    }
+
+    pub fn start(&mut self, now: DateTime<Local>) {
+        if self.start.is_none() {
+            self.start = Some(now);
+            self.last_activity = Some(now);
+            self.encounters = 0;
+        }
+    }
+
+    /// Counts encounters in the current session, starting one when the timer
+    /// is paused so that every encounter is dated.
+    pub fn record_encounters(&mut self, encounters: i32, now: DateTime<Local>) {
+        if encounters > 0 {
+            self.start(now);
+        }
+        if self.is_running() {
+            self.encounters += encounters;
+            self.last_activity = Some(now);
+        }
+    }
+
+    /// Stops the timer, returning the session that just ended
+    pub fn pause(&mut self, end: DateTime<Local>) -> Option<FinishedSession> {
+        let start = self.start.take()?;
+        let end = end.max(start);
+        self.elapsed += end - start;
+        self.elapsed_encounters += self.encounters;
+        Some((start, end, std::mem::take(&mut self.encounters)))
+    }
+
+    /// Pauses the timer when nothing happened for `idle`, ending the session
+    /// at the last activity so that idle time isn't counted.
+    pub fn auto_pause(&mut self, now: DateTime<Local>, idle: TimeDelta) -> Option<FinishedSession> {
+        let last_activity = self.last_activity?;
+        if self.is_running() && now - last_activity >= idle {
+            self.pause(last_activity)
+        } else {
+            None
+        }
+    }
+
+    /// Time and encounters of the running session
+    pub fn current(&self, now: DateTime<Local>) -> (TimeDelta, i32) {
+        match self.start {
+            Some(start) => (now - start, self.encounters),
+            None => (TimeDelta::zero(), 0),
+        }
+    }
+
+    /// Time and encounters of every session of this timer
+    pub fn total(&self, now: DateTime<Local>) -> (TimeDelta, i32) {
+        let (time, encounters) = self.current(now);
+        (self.elapsed + time, self.elapsed_encounters + encounters)
+    }
+}
+
+/// Encounters per hour, when enough time was spent to make it meaningful
+pub fn encounter_rate(duration: TimeDelta, encounters: i32) -> Option<f64> {
+    let hours = duration.num_seconds() as f64 / 3600.0;
+    (duration.num_seconds() >= 60).then(|| encounters as f64 / hours)
+}
+
+pub fn format_duration(duration: TimeDelta) -> String {
+    let seconds = duration.num_seconds().max(0);
+    match (seconds / 3600, seconds / 60 % 60, seconds % 60) {
+        (0, 0, s) => format!("{s} s"),
+        (0, m, s) => format!("{m} min {s:02} s"),
+        (h, m, _) => format!("{h} h {m:02} min"),
+    }
+}
+
+/// Settings shared by the timers of every counter
+#[derive(Debug, Clone)]
+pub struct TimerSettings {
+    /// Minutes without any encounter after which a timer pauses itself
+    pub idle_minutes: i64,
+}
+
+impl Default for TimerSettings {
+    fn default() -> Self {
+        Self { idle_minutes: 5 }
+    }
+}
+
+impl TimerSettings {
+    const IDLE_MINUTES_KEY: &'static str = "timer.idle_minutes";
+
+    pub fn from_values(values: &HashMap<String, String>) -> Self {
+        let mut settings = Self::default();
+        if let Some(idle_minutes) = values
+            .get(Self::IDLE_MINUTES_KEY)
+            .and_then(|value| value.parse().ok())
+        {
+            settings.idle_minutes = idle_minutes;
+        }
+        settings
+    }
+
+    pub fn to_values(&self) -> Vec<(String, String)> {
+        if self.idle_minutes == Self::default().idle_minutes {
+            vec![]
+        } else {
+            vec![(
+                Self::IDLE_MINUTES_KEY.to_string(),
+                self.idle_minutes.to_string(),
+            )]
+        }
+    }
+
+    pub fn idle_delay(&self) -> TimeDelta {
+        TimeDelta::minutes(self.idle_minutes)
+    }
+}
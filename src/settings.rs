@@ -0,0 +1,99 @@
+use crate::filters::GridFilters;
+use crate::goals::Goals;
+use crate::keybindings::CounterBindings;
+use crate::models::Setting;
+use crate::report::ReportTemplates;
+use crate::schema::settings;
+use crate::session::TimerSettings;
+use crate::sprites::SpriteSettings;
+
+use diesel::prelude::*;
+
+use std::collections::HashMap;
+use std::error::Error;
+
+#[derive(Debug, Default, Clone)]
+pub struct Settings {
+    pub report_templates: ReportTemplates,
+    pub counter_bindings: CounterBindings,
+    pub timer: TimerSettings,
+    pub goals: Goals,
+    pub sprites: SpriteSettings,
+    pub filters: GridFilters,
+}
+
+impl Settings {
+    pub fn load(db: &mut SqliteConnection) -> Result<Settings, Box<dyn Error + Send + Sync>> {
+        let values: HashMap<String, String> = settings::table
+            .select(Setting::as_select())
+            .load(db)?
+            .into_iter()
+            .map(|setting| (setting.key, setting.value))
+            .collect();
+
+        Ok(Settings {
+            report_templates: ReportTemplates::from_values(&values),
+            counter_bindings: CounterBindings::from_values(&values),
+            timer: TimerSettings::from_values(&values),
+            goals: Goals::from_values(&values),
+            sprites: SpriteSettings::from_values(&values),
+            filters: GridFilters::from_values(&values),
+        })
+    }
+
+    /// Writes every setting back to the database, removing the ones that were
+    /// reset to their default value.
+    pub fn save(&self, db: &mut SqliteConnection) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let values = self
+            .report_templates
+            .to_values()
+            .into_iter()
+            .chain(self.counter_bindings.to_values())
+            .chain(self.timer.to_values())
+            .chain(self.goals.to_values())
+            .chain(self.sprites.to_values())
+            .chain(self.filters.to_values());
+
+        db.transaction(|db| {
+            diesel::delete(settings::table).execute(db)?;
+            for (key, value) in values {
+                let setting = Setting { key, value };
+                diesel::insert_into(settings::table)
+                    .values(&setting)
+                    .on_conflict(settings::key)
+                    .do_update()
+                    .set(&setting)
+                    .execute(db)?;
+            }
+            Ok::<(), diesel::result::Error>(())
+        })?;
+
+        Ok(())
+    }
+
+    /// Writes the filters of the grids back to the database, without
+    /// rewriting the other settings
+    pub fn save_filters(
+        &self,
+        db: &mut SqliteConnection,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        db.transaction(|db| {
+            diesel::delete(
+                settings::table.filter(settings::key.like(format!("{}%", GridFilters::KEY_PREFIX))),
+            )
+            .execute(db)?;
+            let values: Vec<Setting> = self
+                .filters
+                .to_values()
+                .into_iter()
+                .map(|(key, value)| Setting { key, value })
+                .collect();
+            diesel::insert_into(settings::table)
+                .values(&values)
+                .execute(db)?;
+            Ok::<(), diesel::result::Error>(())
+        })?;
+
+        Ok(())
+    }
+}
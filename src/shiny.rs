@@ -1,8 +1,11 @@
 use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
 use diesel::prelude::*;
+use uuid::Uuid;
 
 use crate::data;
+use crate::odds;
 use crate::schema::shinies;
+use crate::sprites::SpriteKey;
 
 use std::error::Error;
 
@@ -11,6 +14,7 @@ pub use crate::models::Shiny as DbShiny;
 #[derive(Debug, Default, Clone)]
 pub struct Shiny {
     pub id: Option<i32>,
+    pub uuid: String,
     pub species: data::Species,
     pub gender: Option<i32>,
     pub name: Option<String>,
@@ -25,10 +29,28 @@ pub struct Shiny {
     pub hunt_id: Option<i32>,
 }
 
+#[derive(Debug, Clone)]
+pub enum ShinyEditAction {
+    SetSpecies(data::Species),
+    SetGender(Option<i32>),
+    SetName(Option<String>),
+    SetTotalEncounters(Option<i32>),
+    SetPhaseEncounters(Option<i32>),
+    SetPhaseNumber(Option<i32>),
+    SetFoundTime(Option<DateTime<Local>>),
+    SetVersion(Option<String>),
+    SetMethod(Option<String>),
+    SetPlace(Option<String>),
+    SetNotes(Option<String>),
+    SetHunt(Option<i32>),
+}
+
+// Fields cleared in the editor must be cleared in the database too
 #[derive(Debug, AsChangeset, Identifiable, Insertable)]
-#[diesel(table_name = crate::schema::shinies)]
+#[diesel(table_name = crate::schema::shinies, treat_none_as_null = true)]
 pub struct InsertableShiny {
     pub id: Option<i32>,
+    pub uuid: String,
     pub species: i32,
     pub gender: Option<i32>,
     pub name: Option<String>,
@@ -47,6 +69,7 @@ impl Shiny {
     pub fn from_db_shiny(db_shiny: DbShiny) -> Self {
         Self {
             id: Some(db_shiny.id),
+            uuid: db_shiny.uuid,
             species: db_shiny.species.into(),
             gender: db_shiny.gender,
             name: db_shiny.name,
@@ -67,6 +90,11 @@ impl Shiny {
     pub fn copy_into_insertable(&self) -> InsertableShiny {
         InsertableShiny {
             id: self.id,
+            uuid: if self.uuid.is_empty() {
+                Uuid::new_v4().simple().to_string()
+            } else {
+                self.uuid.clone()
+            },
             species: self.species.into(),
             gender: self.gender,
             name: self.name.clone(),
@@ -82,12 +110,55 @@ impl Shiny {
         }
     }
 
+    pub fn perform(&mut self, action: ShinyEditAction) {
+        match action {
+            ShinyEditAction::SetSpecies(species) => self.species = species,
+            ShinyEditAction::SetGender(gender) => self.gender = gender,
+            ShinyEditAction::SetName(name) => self.name = name,
+            ShinyEditAction::SetTotalEncounters(count) => self.total_encounters = count,
+            ShinyEditAction::SetPhaseEncounters(count) => self.phase_encounters = count,
+            ShinyEditAction::SetPhaseNumber(number) => self.phase_number = number,
+            ShinyEditAction::SetFoundTime(time) => self.found_time = time,
+            ShinyEditAction::SetVersion(version) => self.version = version,
+            ShinyEditAction::SetMethod(method) => self.method = method,
+            ShinyEditAction::SetPlace(place) => self.place = place,
+            ShinyEditAction::SetNotes(notes) => self.notes = notes,
+            ShinyEditAction::SetHunt(hunt_id) => self.hunt_id = hunt_id,
+        }
+    }
+
+    /// Shiny sprite of the species, in the form and game it was found in
+    pub fn sprite_key(&self) -> SpriteKey {
+        SpriteKey::new(self.species, true, self.version.as_deref()).with_gender(self.gender)
+    }
+
+    /// How lucky finding this shiny was, from the encounters of its phase
+    pub fn luck_percentile(&self) -> Option<f64> {
+        let encounters = self.phase_encounters.or(self.total_encounters)?;
+        (encounters > 0).then(|| {
+            odds::luck_percentile(
+                odds::method_odds(self.version.as_deref(), self.method.as_deref()),
+                encounters,
+            )
+        })
+    }
+
+    pub fn upsert(&self, db: &mut SqliteConnection) -> QueryResult<DbShiny> {
+        let insertable = self.copy_into_insertable();
+        diesel::insert_into(shinies::table)
+            .values(&insertable)
+            .on_conflict(shinies::id)
+            .do_update()
+            .set(&insertable)
+            .get_result(db)
+    }
+
     pub fn get_all(db: &mut SqliteConnection) -> Result<Vec<Shiny>, Box<dyn Error + Send + Sync>> {
         Ok(shinies::table
             .select(DbShiny::as_select())
             .load(db)?
             .into_iter()
-            .map(|shiny| Shiny::from_db_shiny(shiny))
+            .map(Shiny::from_db_shiny)
             .collect())
     }
 
@@ -103,3 +174,14 @@ impl Shiny {
         Ok(Shiny::from_db_shiny(db_shiny))
     }
 }
+
+impl std::fmt::Display for Shiny {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} - {}",
+            self.name.clone().unwrap_or(self.species.to_string()),
+            self.place.clone().unwrap_or("Inconnue".into())
+        )
+    }
+}
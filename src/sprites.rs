@@ -0,0 +1,198 @@
+use crate::data::Species;
+use crate::odds::GameVersion;
+
+use iced::widget::{image, svg};
+use iced::{Element, Length};
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const SILHOUETTE: &[u8] = include_bytes!("../assets/silhouette.svg");
+
+/// Image formats of the sprites, in the order they are looked for. Only the
+/// first frame of animated GIFs is shown.
+const EXTENSIONS: [&str; 3] = ["png", "gif", "svg"];
+
+/// Which sprites of the sprite folder to show
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SpriteStyle {
+    /// The same sprites everywhere, from the `home` folder
+    #[default]
+    Home,
+    /// The sprites of the game of the hunt or shiny, from the folder named
+    /// after the game, falling back to the `home` folder
+    Game,
+}
+
+impl SpriteStyle {
+    pub const ALL: [SpriteStyle; 2] = [SpriteStyle::Home, SpriteStyle::Game];
+
+    fn key(&self) -> &'static str {
+        match self {
+            SpriteStyle::Home => "home",
+            SpriteStyle::Game => "game",
+        }
+    }
+}
+
+impl std::fmt::Display for SpriteStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            SpriteStyle::Home => "Style HOME",
+            SpriteStyle::Game => "Sprites du jeu",
+        })
+    }
+}
+
+/// Where sprites are loaded from. The folder holds a `home` folder and one
+/// folder per game (`scarlet`, `lets-go-pikachu`…), each with a `normal` and
+/// a `shiny` folder of images named after the national dex number, such as
+/// `0025.png`, or `0025-f.png` for the female form.
+#[derive(Debug, Default, Clone)]
+pub struct SpriteSettings {
+    pub folder: Option<PathBuf>,
+    pub style: SpriteStyle,
+}
+
+impl SpriteSettings {
+    const FOLDER_KEY: &'static str = "sprites.folder";
+    const STYLE_KEY: &'static str = "sprites.style";
+
+    pub fn from_values(values: &HashMap<String, String>) -> Self {
+        Self {
+            folder: values.get(Self::FOLDER_KEY).map(PathBuf::from),
+            style: values
+                .get(Self::STYLE_KEY)
+                .and_then(|key| {
+                    SpriteStyle::ALL
+                        .into_iter()
+                        .find(|style| style.key() == key)
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn to_values(&self) -> Vec<(String, String)> {
+        let mut values = vec![];
+        if let Some(folder) = &self.folder {
+            values.push((
+                Self::FOLDER_KEY.to_string(),
+                folder.to_string_lossy().into_owned(),
+            ));
+        }
+        if self.style != SpriteStyle::default() {
+            values.push((Self::STYLE_KEY.to_string(), self.style.key().to_string()));
+        }
+        values
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SpriteKey {
+    pub species: Species,
+    /// Suffix of the file name of the form, such as `f` for females
+    pub form: Option<&'static str>,
+    pub shiny: bool,
+    pub game: Option<GameVersion>,
+}
+
+impl SpriteKey {
+    pub fn new(species: Species, shiny: bool, version: Option<&str>) -> Self {
+        Self {
+            species,
+            form: None,
+            shiny,
+            game: version.and_then(GameVersion::from_name),
+        }
+    }
+
+    /// Female form, for species whose females look different
+    pub fn with_gender(self, gender: Option<i32>) -> Self {
+        Self {
+            form: (gender == Some(0)).then_some("f"),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Sprite {
+    Image(image::Handle),
+    Svg(svg::Handle),
+}
+
+/// Sprites found in the sprite folder. Handles are kept so the folder is
+/// only searched once per sprite, and so iced decodes each image once.
+#[derive(Debug, Default)]
+pub struct SpriteCache {
+    sprites: RefCell<HashMap<SpriteKey, Option<Sprite>>>,
+}
+
+impl SpriteCache {
+    /// Forgets every sprite, after the sprite settings changed
+    pub fn clear(&self) {
+        self.sprites.borrow_mut().clear();
+    }
+
+    fn find(settings: &SpriteSettings, key: &SpriteKey) -> Option<Sprite> {
+        let folder = settings.folder.as_ref()?;
+        let game_folder = key
+            .game
+            .filter(|_| settings.style == SpriteStyle::Game)
+            .map(|game| game.folder_name());
+        let variant = if key.shiny { "shiny" } else { "normal" };
+        let number = i32::from(key.species);
+        let names: Vec<String> = key
+            .form
+            .into_iter()
+            .flat_map(|form| [format!("{number:04}-{form}"), format!("{number}-{form}")])
+            .chain([format!("{number:04}"), number.to_string()])
+            .collect();
+
+        let mut candidates = vec![];
+        for style_folder in game_folder.into_iter().chain(["home".to_string()]) {
+            let variant_folder = folder.join(style_folder).join(variant);
+            for name in &names {
+                for extension in EXTENSIONS {
+                    candidates.push(variant_folder.join(format!("{name}.{extension}")));
+                }
+            }
+        }
+        candidates
+            .into_iter()
+            .find(|path| path.is_file())
+            .map(|path| Self::load(&path))
+    }
+
+    fn load(path: &Path) -> Sprite {
+        if path.extension().is_some_and(|extension| extension == "svg") {
+            Sprite::Svg(svg::Handle::from_path(path))
+        } else {
+            Sprite::Image(image::Handle::from_path(path))
+        }
+    }
+
+    /// The sprite, or a silhouette when the sprite folder doesn't have it
+    pub fn view<'a, Message: 'a>(
+        &self,
+        settings: &SpriteSettings,
+        key: SpriteKey,
+        size: impl Into<Length> + Copy,
+    ) -> Element<'a, Message> {
+        let sprite = self
+            .sprites
+            .borrow_mut()
+            .entry(key)
+            .or_insert_with(|| Self::find(settings, &key))
+            .clone();
+        match sprite {
+            Some(Sprite::Image(handle)) => image(handle).width(size).height(size).into(),
+            Some(Sprite::Svg(handle)) => svg(handle).width(size).height(size).into(),
+            None => svg(svg::Handle::from_memory(SILHOUETTE))
+                .width(size)
+                .height(size)
+                .into(),
+        }
+    }
+}
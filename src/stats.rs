@@ -0,0 +1,297 @@
+use crate::hunt::Hunt;
+use crate::odds::GameVersion;
+use crate::shiny::Shiny;
+
+use chrono::Datelike;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct Statistics {
+    pub shiny_count: usize,
+    pub hunt_count: usize,
+    pub active_hunt_count: usize,
+    pub total_encounters: i64,
+    pub mean_encounters_per_shiny: Option<f64>,
+    pub median_encounters_per_shiny: Option<f64>,
+    pub by_version: Vec<Breakdown>,
+    pub by_method: Vec<Breakdown>,
+    pub by_place: Vec<Breakdown>,
+    pub by_year: Vec<Breakdown>,
+    pub longest_hunt: Option<HuntSummary>,
+    pub shortest_hunt: Option<HuntSummary>,
+    pub running_hunts: Vec<HuntSummary>,
+    /// Average share of hunts that would have taken longer than ours, in
+    /// percent: above 50, we were lucky overall
+    pub luck_score: Option<f64>,
+    /// Number of shinies by luck percentile, in tenths
+    pub luck_histogram: [usize; 10],
+}
+
+/// Shinies sharing a version, method, place or year
+#[derive(Debug, Clone, Serialize)]
+pub struct Breakdown {
+    pub label: String,
+    pub shiny_count: usize,
+    pub total_encounters: i64,
+    pub mean_encounters: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HuntSummary {
+    pub id: Option<i32>,
+    pub name: String,
+    pub encounters: i32,
+    pub phase_count: i32,
+    /// Hours the shiny is expected to take, at the usual encounter rate
+    pub expected_hours: Option<f64>,
+}
+
+impl HuntSummary {
+    fn of(hunt: &Hunt) -> Self {
+        Self {
+            id: hunt.id,
+            name: hunt.targets_name(),
+            encounters: hunt.previous_encounters + hunt.phase_encounters,
+            phase_count: hunt.phase_count,
+            expected_hours: (!hunt.completed)
+                .then(|| hunt.expected_time())
+                .flatten()
+                .map(|time| time.num_seconds() as f64 / 3600.0),
+        }
+    }
+}
+
+fn median(sorted: &[i32]) -> Option<f64> {
+    match sorted.len() {
+        0 => None,
+        n if n % 2 == 0 => Some((sorted[n / 2 - 1] as f64 + sorted[n / 2] as f64) / 2.0),
+        n => Some(sorted[n / 2] as f64),
+    }
+}
+
+fn mean(values: &[i32]) -> Option<f64> {
+    if values.is_empty() {
+        None
+    } else {
+        Some(values.iter().map(|&c| c as f64).sum::<f64>() / values.len() as f64)
+    }
+}
+
+/// Groups the shinies by the label returned for each of them, the most
+/// common first. Labels are compared ignoring case, shinies without one are
+/// grouped as unknown.
+fn breakdown(shinies: &[Shiny], label: impl Fn(&Shiny) -> Option<String>) -> Vec<Breakdown> {
+    let mut groups: Vec<(String, Vec<&Shiny>)> = vec![];
+    for shiny in shinies {
+        let label = label(shiny)
+            .map(|label| label.trim().to_string())
+            .filter(|label| !label.is_empty())
+            .unwrap_or("Inconnue".into());
+        match groups
+            .iter_mut()
+            .find(|(group, _)| group.to_lowercase() == label.to_lowercase())
+        {
+            Some((_, group)) => group.push(shiny),
+            None => groups.push((label, vec![shiny])),
+        }
+    }
+
+    let mut breakdowns: Vec<Breakdown> = groups
+        .into_iter()
+        .map(|(label, shinies)| {
+            let encounters: Vec<i32> = shinies
+                .iter()
+                .filter_map(|shiny| shiny.total_encounters)
+                .collect();
+            Breakdown {
+                label,
+                shiny_count: shinies.len(),
+                total_encounters: encounters.iter().map(|&c| c as i64).sum(),
+                mean_encounters: mean(&encounters),
+            }
+        })
+        .collect();
+    breakdowns.sort_by(|a, b| {
+        b.shiny_count
+            .cmp(&a.shiny_count)
+            .then_with(|| a.label.cmp(&b.label))
+    });
+    breakdowns
+}
+
+impl Statistics {
+    pub fn compute(hunts: &[Hunt], shinies: &[Shiny]) -> Self {
+        // Encounters of shinies found outside of a recorded hunt are counted
+        // too, the others are already part of their hunt's count.
+        let total_encounters = hunts
+            .iter()
+            .map(|hunt| (hunt.previous_encounters + hunt.phase_encounters) as i64)
+            .sum::<i64>()
+            + shinies
+                .iter()
+                .filter(|shiny| shiny.hunt_id.is_none())
+                .filter_map(|shiny| shiny.total_encounters)
+                .map(|count| count as i64)
+                .sum::<i64>();
+
+        let mut encounters: Vec<i32> = shinies
+            .iter()
+            .filter_map(|shiny| shiny.total_encounters)
+            .collect();
+        encounters.sort_unstable();
+
+        let mut by_year = breakdown(shinies, |shiny| {
+            shiny.found_time.map(|time| time.year().to_string())
+        });
+        by_year.sort_by(|a, b| b.label.cmp(&a.label));
+
+        // Hunts closest to their shiny first, those without any timed
+        // session last
+        let mut running_hunts: Vec<HuntSummary> = hunts
+            .iter()
+            .filter(|hunt| !hunt.completed)
+            .map(HuntSummary::of)
+            .collect();
+        running_hunts.sort_by(|a, b| match (a.expected_hours, b.expected_hours) {
+            (Some(a), Some(b)) => a.total_cmp(&b),
+            (a, b) => b.is_some().cmp(&a.is_some()),
+        });
+
+        let percentiles: Vec<f64> = shinies.iter().filter_map(Shiny::luck_percentile).collect();
+        let mut luck_histogram = [0; 10];
+        for percentile in &percentiles {
+            luck_histogram[((percentile * 10.0) as usize).min(9)] += 1;
+        }
+
+        let completed_hunts = || hunts.iter().filter(|hunt| hunt.completed);
+        let total = |hunt: &&Hunt| hunt.previous_encounters + hunt.phase_encounters;
+
+        Statistics {
+            shiny_count: shinies.len(),
+            hunt_count: hunts.len(),
+            active_hunt_count: hunts.iter().filter(|hunt| !hunt.completed).count(),
+            total_encounters,
+            mean_encounters_per_shiny: mean(&encounters),
+            median_encounters_per_shiny: median(&encounters),
+            // Games are recognized whatever the language they were typed in
+            by_version: breakdown(shinies, |shiny| {
+                shiny.version.as_deref().map(|version| {
+                    GameVersion::from_name(version)
+                        .map(|version| version.to_string())
+                        .unwrap_or(version.to_string())
+                })
+            }),
+            by_method: breakdown(shinies, |shiny| shiny.method.clone()),
+            by_place: breakdown(shinies, |shiny| shiny.place.clone()),
+            by_year,
+            longest_hunt: completed_hunts().max_by_key(total).map(HuntSummary::of),
+            shortest_hunt: completed_hunts().min_by_key(total).map(HuntSummary::of),
+            running_hunts,
+            luck_score: (!percentiles.is_empty()).then(|| {
+                percentiles.iter().map(|p| (1.0 - p) * 100.0).sum::<f64>()
+                    / percentiles.len() as f64
+            }),
+            luck_histogram,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::Species;
+    use crate::session::Session;
+
+    use chrono::{Local, TimeDelta, TimeZone};
+
+    fn shiny(encounters: Option<i32>, hunt_id: Option<i32>, version: &str) -> Shiny {
+        Shiny {
+            species: Species::Pikachu,
+            total_encounters: encounters,
+            hunt_id,
+            version: Some(version.into()),
+            found_time: Some(Local.with_ymd_and_hms(2023, 8, 1, 12, 0, 0).unwrap()),
+            ..Shiny::default()
+        }
+    }
+
+    fn hunt(id: i32, encounters: i32, completed: bool) -> Hunt {
+        Hunt {
+            id: Some(id),
+            target: Species::Pikachu,
+            phase_encounters: encounters,
+            phase_count: 1,
+            completed,
+            ..Hunt::default()
+        }
+    }
+
+    #[test]
+    fn empty_collection() {
+        let stats = Statistics::compute(&[], &[]);
+        assert_eq!(stats.total_encounters, 0);
+        assert_eq!(stats.mean_encounters_per_shiny, None);
+        assert_eq!(stats.luck_score, None);
+        assert!(stats.longest_hunt.is_none());
+    }
+
+    #[test]
+    fn encounters_of_hunted_shinies_are_counted_once() {
+        let hunts = [hunt(1, 300, true), hunt(2, 50, false)];
+        let shinies = [
+            shiny(Some(300), Some(1), "Épée"),
+            shiny(Some(100), None, "sword"),
+            shiny(None, None, "Rubis"),
+        ];
+        let stats = Statistics::compute(&hunts, &shinies);
+        assert_eq!(stats.total_encounters, 450);
+        assert_eq!(stats.active_hunt_count, 1);
+        assert_eq!(stats.mean_encounters_per_shiny, Some(200.0));
+        assert_eq!(stats.median_encounters_per_shiny, Some(200.0));
+        assert_eq!(stats.longest_hunt.unwrap().id, Some(1));
+    }
+
+    #[test]
+    fn versions_are_grouped_whatever_the_language() {
+        let shinies = [
+            shiny(Some(300), None, "Épée"),
+            shiny(Some(100), None, "sword"),
+            shiny(Some(10), None, "Rubis"),
+        ];
+        let stats = Statistics::compute(&[], &shinies);
+        let versions: Vec<(&str, usize)> = stats
+            .by_version
+            .iter()
+            .map(|breakdown| (breakdown.label.as_str(), breakdown.shiny_count))
+            .collect();
+        assert_eq!(versions, vec![("Épée", 2), ("Rubis", 1)]);
+        assert_eq!(stats.by_version[0].mean_encounters, Some(200.0));
+        assert_eq!(stats.by_method[0].label, "Inconnue");
+        assert_eq!(stats.by_year[0].label, "2023");
+    }
+
+    #[test]
+    fn running_hunts_without_a_rate_come_last() {
+        let start = Local.with_ymd_and_hms(2024, 5, 1, 20, 0, 0).unwrap();
+        let mut timed = hunt(2, 10, false);
+        timed.sessions.push(Session {
+            id: None,
+            hunt_id: 2,
+            phase: 1,
+            start_time: start,
+            end_time: start + TimeDelta::hours(1),
+            encounters: 1024,
+        });
+        let mut idle = hunt(3, 0, false);
+        idle.sessions.push(Session {
+            encounters: 0,
+            hunt_id: 3,
+            ..timed.sessions[0].clone()
+        });
+        let stats = Statistics::compute(&[hunt(1, 0, false), idle, timed], &[]);
+        let order: Vec<Option<i32>> = stats.running_hunts.iter().map(|hunt| hunt.id).collect();
+        assert_eq!(order[0], Some(2));
+        assert_eq!(stats.running_hunts[0].expected_hours, Some(4.0));
+        assert_eq!(stats.running_hunts[1].expected_hours, None);
+    }
+}
@@ -26,6 +26,83 @@ pub fn card(_theme: &Theme) -> container::Style {
     }
 }
 
+pub fn focused_card(theme: &Theme) -> container::Style {
+    container::Style {
+        border: iced::Border {
+            color: Color::from_rgb8(137, 160, 209),
+            width: 3.0,
+            ..card(theme).border
+        },
+        ..card(theme)
+    }
+}
+
+pub fn alert_banner(_theme: &Theme) -> container::Style {
+    container::Style {
+        background: Some(Color::from_rgb8(137, 160, 209).into()),
+        text_color: Some(Color::from_rgb8(38, 44, 57)),
+        border: iced::Border {
+            radius: iced::border::Radius::from(8),
+            ..iced::Border::default()
+        },
+        ..container::Style::default()
+    }
+}
+
+pub fn chart_bar(_theme: &Theme) -> container::Style {
+    container::Style {
+        background: Some(Color::from_rgb8(137, 160, 209).into()),
+        border: iced::Border {
+            radius: iced::border::Radius::from(4),
+            ..iced::Border::default()
+        },
+        ..container::Style::default()
+    }
+}
+
+pub fn dex_owned(theme: &Theme) -> container::Style {
+    container::Style {
+        background: Some(Color::from_rgb8(21, 100, 35).into()),
+        ..card(theme)
+    }
+}
+
+pub fn dex_hunting(theme: &Theme) -> container::Style {
+    container::Style {
+        background: Some(Color::from_rgb8(89, 105, 139).into()),
+        ..card(theme)
+    }
+}
+
+pub fn dex_missing(theme: &Theme) -> container::Style {
+    container::Style {
+        text_color: Some(Color::from_rgba8(240, 240, 240, 0.5)),
+        ..card(theme)
+    }
+}
+
+pub fn goal_met(_theme: &Theme) -> container::Style {
+    container::Style {
+        background: Some(Color::from_rgb8(21, 100, 35).into()),
+        border: iced::Border {
+            radius: iced::border::Radius::from(2),
+            ..iced::Border::default()
+        },
+        ..container::Style::default()
+    }
+}
+
+pub fn goal_missed(_theme: &Theme) -> container::Style {
+    container::Style {
+        background: Some(Color::from_rgb8(74, 82, 99).into()),
+        border: iced::Border {
+            radius: iced::border::Radius::from(2),
+            ..iced::Border::default()
+        },
+        ..container::Style::default()
+    }
+}
+
 pub fn side_view(_theme: &Theme) -> container::Style {
     container::Style {
         background: Some(Color::from_rgb8(47, 54, 69).into()),